@@ -0,0 +1,82 @@
+// This file is part of Rubik.
+// Copyright Peter Beard, licensed under the GPLv3. See LICENSE for details.
+//
+//! Fixed inputs for benchmarks. Everything here is deterministic and won't
+//! change between releases, so benchmark results can be compared over time.
+//!
+//! This module is only built with the `bench_fixtures` feature.
+use super::cube::{Cube, Move, ScrambleV1};
+
+/// A typical 20-move scramble
+pub const SCRAMBLE: &str = "D2U'R2UF2DR2U'B2L'D'BR'D2FLU'B'R'F";
+
+/// The seed used to generate the fixed sets of cubes
+const SEED: u64 = 0x5275_6269_6b21;
+
+/// Get a solved cube with `SCRAMBLE` applied
+///
+/// # Example
+/// ```
+/// use rubik::bench_fixtures::scrambled_cube;
+///
+/// assert!(!scrambled_cube().is_solved());
+/// ```
+pub fn scrambled_cube() -> Cube {
+    let mut cube = Cube::new();
+    cube.apply_moves(SCRAMBLE);
+    cube
+}
+
+/// Get a long sequence of moves for measuring `Cube::apply_move` throughput
+///
+/// # Example
+/// ```
+/// use rubik::bench_fixtures::move_sequence;
+/// use rubik::cube::Cube;
+///
+/// let mut cube = Cube::new();
+/// for &m in &move_sequence() {
+///     cube.apply_move(m);
+/// }
+/// ```
+pub fn move_sequence() -> Vec<Move> {
+    ScrambleV1::generate(SEED, 255)
+}
+
+/// Get cubes scrambled with one to five moves, which `IDSolver` can solve
+/// quickly. There are four cubes at each depth.
+///
+/// # Example
+/// ```
+/// use rubik::bench_fixtures::shallow_cubes;
+///
+/// assert_eq!(shallow_cubes().len(), 20);
+/// ```
+pub fn shallow_cubes() -> Vec<Cube> {
+    scrambled_cubes(1..6, 4)
+}
+
+/// Get cubes scrambled with 20 moves. There are 16 of them.
+///
+/// # Example
+/// ```
+/// use rubik::bench_fixtures::deep_cubes;
+///
+/// assert!(deep_cubes().iter().all(|c| !c.is_solved()));
+/// ```
+pub fn deep_cubes() -> Vec<Cube> {
+    scrambled_cubes(20..21, 16)
+}
+
+/// Scramble `per_depth` cubes with each number of moves in `depths`
+fn scrambled_cubes(depths: ::std::ops::Range<u8>, per_depth: u64) -> Vec<Cube> {
+    let mut cubes = Vec::new();
+    for depth in depths {
+        for i in 0..per_depth {
+            let mut cube = Cube::new();
+            cube.scramble_from_seed(SEED + u64::from(depth) * 1000 + i, depth);
+            cubes.push(cube);
+        }
+    }
+    cubes
+}
@@ -4,15 +4,30 @@
 //! Objects and functions for maintaining/manipulating Rubik's cube state.
 use super::rand::{thread_rng, Rng};
 
-use super::solver::Solver;
+use super::solver::{Solver, SolverConfig, SolveError};
 
-use std::collections::HashMap;
+#[cfg(feature = "serde")]
+use super::serde::{Deserialize, Serialize};
+
+use std::collections::{HashMap, HashSet, VecDeque};
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
+use std::error::Error;
 use std::hash::Hash;
 use std::fmt;
+use std::io;
+use std::iter::{self, Peekable};
+use std::str::CharIndices;
+use std::sync::OnceLock;
+
+pub mod pocket;
 
-/// A Corner of a Rubik's cube (there are 8)
+/// A Corner of a Rubik's cube (there are 8), named by its faces. The same
+/// names are used both for the cubicles (positions on the cube) and for the
+/// cubies (pieces) that belong in them. The variants are in cubicle order.
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
-enum Corner {
+#[allow(clippy::upper_case_acronyms)]
+pub enum Corner {
     UFL,
     URF,
     UBR,
@@ -23,6 +38,18 @@ enum Corner {
     DRB,
 }
 
+/// All of the corners in cubicle order
+const CORNERS: [Corner; 8] = [
+    Corner::UFL,
+    Corner::URF,
+    Corner::UBR,
+    Corner::ULB,
+    Corner::DBL,
+    Corner::DLF,
+    Corner::DFR,
+    Corner::DRB,
+];
+
 /// Decompose a corner into faces
 fn decompose_corner(corner: Corner) -> (Face, Face, Face) {
     use self::Corner::*;
@@ -65,9 +92,24 @@ fn get_corner_face(cubicle: Corner, cubie: Corner, face: Face, orientation: u8)
     }
 }
 
-/// An edge of a Rubik's cube (there are 12)
+/// Find the cubie and orientation that shows the given stickers when it's in a
+/// cubicle. Each sticker is a pair of the face it's on and its color.
+fn identify_corner(cubicle: Corner, stickers: &[(Face, Face); 3]) -> Option<(Corner, u8)> {
+    for &cubie in &CORNERS {
+        for orientation in 0..3 {
+            if stickers.iter().all(|&(face, color)| get_corner_face(cubicle, cubie, face, orientation) == color) {
+                return Some((cubie, orientation));
+            }
+        }
+    }
+    None
+}
+
+/// An edge of a Rubik's cube (there are 12), named by its faces. The same
+/// names are used both for the cubicles (positions on the cube) and for the
+/// cubies (pieces) that belong in them. The variants are in cubicle order.
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
-enum Edge {
+pub enum Edge {
     UB,
     UR,
     UF,
@@ -82,6 +124,22 @@ enum Edge {
     DL,
 }
 
+/// All of the edges in cubicle order
+const EDGES: [Edge; 12] = [
+    Edge::UB,
+    Edge::UR,
+    Edge::UF,
+    Edge::UL,
+    Edge::LB,
+    Edge::RB,
+    Edge::RF,
+    Edge::LF,
+    Edge::DB,
+    Edge::DR,
+    Edge::DF,
+    Edge::DL,
+];
+
 /// Decompose an edge into faces
 fn decompose_edge(edge: Edge) -> (Face, Face) {
     use self::Edge::*;
@@ -124,8 +182,55 @@ fn get_edge_face(cubicle: Edge, cubie: Edge, face: Face, orientation: u8) -> Fac
     }
 }
 
-/// There are six possible Rubik's cube moves: Front, Right, Up, Back, Left, and Down.
-/// A move consists of a single clockwise rotation of the corresponding face.
+/// Find the cubie and orientation that shows the given stickers when it's in a
+/// cubicle. Each sticker is a pair of the face it's on and its color.
+fn identify_edge(cubicle: Edge, stickers: &[(Face, Face); 2]) -> Option<(Edge, u8)> {
+    for &cubie in &EDGES {
+        for orientation in 0..2 {
+            if stickers.iter().all(|&(face, color)| get_edge_face(cubicle, cubie, face, orientation) == color) {
+                return Some((cubie, orientation));
+            }
+        }
+    }
+    None
+}
+
+/// The edge cubicles in the order they're listed in Reid notation
+const REID_EDGES: [Edge; 12] = [
+    Edge::UF, Edge::UR, Edge::UB, Edge::UL,
+    Edge::DF, Edge::DR, Edge::DB, Edge::DL,
+    Edge::RF, Edge::LF, Edge::RB, Edge::LB,
+];
+
+/// The order of the faces of each edge in Reid notation
+const REID_EDGE_FACES: [[Face; 2]; 12] = [
+    [Face::U, Face::F], [Face::U, Face::R], [Face::U, Face::B], [Face::U, Face::L],
+    [Face::D, Face::F], [Face::D, Face::R], [Face::D, Face::B], [Face::D, Face::L],
+    [Face::F, Face::R], [Face::F, Face::L], [Face::B, Face::R], [Face::B, Face::L],
+];
+
+/// The corner cubicles in the order they're listed in Reid notation
+const REID_CORNERS: [Corner; 8] = [
+    Corner::URF, Corner::UBR, Corner::ULB, Corner::UFL,
+    Corner::DFR, Corner::DLF, Corner::DBL, Corner::DRB,
+];
+
+/// The order of the faces of each corner in Reid notation
+const REID_CORNER_FACES: [[Face; 3]; 8] = [
+    [Face::U, Face::F, Face::R], [Face::U, Face::R, Face::B],
+    [Face::U, Face::B, Face::L], [Face::U, Face::L, Face::F],
+    [Face::D, Face::R, Face::F], [Face::D, Face::F, Face::L],
+    [Face::D, Face::L, Face::B], [Face::D, Face::B, Face::R],
+];
+
+/// There are six possible Rubik's cube faces to turn: Front, Right, Up, Back, Left, and Down.
+/// A basic move consists of a single clockwise rotation of the corresponding face. Faces
+/// can also be turned counterclockwise (prime) or twice (half turns).
+///
+/// The slice moves M, E, and S turn the middle layer between L and R, U and D, and F and
+/// B in the same direction as L, D, and F respectively. Centers aren't tracked, so slice
+/// moves are treated as if the centers stayed put: an odd number of slice quarter turns
+/// leaves the edges in an odd permutation, which `Cube::is_valid` rejects.
 #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 pub enum Move {
     F,
@@ -140,6 +245,286 @@ pub enum Move {
     BPrime,
     LPrime,
     DPrime,
+    F2,
+    R2,
+    U2,
+    B2,
+    L2,
+    D2,
+    M,
+    E,
+    S,
+    MPrime,
+    EPrime,
+    SPrime,
+}
+
+/// Every move, in the order they're declared
+const MOVES: [Move; 24] = [
+    Move::F, Move::R, Move::U, Move::B, Move::L, Move::D,
+    Move::FPrime, Move::RPrime, Move::UPrime, Move::BPrime, Move::LPrime, Move::DPrime,
+    Move::F2, Move::R2, Move::U2, Move::B2, Move::L2, Move::D2,
+    Move::M, Move::E, Move::S,
+    Move::MPrime, Move::EPrime, Move::SPrime,
+];
+
+/// Every quarter turn of a face
+const QUARTER_TURNS: [Move; 12] = [
+    Move::F,
+    Move::R,
+    Move::U,
+    Move::B,
+    Move::L,
+    Move::D,
+    Move::FPrime,
+    Move::RPrime,
+    Move::UPrime,
+    Move::BPrime,
+    Move::LPrime,
+    Move::DPrime,
+];
+
+/// An axis through the center of the cube. The axes form a right-handed
+/// coordinate system with X pointing out of the R face, Y out of the U face,
+/// and Z out of the F face.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+/// A layer of the cube perpendicular to an axis, e.g. the R layer is the
+/// positive layer on the X axis and the L layer is the negative one.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Layer {
+    Negative,
+    Middle,
+    Positive,
+}
+
+impl Move {
+    /// Get the geometric rotation performed by this move: the axis it turns
+    /// around, the layer that turns, and the signed number of quarter turns.
+    /// Positive quarter turns are counterclockwise when looking down the axis
+    /// towards the center of the cube (i.e. they follow the right-hand rule),
+    /// so a clockwise R is -1 and a clockwise L is 1.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// assert_eq!(Move::R.rotation(), (Axis::X, Layer::Positive, -1));
+    /// assert_eq!(Move::DPrime.rotation(), (Axis::Y, Layer::Negative, -1));
+    /// ```
+    pub fn rotation(self) -> (Axis, Layer, i8) {
+        match self {
+            Move::F => (Axis::Z, Layer::Positive, -1),
+            Move::R => (Axis::X, Layer::Positive, -1),
+            Move::U => (Axis::Y, Layer::Positive, -1),
+            Move::B => (Axis::Z, Layer::Negative, 1),
+            Move::L => (Axis::X, Layer::Negative, 1),
+            Move::D => (Axis::Y, Layer::Negative, 1),
+            Move::FPrime => (Axis::Z, Layer::Positive, 1),
+            Move::RPrime => (Axis::X, Layer::Positive, 1),
+            Move::UPrime => (Axis::Y, Layer::Positive, 1),
+            Move::BPrime => (Axis::Z, Layer::Negative, -1),
+            Move::LPrime => (Axis::X, Layer::Negative, -1),
+            Move::DPrime => (Axis::Y, Layer::Negative, -1),
+            Move::F2 => (Axis::Z, Layer::Positive, -2),
+            Move::R2 => (Axis::X, Layer::Positive, -2),
+            Move::U2 => (Axis::Y, Layer::Positive, -2),
+            Move::B2 => (Axis::Z, Layer::Negative, 2),
+            Move::L2 => (Axis::X, Layer::Negative, 2),
+            Move::D2 => (Axis::Y, Layer::Negative, 2),
+            Move::M => (Axis::X, Layer::Middle, 1),
+            Move::E => (Axis::Y, Layer::Middle, 1),
+            Move::S => (Axis::Z, Layer::Middle, -1),
+            Move::MPrime => (Axis::X, Layer::Middle, -1),
+            Move::EPrime => (Axis::Y, Layer::Middle, -1),
+            Move::SPrime => (Axis::Z, Layer::Middle, 1),
+        }
+    }
+
+    /// Break this move up into quarter turns of a single layer that can be
+    /// executed one after another. Each turn is given as an axis, a layer, and
+    /// a signed quarter turn as in `rotation`.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// assert_eq!(Move::U.atomic_turns(), vec![(Axis::Y, Layer::Positive, -1)]);
+    /// assert_eq!(Move::L2.atomic_turns(), vec![(Axis::X, Layer::Negative, 1); 2]);
+    /// ```
+    pub fn atomic_turns(self) -> Vec<(Axis, Layer, i8)> {
+        let (axis, layer, turns) = self.rotation();
+        (0..turns.abs()).map(|_| (axis, layer, turns.signum())).collect()
+    }
+
+    /// Get the move that undoes this one. Half turns are their own inverse.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// assert_eq!(Move::F.inverse(), Move::FPrime);
+    /// assert_eq!(Move::MPrime.inverse(), Move::M);
+    /// assert_eq!(Move::R2.inverse(), Move::R2);
+    /// ```
+    pub fn inverse(self) -> Move {
+        invert_move(self)
+    }
+
+    /// Determine whether this is a counterclockwise (prime) move
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// assert!(Move::UPrime.is_prime());
+    /// assert!(!Move::U.is_prime());
+    /// assert!(!Move::U2.is_prime());
+    /// ```
+    pub fn is_prime(self) -> bool {
+        layer_turns(self).1 == 3
+    }
+
+    /// Get the face this move turns. Slice moves don't turn a face, so they
+    /// give the face they turn in the same direction as: L for M, D for E,
+    /// and F for S.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// assert_eq!(Move::RPrime.face(), Face::R);
+    /// assert_eq!(Move::D2.face(), Face::D);
+    /// assert_eq!(Move::M.face(), Face::L);
+    /// ```
+    pub fn face(self) -> Face {
+        match layer_turns(self).0 {
+            Move::F | Move::S => Face::F,
+            Move::R => Face::R,
+            Move::U => Face::U,
+            Move::B => Face::B,
+            Move::L | Move::M => Face::L,
+            _ => Face::D,
+        }
+    }
+
+    /// Parse a single move in standard notation, e.g. `R`, `U'`, or `F2`.
+    /// The error points at the first character that can't be part of the
+    /// move. An empty string is reported as a `'\0'` at offset 0.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// assert_eq!(Move::try_from_str("U'"), Ok(Move::UPrime));
+    ///
+    /// let err = Move::try_from_str("R3").unwrap_err();
+    /// assert_eq!(err.character, '3');
+    /// assert_eq!(err.offset, 1);
+    /// ```
+    pub fn try_from_str(s: &str) -> Result<Move, MoveParseError> {
+        let normalized = s.replace(['`', '\u{2032}'], "'");
+        if let Some(m) = lookup_move(&normalized) {
+            return Ok(m);
+        }
+
+        // Find the first character that doesn't fit
+        let mut chars = s.char_indices();
+        let error = match chars.next() {
+            None => MoveParseError { character: '\0', offset: 0 },
+            Some((_, 'F')) | Some((_, 'R')) | Some((_, 'U')) | Some((_, 'B')) | Some((_, 'L')) | Some((_, 'D')) |
+            Some((_, 'M')) | Some((_, 'E')) | Some((_, 'S')) => {
+                // The move letter is fine on its own, so there's a second
+                // character. If it's a valid suffix there must be a third.
+                let (offset, character) = chars.next().unwrap();
+                let is_suffix = match character {
+                    '\'' | '`' | '\u{2032}' => true,
+                    '2' => !"MES".contains(&s[..offset]),
+                    _ => false,
+                };
+                if is_suffix {
+                    let (offset, character) = chars.next().unwrap();
+                    MoveParseError { character, offset }
+                } else {
+                    MoveParseError { character, offset }
+                }
+            },
+            Some((offset, character)) => MoveParseError { character, offset },
+        };
+
+        Err(error)
+    }
+
+    /// Get the move with the given index, the inverse of `u8::from`. Moves
+    /// are numbered in the order they're declared: the clockwise face turns
+    /// F, R, U, B, L, D, then the counterclockwise ones, the half turns, and
+    /// finally the slice moves. This is the same order the solvers try moves
+    /// in. Returns `None` if there's no move with that index.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// assert_eq!(Move::from_u8(1), Some(Move::R));
+    /// assert_eq!(Move::from_u8(u8::from(Move::UPrime)), Some(Move::UPrime));
+    /// assert_eq!(Move::from_u8(24), None);
+    /// ```
+    pub fn from_u8(n: u8) -> Option<Move> {
+        MOVES.get(n as usize).cloned()
+    }
+}
+
+/// Determine whether a sequence of moves can be performed one layer at a time,
+/// i.e. none of the moves requires turning more than one layer at once.
+/// Every current `Move` turns a single layer, so this is always true.
+///
+/// # Example
+/// ```
+/// use rubik::cube::*;
+///
+/// assert!(moves_are_physically_sequential(&[Move::R, Move::UPrime]));
+/// ```
+pub fn moves_are_physically_sequential(_moves: &[Move]) -> bool {
+    true
+}
+
+/// Split a sequence of moves into quarter turns of a single layer, in the
+/// order they should be executed. This is handy for driving hardware that can
+/// only turn one layer at a time.
+///
+/// # Example
+/// ```
+/// use rubik::cube::*;
+///
+/// let turns = split_atomic(&[Move::R, Move::DPrime]);
+/// assert_eq!(turns, vec![
+///     (Axis::X, Layer::Positive, -1),
+///     (Axis::Y, Layer::Negative, -1),
+/// ]);
+/// ```
+pub fn split_atomic(moves: &[Move]) -> Vec<(Axis, Layer, i8)> {
+    moves.iter().flat_map(|m| m.atomic_turns()).collect()
+}
+
+/// Write a move in standard notation, e.g. `F`, `F'`, or `F2`
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (layer, turns) = layer_turns(*self);
+        write!(f, "{}", layer_notation(layer, turns))
+    }
+}
+
+/// Get the index of a move, for storing moves compactly. See `Move::from_u8`
+/// for the numbering.
+impl From<Move> for u8 {
+    fn from(m: Move) -> u8 {
+        m as u8
+    }
 }
 
 /// Create a Move from a &str. See
@@ -147,27 +532,49 @@ pub enum Move {
 /// for notation.
 ///
 /// # Panics
-/// This function will panic if the input isn't a valid move, i.e. not one of FRUBLD(').
+/// This function will panic if the input isn't a valid move, i.e. not one of FRUBLD('|2)
+/// or MES('). Use `Move::try_from_str` to handle invalid input without panicking.
 impl<'a> From<&'a str> for Move {
     fn from(s: &'a str) -> Move {
-        match s {
-            "F" => Move::F,
-            "R" => Move::R,
-            "U" => Move::U,
-            "B" => Move::B,
-            "L" => Move::L,
-            "D" => Move::D,
-            "F'" => Move::FPrime,
-            "R'" => Move::RPrime,
-            "U'" => Move::UPrime,
-            "B'" => Move::BPrime,
-            "L'" => Move::LPrime,
-            "D'" => Move::DPrime,
-            _ => panic!("Invalid move: {}", s),
+        match Move::try_from_str(s) {
+            Ok(m) => m,
+            Err(_) => panic!("Invalid move: {}", s),
         }
     }
 }
 
+/// Look up a single move in standard notation
+fn lookup_move(s: &str) -> Option<Move> {
+    let m = match s {
+        "F" => Move::F,
+        "R" => Move::R,
+        "U" => Move::U,
+        "B" => Move::B,
+        "L" => Move::L,
+        "D" => Move::D,
+        "F'" => Move::FPrime,
+        "R'" => Move::RPrime,
+        "U'" => Move::UPrime,
+        "B'" => Move::BPrime,
+        "L'" => Move::LPrime,
+        "D'" => Move::DPrime,
+        "F2" => Move::F2,
+        "R2" => Move::R2,
+        "U2" => Move::U2,
+        "B2" => Move::B2,
+        "L2" => Move::L2,
+        "D2" => Move::D2,
+        "M" => Move::M,
+        "E" => Move::E,
+        "S" => Move::S,
+        "M'" => Move::MPrime,
+        "E'" => Move::EPrime,
+        "S'" => Move::SPrime,
+        _ => return None,
+    };
+    Some(m)
+}
+
 /// Create a Move from a char. See
 /// [http://rubiks.wikia.com/wiki/Notation](http://rubiks.wikia.com/wiki/Notation) 
 /// for notation.
@@ -183,727 +590,5095 @@ impl From<char> for Move {
             'B' => Move::B,
             'L' => Move::L,
             'D' => Move::D,
+            'M' => Move::M,
+            'E' => Move::E,
+            'S' => Move::S,
             _ => panic!("Invalid move: {}", c),
         }
     }
 }
 
-/// A face of a cubie (corresponds to a single color sticker on a real cube)
-#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
-pub enum Face {
-    F,
-    R,
-    U,
-    B,
-    L,
-    D,
+/// Find the corner cubicles located in a face clockwise from top left
+fn face_corners(face: Face) -> [Corner; 4] {
+    use self::Corner::*;
+    match face {
+        Face::F => [UFL, URF, DFR, DLF],
+        Face::R => [URF, UBR, DRB, DFR],
+        Face::U => [ULB, UBR, URF, UFL],
+        Face::B => [UBR, ULB, DBL, DRB],
+        Face::L => [ULB, UFL, DLF, DBL],
+        Face::D => [DLF, DFR, DRB, DBL],
+    }
 }
 
-/// Create a Face from a char. See
-/// [http://rubiks.wikia.com/wiki/Notation](http://rubiks.wikia.com/wiki/Notation) 
-/// for notation.
-///
-/// # Panics
-/// This function will panic if the input char isn't a valid face, i.e. not one of FRUBLD.
-impl From<char> for Face {
-    fn from(ch: char) -> Face {
-        match ch {
-            'F' => Face::F,
-            'R' => Face::R,
-            'U' => Face::U,
-            'B' => Face::B,
-            'L' => Face::L,
-            'D' => Face::D,
-            _ => panic!("Invalid face name: {}", ch),
-        }
+/// Find the edge cubicles in a face clockwise from the top
+fn face_edges(face: Face) -> [Edge; 4] {
+    use self::Edge::*;
+    match face {
+        Face::F => [UF, RF, DF, LF],
+        Face::R => [UR, RB, DR, RF],
+        Face::U => [UB, UR, UF, UL],
+        Face::B => [UB, LB, DB, RB],
+        Face::L => [UL, LF, DL, LB],
+        Face::D => [DF, DR, DB, DL],
     }
 }
 
-/// Takes a symmetric group and returns a vector representing its disjoint
-/// cycles including cycles with length 1.
-fn disjoint_cycle_decompose<T: Copy + Eq + Hash>(map: &HashMap<T, T>) -> Vec<Vec<T>> {
-    let mut cycles: Vec<Vec<T>> = Vec::new();
-    let mut current_cycle: Vec<T> = Vec::new();
-    let mut used_values: Vec<T> = Vec::new();
-    for &v in map.values() {
-        if used_values.contains(&v) {
-            continue;
-        }
+/// Sticker indices of the corners and edges returned by `face_corners` and
+/// `face_edges`, in the layout used by `get_face`
+const FACE_CORNER_INDICES: [usize; 4] = [0, 2, 8, 6];
+const FACE_EDGE_INDICES: [usize; 4] = [1, 5, 7, 3];
 
-        current_cycle.push(v);
-        let mut current_value = v;
-        loop {
-            if (current_cycle.len() > 1 && current_cycle[0] == current_value)
-            || (used_values.contains(&current_value)) {
-                current_cycle.pop();
-                cycles.push(current_cycle.clone());
-                current_cycle = Vec::new();
-                break;
-            }
-            used_values.push(current_value);
-            current_value = *(map.get(&current_value).unwrap());
-            current_cycle.push(current_value);
-        }
+/// Find the index of the sticker on a face of a corner cubicle
+fn corner_sticker_index(cubicle: Corner, face: Face) -> usize {
+    let i = face_corners(face).iter().position(|&c| c == cubicle).unwrap();
+    FACE_CORNER_INDICES[i]
+}
+
+/// Find the index of the sticker on a face of an edge cubicle
+fn edge_sticker_index(cubicle: Edge, face: Face) -> usize {
+    let i = face_edges(face).iter().position(|&e| e == cubicle).unwrap();
+    FACE_EDGE_INDICES[i]
+}
+
+/// An error encountered while parsing a string of moves
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct MoveParseError {
+    /// The character that couldn't be parsed
+    pub character: char,
+    /// The byte offset of the character in the input string
+    pub offset: usize,
+}
+
+impl fmt::Display for MoveParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid move character '{}' at offset {}", self.character, self.offset)
     }
+}
 
-    // Sort the cycles by length
-    cycles.sort_by_key(|k| k.len());
-    cycles.reverse();
+impl Error for MoveParseError {}
 
-    cycles
+/// Keeps `IntoMoves` from being implemented outside this crate
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for super::Move {}
+    impl Sealed for &[super::Move] {}
+    impl Sealed for Vec<super::Move> {}
+    impl Sealed for &str {}
 }
 
-#[derive(Clone, Eq, PartialEq)]
-/// Map corner cubicles to cubies
-struct CornerPermutation {
-    map: HashMap<Corner, Corner>,
+/// Things that can be turned into a sequence of moves: a single `Move`, a
+/// slice or `Vec` of moves, or a string of moves in standard notation. See
+/// `Cube::apply_any`.
+pub trait IntoMoves: private::Sealed {
+    /// Get the moves, or an error if they can't be parsed
+    fn into_moves(self) -> Result<Vec<Move>, MoveParseError>;
 }
 
-impl Default for CornerPermutation {
-    /// The default mapping has each cubie in its corresponding cubicle
-    fn default() -> CornerPermutation {
-        let mut m: HashMap<Corner, Corner> = HashMap::new();
-        // Default mapping
-        use self::Corner;
-        m.insert(Corner::UFL, Corner::UFL);
-        m.insert(Corner::URF, Corner::URF);
-        m.insert(Corner::UBR, Corner::UBR);
-        m.insert(Corner::ULB, Corner::ULB);
-        m.insert(Corner::DBL, Corner::DBL);
-        m.insert(Corner::DLF, Corner::DLF);
-        m.insert(Corner::DFR, Corner::DFR);
-        m.insert(Corner::DRB, Corner::DRB);
+impl IntoMoves for Move {
+    fn into_moves(self) -> Result<Vec<Move>, MoveParseError> {
+        Ok(vec![self])
+    }
+}
 
-        CornerPermutation {
-            map: m,
-        }
+impl IntoMoves for &[Move] {
+    fn into_moves(self) -> Result<Vec<Move>, MoveParseError> {
+        Ok(self.to_vec())
     }
 }
 
-impl fmt::Debug for CornerPermutation {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let cycles = disjoint_cycle_decompose(&self.map);
-        write!(f, "{:?}", cycles)
+impl IntoMoves for Vec<Move> {
+    fn into_moves(self) -> Result<Vec<Move>, MoveParseError> {
+        Ok(self)
     }
 }
 
-impl CornerPermutation {
-    /// Create a solved permutation
-    pub fn new() -> CornerPermutation {
-        CornerPermutation::default()
+impl IntoMoves for &str {
+    fn into_moves(self) -> Result<Vec<Move>, MoveParseError> {
+        parse_moves(self)
     }
+}
 
-    /// Get a the cubie located in a particular cubicle
-    pub fn get(&self, cubicle: Corner) -> Corner {
-        *self.map.get(&cubicle).unwrap()
+/// The most times a group of moves can be repeated. This keeps a short string
+/// from standing for an enormous number of moves.
+pub const MAX_GROUP_REPEATS: usize = 1000;
+
+/// An iterator that parses a string of moves one move at a time, without
+/// building a list of every move first. It yields an error for the first
+/// invalid character and stops there. ASCII whitespace between moves is
+/// skipped, so empty or whitespace-only input contains no moves.
+///
+/// A group of moves in parentheses followed by a count, like `(RUR'U')6` or
+/// `(RUR'U')x6`, is repeated that many times. Groups can't be nested, and
+/// can't be repeated more than `MAX_GROUP_REPEATS` times. An unclosed group is
+/// reported as an error at its opening parenthesis.
+///
+/// # Example
+/// ```
+/// use rubik::cube::*;
+///
+/// # fn main() -> Result<(), MoveParseError> {
+/// let mut cube = Cube::new();
+/// for m in MoveIter::new("RUR'U'") {
+///     cube.apply_move(m?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct MoveIter<'a> {
+    chars: Peekable<CharIndices<'a>>,
+    /// The length of the input in bytes, for errors at the end of the input
+    len: usize,
+    /// Moves from the current token that haven't been returned yet
+    pending: VecDeque<Move>,
+    /// The moves in the current group, if the current token is a group
+    group: Vec<Move>,
+    /// How many more times the current group needs to be repeated
+    repeats: usize,
+    /// Whether an error has been returned
+    failed: bool,
+}
+
+impl<'a> MoveIter<'a> {
+    /// Create an iterator over the moves in a string
+    pub fn new(moves: &'a str) -> MoveIter<'a> {
+        MoveIter {
+            chars: moves.char_indices().peekable(),
+            len: moves.len(),
+            pending: VecDeque::new(),
+            group: Vec::new(),
+            repeats: 0,
+            failed: false,
+        }
     }
 
-    /// Apply a move and determine which cubies end up where
-    pub fn permute(&mut self, m: Move) {
-        use self::Move;
-        use self::Corner;
-        let cycle = match m {
-            Move::F => {
-                (Corner::URF, Corner::DFR, Corner::DLF, Corner::UFL)
-            },
-            Move::R => {
-                (Corner::UBR, Corner::DRB, Corner::DFR, Corner::URF)
-            },
-            Move::U => {
-                (Corner::URF, Corner::UFL, Corner::ULB, Corner::UBR)
-            },
-            Move::B => {
-                (Corner::ULB, Corner::DBL, Corner::DRB, Corner::UBR)
-            },
-            Move::L => {
-                (Corner::UFL, Corner::DLF, Corner::DBL, Corner::ULB)
-            },
-            Move::D => {
-                (Corner::DRB, Corner::DBL, Corner::DLF, Corner::DFR)
+    /// Parse the next token and return its byte offset, or `None` at the end
+    /// of the input. Most tokens are a single move, but slice half turns and
+    /// wide moves are several. A group of moves is one token, whose moves are
+    /// put in `group` and repeated `repeats` times.
+    fn next_token(&mut self) -> Result<Option<usize>, MoveParseError> {
+        self.skip_whitespace();
+        match self.chars.next() {
+            Some((offset, '(')) => {
+                self.parse_group(offset)?;
+                Ok(Some(offset))
             },
-            Move::FPrime => {
-                (Corner::URF, Corner::UFL, Corner::DLF, Corner::DFR)
+            Some((offset, ch)) => {
+                self.parse_move(offset, ch)?;
+                Ok(Some(offset))
             },
-            Move::RPrime => {
-                (Corner::UBR, Corner::URF, Corner::DFR, Corner::DRB)
+            None => Ok(None),
+        }
+    }
+
+    /// Parse a parenthesized group of moves and its repeat count, given the
+    /// offset of the opening parenthesis
+    fn parse_group(&mut self, open: usize) -> Result<(), MoveParseError> {
+        loop {
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some((_, ')')) => break,
+                Some((offset, '(')) => return Err(MoveParseError { character: '(', offset }),
+                Some((offset, ch)) => self.parse_move(offset, ch)?,
+                None => return Err(MoveParseError { character: '(', offset: open }),
+            }
+        }
+        self.group = self.pending.drain(..).collect();
+
+        // The count can be written as either 3 or x3. A group without a count
+        // is done once.
+        let marked = match self.chars.peek() {
+            Some(&(_, 'x')) => {
+                self.chars.next();
+                true
             },
-            Move::UPrime => {
-                (Corner::URF, Corner::UBR, Corner::ULB, Corner::UFL)
+            _ => false,
+        };
+        let mut count: Option<usize> = None;
+        while let Some(&(offset, ch)) = self.chars.peek() {
+            let digit = match ch.to_digit(10) {
+                Some(d) => d as usize,
+                None => break,
+            };
+            self.chars.next();
+            count = Some(count.unwrap_or(0) * 10 + digit);
+            if count > Some(MAX_GROUP_REPEATS) {
+                return Err(MoveParseError { character: ch, offset });
+            }
+        }
+
+        self.repeats = match count {
+            Some(_) if self.group.is_empty() => 0,
+            Some(c) => c,
+            None if !marked => 1,
+            None => {
+                let (offset, character) = self.chars.peek().cloned().unwrap_or((self.len, '\0'));
+                return Err(MoveParseError { character, offset });
             },
-            Move::BPrime => {
-                (Corner::ULB, Corner::UBR, Corner::DRB, Corner::DBL)
+        };
+        Ok(())
+    }
+
+    /// Parse a single move (which might be several moves, see `next_token`)
+    /// into `pending`, given its first character and that character's offset
+    fn parse_move(&mut self, offset: usize, ch: char) -> Result<(), MoveParseError> {
+        // Wide moves are written either as a face followed by w or as a
+        // lowercase face
+        let (layer, wide) = match ch {
+            'F' | 'R' | 'U' | 'B' | 'L' | 'D' => match self.chars.peek() {
+                Some(&(_, 'w')) => {
+                    self.chars.next();
+                    (Move::from(ch), true)
+                },
+                _ => (Move::from(ch), false),
             },
-            Move::LPrime => {
-                (Corner::UFL, Corner::ULB, Corner::DBL, Corner::DLF)
+            'f' | 'r' | 'u' | 'b' | 'l' | 'd' => (Move::from(ch.to_ascii_uppercase()), true),
+            'M' | 'E' | 'S' => (Move::from(ch), false),
+            _ => return Err(MoveParseError { character: ch, offset }),
+        };
+
+        // Look ahead for a prime or half turn suffix
+        let turns = match self.chars.peek() {
+            Some(&(_, '\'')) | Some(&(_, '`')) | Some(&(_, '\u{2032}')) => {
+                self.chars.next();
+                3
             },
-            Move::DPrime => {
-                (Corner::DRB, Corner::DFR, Corner::DLF, Corner::DBL)
+            Some(&(_, '2')) => {
+                self.chars.next();
+                2
             },
+            _ => 1,
         };
 
-        let mut new_map = self.map.clone();
-        new_map.insert(cycle.1, *self.map.get(&cycle.0).unwrap());
-        new_map.insert(cycle.2, *self.map.get(&cycle.1).unwrap());
-        new_map.insert(cycle.3, *self.map.get(&cycle.2).unwrap());
-        new_map.insert(cycle.0, *self.map.get(&cycle.3).unwrap());
-        self.map = new_map;
+        self.pending.extend(layer_moves(layer, turns));
+        if wide {
+            self.pending.extend(layer_moves(wide_slice(layer), turns));
+        }
+        Ok(())
     }
-}
 
-#[derive(Clone, Eq, PartialEq)]
-/// Map edge cubicles to cubies
-struct EdgePermutation {
-    map: HashMap<Edge, Edge>,
-}
+    /// Skip any whitespace before the next token
+    fn skip_whitespace(&mut self) {
+        while let Some(&(_, ch)) = self.chars.peek() {
+            if !ch.is_ascii_whitespace() {
+                break;
+            }
+            self.chars.next();
+        }
+    }
 
-impl Default for EdgePermutation {
-    /// The default mapping has each cubie in its corresponding cubicle
-    fn default() -> EdgePermutation {
-        let mut m: HashMap<Edge, Edge> = HashMap::new();
-        use self::Edge;
-        m.insert(Edge::UB, Edge::UB);
-        m.insert(Edge::UR, Edge::UR);
-        m.insert(Edge::UF, Edge::UF);
-        m.insert(Edge::UL, Edge::UL);
-        m.insert(Edge::LB, Edge::LB);
-        m.insert(Edge::RB, Edge::RB);
-        m.insert(Edge::RF, Edge::RF);
-        m.insert(Edge::LF, Edge::LF);
-        m.insert(Edge::DB, Edge::DB);
-        m.insert(Edge::DR, Edge::DR);
-        m.insert(Edge::DF, Edge::DF);
-        m.insert(Edge::DL, Edge::DL);
+    /// Queue the next repetition of the current group, if there is one
+    fn repeat_group(&mut self) -> bool {
+        if self.repeats == 0 {
+            return false;
+        }
 
-        EdgePermutation {
-            map: m,
+        self.repeats -= 1;
+        self.pending.extend(&self.group);
+        true
+    }
+}
+
+impl Iterator for MoveIter<'_> {
+    type Item = Result<Move, MoveParseError>;
+
+    fn next(&mut self) -> Option<Result<Move, MoveParseError>> {
+        // Tokens can be empty, e.g. a group repeated zero times, so keep
+        // going until there's a move
+        loop {
+            if let Some(m) = self.pending.pop_front() {
+                return Some(Ok(m));
+            }
+            if self.failed {
+                return None;
+            }
+            if self.repeat_group() {
+                continue;
+            }
+
+            match self.next_token() {
+                Ok(Some(_)) => {},
+                Ok(None) => return None,
+                Err(e) => {
+                    self.failed = true;
+                    return Some(Err(e));
+                },
+            }
         }
     }
 }
 
-impl fmt::Debug for EdgePermutation {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let cycles = disjoint_cycle_decompose(&self.map);
-        write!(f, "{:?}", cycles)
+/// Parse a string of moves, passing each move to a function as soon as it has
+/// been parsed. Parsing stops early if the function returns false. Empty or
+/// whitespace-only input contains no moves.
+fn for_each_move<F: FnMut(Move) -> bool>(moves: &str, mut f: F) -> Result<(), MoveParseError> {
+    for m in MoveIter::new(moves) {
+        if !f(m?) {
+            break;
+        }
     }
+
+    Ok(())
 }
 
-impl EdgePermutation {
-    /// Create a new solved permutation
-    pub fn new() -> EdgePermutation {
-        EdgePermutation::default()
+/// Parse a string of moves one token at a time, passing the byte offset of
+/// each token and the moves it stands for to a function. A group of moves is
+/// never a single token, so groups are an error at their opening parenthesis.
+fn for_each_token<F: FnMut(usize, &[Move])>(moves: &str, mut f: F) -> Result<(), MoveParseError> {
+    let mut tokens = MoveIter::new(moves);
+    while let Some(offset) = tokens.next_token()? {
+        if moves[offset..].starts_with('(') {
+            return Err(MoveParseError { character: '(', offset });
+        }
+        f(offset, tokens.pending.make_contiguous());
+        tokens.pending.clear();
     }
 
-    /// Get a the cubie located in a particular cubicle
-    pub fn get(&self, cubicle: Edge) -> Edge {
-        *self.map.get(&cubicle).unwrap()
+    Ok(())
+}
+
+/// Get the moves that turn a layer the given number of clockwise quarter
+/// turns, given the layer's clockwise quarter turn. There are no half turn
+/// slice moves, so those are two quarter turns.
+fn layer_moves(layer: Move, turns: u8) -> impl Iterator<Item = Move> {
+    let (m, count) = match (layer, turns) {
+        (Move::F, 2) => (Move::F2, 1),
+        (Move::R, 2) => (Move::R2, 1),
+        (Move::U, 2) => (Move::U2, 1),
+        (Move::B, 2) => (Move::B2, 1),
+        (Move::L, 2) => (Move::L2, 1),
+        (Move::D, 2) => (Move::D2, 1),
+        (_, 0) => (layer, 0),
+        (_, 2) => (layer, 2),
+        (_, 3) => (invert_move(layer), 1),
+        _ => (layer, 1),
+    };
+    iter::repeat(m).take(count)
+}
+
+/// Get the slice move that turns the middle layer next to a face in the same
+/// direction as the face's clockwise quarter turn, i.e. the slice that a wide
+/// move turns along with the face
+fn wide_slice(face: Move) -> Move {
+    match face {
+        Move::R => Move::MPrime,
+        Move::L => Move::M,
+        Move::U => Move::EPrime,
+        Move::D => Move::E,
+        Move::F => Move::S,
+        Move::B => Move::SPrime,
+        _ => unreachable!("Only faces have wide moves"),
     }
+}
 
-    /// Apply a move and determine which cubies end up where
-    pub fn permute(&mut self, m: Move) {
-        use self::Move;
-        let cycle = match m {
-            Move::F => {
-                (Edge::UF, Edge::RF, Edge::DF, Edge::LF)
-            },
-            Move::R => {
-                (Edge::UR, Edge::RB, Edge::DR, Edge::RF)
-            },
-            Move::U => {
-                (Edge::UB, Edge::UR, Edge::UF, Edge::UL)
-            },
-            Move::B => {
-                (Edge::UB, Edge::LB, Edge::DB, Edge::RB)
-            },
-            Move::L => {
-                (Edge::UL, Edge::LF, Edge::DL, Edge::LB)
-            },
-            Move::D => {
-                (Edge::DF, Edge::DR, Edge::DB, Edge::DL)
-            },
-            Move::FPrime => {
-                (Edge::UF, Edge::LF, Edge::DF, Edge::RF)
-            },
-            Move::RPrime => {
-                (Edge::UR, Edge::RF, Edge::DR, Edge::RB)
-            },
-            Move::UPrime => {
-                (Edge::UB, Edge::UL, Edge::UF, Edge::UR)
-            },
-            Move::BPrime => {
-                (Edge::UB, Edge::RB, Edge::DB, Edge::LB)
-            },
-            Move::LPrime => {
-                (Edge::UL, Edge::LB, Edge::DL, Edge::LF)
-            },
-            Move::DPrime => {
-                (Edge::DF, Edge::DL, Edge::DB, Edge::DR)
-            },
-        };
+/// Parse a string of moves into a list of moves
+fn parse_moves(moves: &str) -> Result<Vec<Move>, MoveParseError> {
+    MoveIter::new(moves).collect()
+}
 
-        let mut new_map = self.map.clone();
-        new_map.insert(cycle.1, *self.map.get(&cycle.0).unwrap());
-        new_map.insert(cycle.2, *self.map.get(&cycle.1).unwrap());
-        new_map.insert(cycle.3, *self.map.get(&cycle.2).unwrap());
-        new_map.insert(cycle.0, *self.map.get(&cycle.3).unwrap());
-        self.map = new_map;
+/// Write a number of quarter turns of a layer in standard notation, e.g. `R2`
+/// for two turns of R
+fn layer_notation(layer: Move, turns: u8) -> String {
+    match turns {
+        1 => format!("{:?}", layer),
+        2 => format!("{:?}2", layer),
+        _ => format!("{:?}'", layer),
     }
 }
 
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
-/// Corner orientation state
-struct X(u8,u8,u8,u8,u8,u8,u8,u8);
+/// Get the clockwise quarter turn of the layer a move turns, and the number of
+/// those quarter turns the move is equivalent to
+fn layer_turns(m: Move) -> (Move, u8) {
+    match m {
+        Move::F | Move::R | Move::U | Move::B | Move::L | Move::D |
+        Move::M | Move::E | Move::S => (m, 1),
+        Move::FPrime => (Move::F, 3),
+        Move::RPrime => (Move::R, 3),
+        Move::UPrime => (Move::U, 3),
+        Move::BPrime => (Move::B, 3),
+        Move::LPrime => (Move::L, 3),
+        Move::DPrime => (Move::D, 3),
+        Move::F2 => (Move::F, 2),
+        Move::R2 => (Move::R, 2),
+        Move::U2 => (Move::U, 2),
+        Move::B2 => (Move::B, 2),
+        Move::L2 => (Move::L, 2),
+        Move::D2 => (Move::D, 2),
+        Move::MPrime => (Move::M, 3),
+        Move::EPrime => (Move::E, 3),
+        Move::SPrime => (Move::S, 3),
+    }
+}
 
-/// Swap values in an X vector
-fn swap_x(values: X, indices: &[u8; 8]) -> X {
-    let mut swapped = [0u8; 8];
-    for (curr, &i) in indices.iter().enumerate() {
-        swapped[curr] = match i {
-            0 => values.0,
-            1 => values.1,
-            2 => values.2,
-            3 => values.3,
-            4 => values.4,
-            5 => values.5,
-            6 => values.6,
-            7 => values.7,
-            _ => panic!("Invalid index for X tuple"),
-        };
+/// Get the move that undoes a move
+fn invert_move(m: Move) -> Move {
+    match m {
+        Move::F => Move::FPrime,
+        Move::R => Move::RPrime,
+        Move::U => Move::UPrime,
+        Move::B => Move::BPrime,
+        Move::L => Move::LPrime,
+        Move::D => Move::DPrime,
+        Move::FPrime => Move::F,
+        Move::RPrime => Move::R,
+        Move::UPrime => Move::U,
+        Move::BPrime => Move::B,
+        Move::LPrime => Move::L,
+        Move::DPrime => Move::D,
+        Move::F2 | Move::R2 | Move::U2 | Move::B2 | Move::L2 | Move::D2 => m,
+        Move::M => Move::MPrime,
+        Move::E => Move::EPrime,
+        Move::S => Move::SPrime,
+        Move::MPrime => Move::M,
+        Move::EPrime => Move::E,
+        Move::SPrime => Move::S,
     }
+}
 
-    X(
-        swapped[0],
-        swapped[1],
-        swapped[2],
-        swapped[3],
-        swapped[4],
-        swapped[5],
-        swapped[6],
-        swapped[7],
-    )
+/// Write a sequence of moves in standard notation with spaces between them
+///
+/// # Example
+/// ```
+/// use rubik::cube::*;
+///
+/// let moves = [Move::F, Move::RPrime, Move::U2];
+/// assert_eq!(moves_to_string(&moves), "F R' U2");
+/// ```
+pub fn moves_to_string(moves: &[Move]) -> String {
+    let notation: Vec<String> = moves.iter().map(|m| m.to_string()).collect();
+    notation.join(" ")
 }
 
-/// Add values to an X vector
-fn add_x(values: X, addends: &[u8; 8]) -> X {
-    X(
-        (values.0 + addends[0]) % 3,
-        (values.1 + addends[1]) % 3,
-        (values.2 + addends[2]) % 3,
-        (values.3 + addends[3]) % 3,
-        (values.4 + addends[4]) % 3,
-        (values.5 + addends[5]) % 3,
-        (values.6 + addends[6]) % 3,
-        (values.7 + addends[7]) % 3,
-    )
+/// Write a scramble the way the WCA scramble program writes them, which
+/// `Cube::import_wca` reads back. This is the same as `moves_to_string`, except
+/// that slice moves aren't used in WCA scrambles, so they're an error.
+///
+/// # Example
+/// ```
+/// use rubik::cube::*;
+///
+/// let moves = [Move::D2, Move::FPrime, Move::L];
+/// assert_eq!(export_wca(&moves), Ok("D2 F' L".to_string()));
+/// assert_eq!(export_wca(&[Move::R, Move::M]), Err(CubeError::SliceMove(Move::M)));
+/// ```
+pub fn export_wca(moves: &[Move]) -> Result<String, CubeError> {
+    if let Some(&m) = moves.iter().find(|m| m.rotation().1 == Layer::Middle) {
+        return Err(CubeError::SliceMove(m));
+    }
+    Ok(moves_to_string(moves))
 }
 
-#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
-/// Edge orientation state
-struct Y(u8,u8,u8,u8,u8,u8,u8,u8,u8,u8,u8,u8);
+/// Parse a scramble in WCA notation. See `Cube::import_wca`.
+fn parse_wca(scramble: &str) -> Result<Vec<Move>, MoveParseError> {
+    let mut moves = Vec::new();
+    let mut chars = scramble.char_indices().peekable();
+    while let Some((offset, character)) = chars.next() {
+        if character.is_ascii_whitespace() {
+            continue;
+        }
+        if !"FRUBLD".contains(character) {
+            return Err(MoveParseError { character, offset });
+        }
 
-/// Swap values in a Y vector
-fn swap_y(values: Y, indices: &[u8; 12]) -> Y {
-    let mut swapped = [0u8; 12];
-    for (curr, &i) in indices.iter().enumerate() {
-        swapped[curr] = match i {
-            0 => values.0,
-            1 => values.1,
-            2 => values.2,
-            3 => values.3,
-            4 => values.4,
-            5 => values.5,
-            6 => values.6,
-            7 => values.7,
-            8 => values.8,
-            9 => values.9,
-            10 => values.10,
-            11 => values.11,
-            _ => panic!("Invalid index for Y tuple"),
+        let turns = match chars.peek() {
+            Some(&(_, '\'')) => 3,
+            Some(&(_, '2')) => 2,
+            _ => 1,
         };
+        if turns != 1 {
+            chars.next();
+        }
+
+        // Every move has to be followed by a space
+        match chars.peek() {
+            Some(&(offset, character)) if !character.is_ascii_whitespace() => {
+                return Err(MoveParseError { character, offset });
+            },
+            _ => moves.extend(layer_moves(Move::from(character), turns)),
+        }
     }
 
-    Y(
-        swapped[0],
-        swapped[1],
-        swapped[2],
-        swapped[3],
-        swapped[4],
-        swapped[5],
-        swapped[6],
-        swapped[7],
-        swapped[8],
-        swapped[9],
-        swapped[10],
-        swapped[11],
-    )
+    Ok(moves)
 }
 
-/// Add values to a Y vector
-fn add_y(values: Y, addends: &[u8; 12]) -> Y {
-    Y(
-        (values.0 + addends[0]) % 2,
-        (values.1 + addends[1]) % 2,
-        (values.2 + addends[2]) % 2,
-        (values.3 + addends[3]) % 2,
-        (values.4 + addends[4]) % 2,
-        (values.5 + addends[5]) % 2,
-        (values.6 + addends[6]) % 2,
-        (values.7 + addends[7]) % 2,
-        (values.8 + addends[8]) % 2,
-        (values.9 + addends[9]) % 2,
-        (values.10 + addends[10]) % 2,
-        (values.11 + addends[11]) % 2,
-    )
+/// Get the sequence of moves that undoes a sequence of moves, i.e. the moves
+/// in reverse order with each one inverted
+///
+/// # Example
+/// ```
+/// use rubik::cube::*;
+///
+/// let moves = [Move::F, Move::RPrime, Move::U2];
+/// assert_eq!(invert_moves(&moves), vec![Move::U2, Move::R, Move::FPrime]);
+/// ```
+pub fn invert_moves(moves: &[Move]) -> Vec<Move> {
+    moves.iter().rev().map(|&m| invert_move(m)).collect()
 }
 
-#[derive(Clone, Eq, PartialEq)]
-/// Maintain the state information for a Rubik's cube.
-pub struct Cube {
-    sigma: CornerPermutation,
-    tau: EdgePermutation,
-    x: X,
-    y: Y,
+/// Build the commutator `[A, B] = A B A' B'` of two move sequences
+///
+/// # Example
+/// ```
+/// use rubik::cube::*;
+///
+/// let sexy = commutator(&[Move::R], &[Move::U]);
+/// assert_eq!(sexy, vec![Move::R, Move::U, Move::RPrime, Move::UPrime]);
+/// ```
+pub fn commutator(a: &[Move], b: &[Move]) -> Vec<Move> {
+    let mut moves = a.to_vec();
+    moves.extend_from_slice(b);
+    moves.extend(invert_moves(a));
+    moves.extend(invert_moves(b));
+    moves
 }
 
-impl fmt::Debug for Cube {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "\u{03c3} = {:?}\n\u{03c4} = {:?}\nx = {:?}\ny = {:?}",
-               self.sigma, self.tau, self.x, self.y)
-    }
+/// Build the conjugate `A B A'` of a move sequence `B` by a setup sequence `A`
+///
+/// # Example
+/// ```
+/// use rubik::cube::*;
+///
+/// let moves = conjugate(&[Move::F], &[Move::R, Move::U]);
+/// assert_eq!(moves, vec![Move::F, Move::R, Move::U, Move::FPrime]);
+/// ```
+pub fn conjugate(setup: &[Move], alg: &[Move]) -> Vec<Move> {
+    let mut moves = setup.to_vec();
+    moves.extend_from_slice(alg);
+    moves.extend(invert_moves(setup));
+    moves
 }
 
-impl Cube {
-    /// Create a new cube in the solved state.
-    pub fn new() -> Cube {
-        Cube {
-            sigma: CornerPermutation::new(),
-            tau: EdgePermutation::new(),
-            x: X::default(),
-            y: Y::default(),
-        }
-    }
+/// A way of counting moves. Solvers use the metric to decide which moves
+/// they may use and how long the longest optimal solution can be.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Metric {
+    /// Any turn of a face is one move (see `count_htm`). Every cube can be
+    /// solved in 20 moves or fewer.
+    HalfTurn,
+    /// Quarter turns of a face are one move and half turns are two (see
+    /// `count_qtm`). Every cube can be solved in 26 moves or fewer.
+    QuarterTurn,
+}
 
-    /// Apply a random series of moves to scramble the cube
-    /// # Arguments
-    /// move_count: The number of random moves to apply to the cube.
+impl Metric {
+    /// Get the most moves needed to solve any cube in this metric (God's
+    /// number)
+    ///
     /// # Example
     /// ```
-    /// use rubik::cube::*;
-    /// 
-    /// let mut cube = Cube::new();
-    /// cube.scramble(20);
-    /// assert!(!cube.is_solved());
+    /// use rubik::cube::Metric;
+    ///
+    /// assert_eq!(Metric::HalfTurn.gods_number(), 20);
+    /// assert_eq!(Metric::QuarterTurn.gods_number(), 26);
     /// ```
-    pub fn scramble(&mut self, move_count: u8) {
-        let moves = [
-            Move::F,
-            Move::R,
-            Move::U,
-            Move::B,
-            Move::L,
-            Move::D,
-            Move::FPrime,
-            Move::RPrime,
-            Move::UPrime,
-            Move::BPrime,
-            Move::LPrime,
-            Move::DPrime,
-        ];
-        let mut rng = thread_rng();
-        for _ in 0..move_count {
-            let m = rng.choose(&moves).unwrap();
-            self.apply_move(*m);
+    pub fn gods_number(self) -> u8 {
+        match self {
+            Metric::HalfTurn => 20,
+            Metric::QuarterTurn => 26,
         }
     }
+}
 
-    /// Apply a string of moves to a cube. Notation here: [http://rubiks.wikia.com/wiki/Notation](http://rubiks.wikia.com/wiki/Notation) 
-    ///
-    /// # Arguments
-    /// moves: A move or moves to apply to the cube, e.g. FRUU'R'F'
-    ///
-    /// # Panics
-    /// This function will panic if it encounters an invalid character. Allowed characters are FRUBLD, ', `, \u{2032} (prime), and 2.
-    ///
-    /// # Example
-    /// ```
-    /// use rubik::cube::*;
-    ///
-    /// let mut cube = Cube::new();
-    /// // Scramble the cube a little
-    /// cube.apply_moves("FUR");
-    /// assert!(!cube.is_solved());
-    ///
-    /// // Undo the moves
-    /// cube.apply_moves("R'U'F'");
-    /// assert!(cube.is_solved());
-    /// ```
-    pub fn apply_moves(&mut self, moves: &str) {
-        use self::Move;
-        let mut movelist: Vec<Move> = Vec::new();
-        let mut prevch = 'X';
-        for ch in moves.chars() {
-            match ch {
-                'F' | 'R' | 'U' | 'B' | 'L' | 'D' => {
-                    movelist.push(Move::from(ch));
-                },
-                '2' => {
-                    movelist.push(Move::from(prevch));
-                },
-                '\'' | '`' | '\u{2032}' => {
-                    match prevch {
-                        'F' | 'R' | 'U' | 'B' | 'L' | 'D' => {
-                            movelist.pop();
-                            let mut s = prevch.to_string();
-                            s.push('\'');
-                            movelist.push(Move::from(s.as_str()));
-                        },
-                        _ => {
-                            panic!("Invalid character combination: {}{}", prevch, ch);
-                        },
-                    }
-                },
-                _ => {
-                    panic!("Unrecognized move: {}", ch);
+/// Count the moves in a sequence in the half turn metric (HTM), where any turn
+/// of a face counts as one move. Slice moves count as two since they turn the
+/// two faces on either side of the slice.
+///
+/// # Example
+/// ```
+/// use rubik::cube::*;
+///
+/// assert_eq!(count_htm(&[Move::R, Move::U2, Move::M]), 4);
+/// ```
+pub fn count_htm(moves: &[Move]) -> usize {
+    moves.iter().map(|m| match m.rotation() {
+        (_, Layer::Middle, _) => 2,
+        _ => 1,
+    }).sum()
+}
+
+/// Count the moves in a sequence in the quarter turn metric (QTM), where a
+/// quarter turn of a face counts as one move and a half turn counts as two.
+/// Slice moves count as two quarter turns of the faces on either side.
+///
+/// # Example
+/// ```
+/// use rubik::cube::*;
+///
+/// assert_eq!(count_qtm(&[Move::R, Move::U2, Move::M]), 5);
+/// ```
+pub fn count_qtm(moves: &[Move]) -> usize {
+    moves.iter().map(|m| match m.rotation() {
+        (_, Layer::Middle, turns) => 2 * turns.unsigned_abs() as usize,
+        (_, _, turns) => turns.unsigned_abs() as usize,
+    }).sum()
+}
+
+/// Count the moves in a sequence in the slice turn metric (STM), where any
+/// turn of a face or a slice counts as one move
+///
+/// # Example
+/// ```
+/// use rubik::cube::*;
+///
+/// assert_eq!(count_stm(&[Move::R, Move::U2, Move::M]), 3);
+/// ```
+pub fn count_stm(moves: &[Move]) -> usize {
+    moves.len()
+}
+
+/// Expand every half turn in a sequence of moves into two clockwise quarter
+/// turns of the same face, e.g. `F2` becomes `F F`. Quarter turns and slice
+/// moves are left alone, and the result has the same effect on a cube as the
+/// original sequence. This is the opposite of `simplify_moves`.
+///
+/// # Example
+/// ```
+/// use rubik::cube::*;
+///
+/// let moves = [Move::R, Move::U2, Move::FPrime];
+/// assert_eq!(to_quarter_turns(&moves), vec![Move::R, Move::U, Move::U, Move::FPrime]);
+/// ```
+pub fn to_quarter_turns(moves: &[Move]) -> Vec<Move> {
+    moves.iter().flat_map(|&m| match layer_turns(m) {
+        (layer, 2) => vec![layer, layer],
+        _ => vec![m],
+    }).collect()
+}
+
+/// Simplify a sequence of moves by merging consecutive turns of the same layer
+/// and dropping any that cancel out, e.g. `F F` becomes `F2` and `R U U' R'`
+/// disappears entirely. Merging is repeated until nothing else can be merged,
+/// and the result has the same effect on a cube as the original sequence.
+///
+/// # Example
+/// ```
+/// use rubik::cube::*;
+///
+/// let moves = [Move::F, Move::R, Move::RPrime, Move::F, Move::U];
+/// assert_eq!(simplify_moves(&moves), vec![Move::F2, Move::U]);
+/// ```
+pub fn simplify_moves(moves: &[Move]) -> Vec<Move> {
+    merge_layer_turns(moves).into_iter()
+        .flat_map(|(layer, count)| layer_moves(layer, count))
+        .collect()
+}
+
+/// Merge consecutive turns of the same layer, dropping any that cancel out.
+/// Returns the clockwise quarter turn of each remaining layer turn with its
+/// number of clockwise quarter turns (1, 2, or 3).
+fn merge_layer_turns(moves: &[Move]) -> Vec<(Move, u8)> {
+    let mut turns: Vec<(Move, u8)> = Vec::new();
+    for &m in moves {
+        let (layer, count) = layer_turns(m);
+        match turns.last().cloned() {
+            Some((last_layer, last_count)) if last_layer == layer => {
+                turns.pop();
+                let total = (last_count + count) % 4;
+                if total != 0 {
+                    turns.push((layer, total));
                 }
+            },
+            _ => turns.push((layer, count)),
+        }
+    }
+
+    turns
+}
+
+/// Put a sequence of moves in a canonical form, so that sequences that are the
+/// same up to a rotation of the whole cube once they've been simplified come
+/// out the same. The moves are simplified with `simplify_moves`, then the faces
+/// are relabeled by whichever of the 24 rotations gives the smallest sequence
+/// when the moves are compared by their `u8` indices.
+///
+/// Sequences that have the same effect aren't necessarily the same after
+/// simplifying, since only consecutive turns of the same layer are merged.
+/// For example `R L R'` and `L` do the same thing but have different forms.
+///
+/// # Example
+/// ```
+/// use rubik::cube::*;
+///
+/// // The same trigger done on the right and on the front
+/// let right = [Move::R, Move::U, Move::RPrime, Move::UPrime];
+/// let front = [Move::F, Move::U, Move::FPrime, Move::UPrime];
+/// assert_eq!(canonicalize(&right), canonicalize(&front));
+/// ```
+pub fn canonicalize(moves: &[Move]) -> Vec<Move> {
+    let simplified = simplify_moves(moves);
+    cube_rotations().iter()
+        .map(|rotation| simplified.iter().flat_map(|&m| rotate_move(m, rotation)).collect::<Vec<Move>>())
+        .min_by_key(|rotated| rotated.iter().map(|&m| u8::from(m)).collect::<Vec<u8>>())
+        .unwrap()
+}
+
+/// Get every rotation of the whole cube, as the faces that F, R, U, B, L, and
+/// D are moved to
+fn cube_rotations() -> Vec<[Move; 6]> {
+    // Rotating the whole cube the same way as R and as U generates the rest
+    let x = [Move::U, Move::R, Move::B, Move::D, Move::L, Move::F];
+    let y = [Move::L, Move::F, Move::U, Move::R, Move::B, Move::D];
+
+    let mut rotations = vec![[Move::F, Move::R, Move::U, Move::B, Move::L, Move::D]];
+    let mut i = 0;
+    while i < rotations.len() {
+        for generator in &[x, y] {
+            let mut rotation = rotations[i];
+            for face in rotation.iter_mut() {
+                *face = generator[face_turn_index(*face)];
+            }
+            if !rotations.contains(&rotation) {
+                rotations.push(rotation);
+            }
+        }
+        i += 1;
+    }
+
+    rotations
+}
+
+/// Get the index of a face's clockwise quarter turn in the order F, R, U, B,
+/// L, D
+fn face_turn_index(face: Move) -> usize {
+    QUARTER_TURNS[..6].iter().position(|&f| f == face).unwrap()
+}
+
+/// Get the moves that do the same thing as a move after the whole cube has
+/// been rotated (see `cube_rotations`). A slice moves to the slice next to
+/// wherever the face it turns with went.
+fn rotate_move(m: Move, rotation: &[Move; 6]) -> impl Iterator<Item = Move> {
+    let (layer, turns) = layer_turns(m);
+    match layer {
+        Move::M | Move::E | Move::S => {
+            let face = match layer {
+                Move::M => Move::L,
+                Move::E => Move::D,
+                _ => Move::F,
+            };
+            let (slice, direction) = layer_turns(wide_slice(rotation[face_turn_index(face)]));
+            layer_moves(slice, direction * turns % 4)
+        },
+        _ => layer_moves(rotation[face_turn_index(layer)], turns),
+    }
+}
+
+/// A face of a cubie (corresponds to a single color sticker on a real cube)
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Face {
+    F,
+    R,
+    U,
+    B,
+    L,
+    D,
+}
+
+/// All of the faces in URFDLB order
+const FACES: [Face; 6] = [Face::U, Face::R, Face::F, Face::D, Face::L, Face::B];
+
+/// Create a Face from a char. See
+/// [http://rubiks.wikia.com/wiki/Notation](http://rubiks.wikia.com/wiki/Notation) 
+/// for notation.
+///
+/// # Panics
+/// This function will panic if the input char isn't a valid face, i.e. not one of FRUBLD.
+impl From<char> for Face {
+    fn from(ch: char) -> Face {
+        match ch {
+            'F' => Face::F,
+            'R' => Face::R,
+            'U' => Face::U,
+            'B' => Face::B,
+            'L' => Face::L,
+            'D' => Face::D,
+            _ => panic!("Invalid face name: {}", ch),
+        }
+    }
+}
+
+impl Face {
+    /// Get the face on the other side of the cube
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// assert_eq!(Face::U.opposite(), Face::D);
+    /// assert_eq!(Face::L.opposite(), Face::R);
+    /// ```
+    pub fn opposite(self) -> Face {
+        match self {
+            Face::F => Face::B,
+            Face::R => Face::L,
+            Face::U => Face::D,
+            Face::B => Face::F,
+            Face::L => Face::R,
+            Face::D => Face::U,
+        }
+    }
+
+    /// Determine whether two faces share an edge. A face isn't adjacent to
+    /// itself or to its opposite.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// assert!(Face::U.is_adjacent(Face::F));
+    /// assert!(!Face::U.is_adjacent(Face::D));
+    /// assert!(!Face::U.is_adjacent(Face::U));
+    /// ```
+    pub fn is_adjacent(self, other: Face) -> bool {
+        other != self && other != self.opposite()
+    }
+}
+
+/// The corner of a face that sticker indices start from. Stickers are always
+/// read row by row, moving away from the origin: e.g. `BottomLeft` reads the
+/// bottom row from left to right, then the middle row, then the top row.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Origin {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Ways of unfolding the cube into a net. Every style has U above F and D below
+/// it; they differ in where the back face goes.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum NetStyle {
+    /// L, F, R, and B in a row, as printed by `Cube::print`. B is seen from
+    /// behind with U at the top, which is how `get_face` returns it.
+    Horizontal,
+    /// The cross unfold with B hanging below D, so B is turned upside down
+    /// with D at the top
+    Vertical,
+    /// B as seen from the front through the cube, so it's mirrored left to
+    /// right compared to `Horizontal`
+    Transparent,
+}
+
+/// Takes a symmetric group, given as its elements and a function mapping each
+/// element to its image, and returns a vector representing its disjoint cycles
+/// including cycles with length 1.
+fn disjoint_cycle_decompose<T: Copy + Eq, F: Fn(T) -> T>(elements: &[T], image: F) -> Vec<Vec<T>> {
+    let mut cycles: Vec<Vec<T>> = Vec::new();
+    let mut current_cycle: Vec<T> = Vec::new();
+    let mut used_values: Vec<T> = Vec::new();
+    for &v in elements {
+        if used_values.contains(&v) {
+            continue;
+        }
+
+        current_cycle.push(v);
+        let mut current_value = v;
+        loop {
+            if (current_cycle.len() > 1 && current_cycle[0] == current_value)
+            || (used_values.contains(&current_value)) {
+                current_cycle.pop();
+                cycles.push(current_cycle.clone());
+                current_cycle = Vec::new();
+                break;
+            }
+            used_values.push(current_value);
+            current_value = image(current_value);
+            current_cycle.push(current_value);
+        }
+    }
+
+    // Sort the cycles by length
+    cycles.sort_by_key(|k| k.len());
+    cycles.reverse();
+
+    cycles
+}
+
+#[derive(Copy, Clone, Eq, Hash, PartialEq)]
+/// Map corner cubicles to cubies. The cubie in each cubicle is stored at the
+/// cubicle's index in `CORNERS`.
+struct CornerPermutation {
+    map: [Corner; 8],
+}
+
+impl Default for CornerPermutation {
+    /// The default mapping has each cubie in its corresponding cubicle
+    fn default() -> CornerPermutation {
+        CornerPermutation {
+            map: CORNERS,
+        }
+    }
+}
+
+impl fmt::Debug for CornerPermutation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cycles = disjoint_cycle_decompose(&CORNERS, |c| self.get(c));
+        write!(f, "{:?}", cycles)
+    }
+}
+
+impl CornerPermutation {
+    /// Create a solved permutation
+    pub fn new() -> CornerPermutation {
+        CornerPermutation::default()
+    }
+
+    /// Get a the cubie located in a particular cubicle
+    pub fn get(&self, cubicle: Corner) -> Corner {
+        self.map[cubicle as usize]
+    }
+
+    /// Get the cubie index located in each cubicle, in cubicle order
+    pub fn to_array(self) -> [u8; 8] {
+        let mut a = [0u8; 8];
+        for (i, &c) in self.map.iter().enumerate() {
+            a[i] = c as u8;
+        }
+        a
+    }
+
+    /// Create a permutation from the cubie index located in each cubicle
+    pub fn from_array(a: &[u8; 8]) -> CornerPermutation {
+        let mut m = CORNERS;
+        for (i, &c) in a.iter().enumerate() {
+            m[i] = CORNERS[c as usize];
+        }
+
+        CornerPermutation {
+            map: m,
+        }
+    }
+
+    /// Apply a move and determine which cubies end up where
+    pub fn permute(&mut self, m: Move) {
+        use self::Move;
+        use self::Corner;
+        let cycle = match m {
+            Move::F | Move::F2 => {
+                (Corner::URF, Corner::DFR, Corner::DLF, Corner::UFL)
+            },
+            Move::R | Move::R2 => {
+                (Corner::UBR, Corner::DRB, Corner::DFR, Corner::URF)
+            },
+            Move::U | Move::U2 => {
+                (Corner::URF, Corner::UFL, Corner::ULB, Corner::UBR)
+            },
+            Move::B | Move::B2 => {
+                (Corner::ULB, Corner::DBL, Corner::DRB, Corner::UBR)
+            },
+            Move::L | Move::L2 => {
+                (Corner::UFL, Corner::DLF, Corner::DBL, Corner::ULB)
+            },
+            Move::D | Move::D2 => {
+                (Corner::DRB, Corner::DBL, Corner::DLF, Corner::DFR)
+            },
+            Move::FPrime => {
+                (Corner::URF, Corner::UFL, Corner::DLF, Corner::DFR)
+            },
+            Move::RPrime => {
+                (Corner::UBR, Corner::URF, Corner::DFR, Corner::DRB)
+            },
+            Move::UPrime => {
+                (Corner::URF, Corner::UBR, Corner::ULB, Corner::UFL)
+            },
+            Move::BPrime => {
+                (Corner::ULB, Corner::UBR, Corner::DRB, Corner::DBL)
+            },
+            Move::LPrime => {
+                (Corner::UFL, Corner::ULB, Corner::DBL, Corner::DLF)
+            },
+            Move::DPrime => {
+                (Corner::DRB, Corner::DFR, Corner::DLF, Corner::DBL)
+            },
+            Move::M | Move::E | Move::S | Move::MPrime | Move::EPrime | Move::SPrime => {
+                // Slice moves don't move any corners
+                return;
+            },
+        };
+
+        // Half turns go around the cycle twice
+        let times = if layer_turns(m).1 == 2 { 2 } else { 1 };
+        for _ in 0..times {
+            let old_map = self.map;
+            self.map[cycle.1 as usize] = old_map[cycle.0 as usize];
+            self.map[cycle.2 as usize] = old_map[cycle.1 as usize];
+            self.map[cycle.3 as usize] = old_map[cycle.2 as usize];
+            self.map[cycle.0 as usize] = old_map[cycle.3 as usize];
+        }
+    }
+}
+
+#[derive(Copy, Clone, Eq, Hash, PartialEq)]
+/// Map edge cubicles to cubies. The cubie in each cubicle is stored at the
+/// cubicle's index in `EDGES`.
+struct EdgePermutation {
+    map: [Edge; 12],
+}
+
+impl Default for EdgePermutation {
+    /// The default mapping has each cubie in its corresponding cubicle
+    fn default() -> EdgePermutation {
+        EdgePermutation {
+            map: EDGES,
+        }
+    }
+}
+
+impl fmt::Debug for EdgePermutation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cycles = disjoint_cycle_decompose(&EDGES, |e| self.get(e));
+        write!(f, "{:?}", cycles)
+    }
+}
+
+impl EdgePermutation {
+    /// Create a new solved permutation
+    pub fn new() -> EdgePermutation {
+        EdgePermutation::default()
+    }
+
+    /// Get a the cubie located in a particular cubicle
+    pub fn get(&self, cubicle: Edge) -> Edge {
+        self.map[cubicle as usize]
+    }
+
+    /// Get the cubie index located in each cubicle, in cubicle order
+    pub fn to_array(self) -> [u8; 12] {
+        let mut a = [0u8; 12];
+        for (i, &e) in self.map.iter().enumerate() {
+            a[i] = e as u8;
+        }
+        a
+    }
+
+    /// Create a permutation from the cubie index located in each cubicle
+    pub fn from_array(a: &[u8; 12]) -> EdgePermutation {
+        let mut m = EDGES;
+        for (i, &e) in a.iter().enumerate() {
+            m[i] = EDGES[e as usize];
+        }
+
+        EdgePermutation {
+            map: m,
+        }
+    }
+
+    /// Apply a move and determine which cubies end up where
+    pub fn permute(&mut self, m: Move) {
+        use self::Move;
+        let cycle = match m {
+            Move::F | Move::F2 => {
+                (Edge::UF, Edge::RF, Edge::DF, Edge::LF)
+            },
+            Move::R | Move::R2 => {
+                (Edge::UR, Edge::RB, Edge::DR, Edge::RF)
+            },
+            Move::U | Move::U2 => {
+                (Edge::UB, Edge::UR, Edge::UF, Edge::UL)
+            },
+            Move::B | Move::B2 => {
+                (Edge::UB, Edge::LB, Edge::DB, Edge::RB)
+            },
+            Move::L | Move::L2 => {
+                (Edge::UL, Edge::LF, Edge::DL, Edge::LB)
+            },
+            Move::D | Move::D2 => {
+                (Edge::DF, Edge::DR, Edge::DB, Edge::DL)
+            },
+            Move::FPrime => {
+                (Edge::UF, Edge::LF, Edge::DF, Edge::RF)
+            },
+            Move::RPrime => {
+                (Edge::UR, Edge::RF, Edge::DR, Edge::RB)
+            },
+            Move::UPrime => {
+                (Edge::UB, Edge::UL, Edge::UF, Edge::UR)
+            },
+            Move::BPrime => {
+                (Edge::UB, Edge::RB, Edge::DB, Edge::LB)
+            },
+            Move::LPrime => {
+                (Edge::UL, Edge::LB, Edge::DL, Edge::LF)
+            },
+            Move::DPrime => {
+                (Edge::DF, Edge::DL, Edge::DB, Edge::DR)
+            },
+            Move::M => {
+                (Edge::UB, Edge::UF, Edge::DF, Edge::DB)
+            },
+            Move::E => {
+                (Edge::LF, Edge::RF, Edge::RB, Edge::LB)
+            },
+            Move::S => {
+                (Edge::UL, Edge::UR, Edge::DR, Edge::DL)
+            },
+            Move::MPrime => {
+                (Edge::UB, Edge::DB, Edge::DF, Edge::UF)
+            },
+            Move::EPrime => {
+                (Edge::LF, Edge::LB, Edge::RB, Edge::RF)
+            },
+            Move::SPrime => {
+                (Edge::UL, Edge::DL, Edge::DR, Edge::UR)
+            },
+        };
+
+        // Half turns go around the cycle twice
+        let times = if layer_turns(m).1 == 2 { 2 } else { 1 };
+        for _ in 0..times {
+            let old_map = self.map;
+            self.map[cycle.1 as usize] = old_map[cycle.0 as usize];
+            self.map[cycle.2 as usize] = old_map[cycle.1 as usize];
+            self.map[cycle.3 as usize] = old_map[cycle.2 as usize];
+            self.map[cycle.0 as usize] = old_map[cycle.3 as usize];
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+/// Corner orientation state
+struct X(u8,u8,u8,u8,u8,u8,u8,u8);
+
+impl X {
+    /// Get the orientations as an array in cubicle order
+    fn to_array(self) -> [u8; 8] {
+        [self.0, self.1, self.2, self.3, self.4, self.5, self.6, self.7]
+    }
+
+    /// Create an orientation vector from an array in cubicle order
+    fn from_array(a: &[u8; 8]) -> X {
+        X(a[0], a[1], a[2], a[3], a[4], a[5], a[6], a[7])
+    }
+}
+
+/// Get the changes a move makes to the corner orientations: the cubicle each
+/// orientation moves from, and the amount added to it afterwards
+fn corner_orientation_change(m: Move) -> ([u8; 8], [u8; 8]) {
+    match m {
+        Move::F => ([5,0,2,3,4,6,1,7], [1,2,0,0,0,2,1,0]),
+        Move::R => ([0,6,1,3,4,5,7,2], [0,1,2,0,0,0,2,1]),
+        Move::U => ([1,2,3,0,4,5,6,7], [0u8; 8]),
+        Move::B => ([0,1,7,2,3,5,6,4], [0,0,1,2,1,0,0,2]),
+        Move::L => ([3,1,2,4,5,0,6,7], [2,0,0,1,2,1,0,0]),
+        Move::D => ([0,1,2,3,7,4,5,6], [0u8; 8]),
+        Move::FPrime => ([1,6,2,3,4,0,5,7], [1,2,0,0,0,2,1,0]),
+        Move::RPrime => ([0,2,7,3,4,5,1,6], [0,1,2,0,0,0,2,1]),
+        Move::UPrime => ([3,0,1,2,4,5,6,7], [0u8; 8]),
+        Move::BPrime => ([0,1,3,4,7,5,6,2], [0,0,1,2,1,0,0,2]),
+        Move::LPrime => ([5,1,2,0,3,4,6,7], [2,0,0,1,2,1,0,0]),
+        Move::DPrime => ([0,1,2,3,5,6,7,4], [0u8; 8]),
+        Move::F2 => ([6,5,2,3,4,1,0,7], [0u8; 8]),
+        Move::R2 => ([0,7,6,3,4,5,2,1], [0u8; 8]),
+        Move::U2 => ([2,3,0,1,4,5,6,7], [0u8; 8]),
+        Move::B2 => ([0,1,4,7,2,5,6,3], [0u8; 8]),
+        Move::L2 => ([4,1,2,5,0,3,6,7], [0u8; 8]),
+        Move::D2 => ([0,1,2,3,6,7,4,5], [0u8; 8]),
+        Move::M | Move::E | Move::S |
+        Move::MPrime | Move::EPrime | Move::SPrime => ([0,1,2,3,4,5,6,7], [0u8; 8]),
+    }
+}
+
+/// Swap values in an X vector
+fn swap_x(values: X, indices: &[u8; 8]) -> X {
+    let mut swapped = [0u8; 8];
+    for (curr, &i) in indices.iter().enumerate() {
+        swapped[curr] = match i {
+            0 => values.0,
+            1 => values.1,
+            2 => values.2,
+            3 => values.3,
+            4 => values.4,
+            5 => values.5,
+            6 => values.6,
+            7 => values.7,
+            _ => panic!("Invalid index for X tuple"),
+        };
+    }
+
+    X(
+        swapped[0],
+        swapped[1],
+        swapped[2],
+        swapped[3],
+        swapped[4],
+        swapped[5],
+        swapped[6],
+        swapped[7],
+    )
+}
+
+/// Add values to an X vector
+fn add_x(values: X, addends: &[u8; 8]) -> X {
+    X(
+        (values.0 + addends[0]) % 3,
+        (values.1 + addends[1]) % 3,
+        (values.2 + addends[2]) % 3,
+        (values.3 + addends[3]) % 3,
+        (values.4 + addends[4]) % 3,
+        (values.5 + addends[5]) % 3,
+        (values.6 + addends[6]) % 3,
+        (values.7 + addends[7]) % 3,
+    )
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+/// Edge orientation state
+struct Y(u8,u8,u8,u8,u8,u8,u8,u8,u8,u8,u8,u8);
+
+impl Y {
+    /// Get the orientations as an array in cubicle order
+    fn to_array(self) -> [u8; 12] {
+        [
+            self.0, self.1, self.2, self.3, self.4, self.5,
+            self.6, self.7, self.8, self.9, self.10, self.11,
+        ]
+    }
+
+    /// Create an orientation vector from an array in cubicle order
+    fn from_array(a: &[u8; 12]) -> Y {
+        Y(a[0], a[1], a[2], a[3], a[4], a[5], a[6], a[7], a[8], a[9], a[10], a[11])
+    }
+}
+
+/// Swap values in a Y vector
+fn swap_y(values: Y, indices: &[u8; 12]) -> Y {
+    let mut swapped = [0u8; 12];
+    for (curr, &i) in indices.iter().enumerate() {
+        swapped[curr] = match i {
+            0 => values.0,
+            1 => values.1,
+            2 => values.2,
+            3 => values.3,
+            4 => values.4,
+            5 => values.5,
+            6 => values.6,
+            7 => values.7,
+            8 => values.8,
+            9 => values.9,
+            10 => values.10,
+            11 => values.11,
+            _ => panic!("Invalid index for Y tuple"),
+        };
+    }
+
+    Y(
+        swapped[0],
+        swapped[1],
+        swapped[2],
+        swapped[3],
+        swapped[4],
+        swapped[5],
+        swapped[6],
+        swapped[7],
+        swapped[8],
+        swapped[9],
+        swapped[10],
+        swapped[11],
+    )
+}
+
+/// Add values to a Y vector
+fn add_y(values: Y, addends: &[u8; 12]) -> Y {
+    Y(
+        (values.0 + addends[0]) % 2,
+        (values.1 + addends[1]) % 2,
+        (values.2 + addends[2]) % 2,
+        (values.3 + addends[3]) % 2,
+        (values.4 + addends[4]) % 2,
+        (values.5 + addends[5]) % 2,
+        (values.6 + addends[6]) % 2,
+        (values.7 + addends[7]) % 2,
+        (values.8 + addends[8]) % 2,
+        (values.9 + addends[9]) % 2,
+        (values.10 + addends[10]) % 2,
+        (values.11 + addends[11]) % 2,
+    )
+}
+
+/// The number of legal cube states, i.e. 8! * 12! / 2 * 3^7 * 2^11
+pub const STATE_COUNT: u128 = 43_252_003_274_489_856_000;
+
+/// Number of distinct edge permutations with a given parity (12! / 2)
+const EDGE_PERMUTATIONS_BY_PARITY: u128 = 239_500_800;
+
+/// Number of corner orientation states (3^7)
+const CORNER_ORIENTATIONS: u128 = 2187;
+
+/// Number of edge orientation states (2^11)
+const EDGE_ORIENTATIONS: u128 = 2048;
+
+/// Compute the Lehmer code of a permutation, i.e. for each element the number
+/// of later elements that are smaller than it
+fn lehmer_code(perm: &[u8]) -> Vec<u8> {
+    perm.iter().enumerate().map(
+        |(i, &p)| perm[i+1..].iter().filter(|&&q| q < p).count() as u8
+    ).collect()
+}
+
+/// The number of ways to choose `k` things from `n`
+fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    (0..k).fold(1, |c, i| c * (n - i) / (i + 1))
+}
+
+/// Rank a permutation in lexicographic order
+fn rank_permutation(perm: &[u8]) -> u64 {
+    let n = perm.len() as u64;
+    lehmer_code(perm).iter().enumerate().fold(
+        0, |rank, (i, &d)| rank * (n - i as u64) + d as u64
+    )
+}
+
+/// Build a permutation from its lexicographic rank
+fn unrank_permutation(mut rank: u64, perm: &mut [u8]) {
+    let n = perm.len();
+    let mut digits = vec![0u8; n];
+    for i in (0..n).rev() {
+        let base = (n - i) as u64;
+        digits[i] = (rank % base) as u8;
+        rank /= base;
+    }
+
+    let mut remaining: Vec<u8> = (0..n as u8).collect();
+    for (i, &d) in digits.iter().enumerate() {
+        perm[i] = remaining.remove(d as usize);
+    }
+}
+
+/// Determine whether a permutation is odd
+fn permutation_parity(perm: &[u8]) -> bool {
+    lehmer_code(perm).iter().fold(0u32, |sum, &d| sum + d as u32) % 2 == 1
+}
+
+/// An error describing why a cube state is invalid
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum CubeError {
+    /// The cubie positions aren't a permutation (a cubie is missing or repeated)
+    InvalidPermutation,
+    /// A cubie orientation is out of range
+    InvalidOrientation,
+    /// The state is well-formed but can't be reached from the solved cube
+    Illegal,
+    /// The input has the wrong number or length of fields
+    InvalidFormat,
+    /// The input contains a character that isn't a face name
+    InvalidCharacter(char),
+    /// A set of stickers doesn't match any real cubie
+    InvalidCubie,
+    /// The requested search is too deep to be done in a reasonable amount of memory
+    TooDeep,
+    /// The input has more moves than allowed
+    TooLong,
+    /// A slice move can't be written in the requested notation
+    SliceMove(Move),
+}
+
+impl fmt::Display for CubeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CubeError::InvalidPermutation => write!(f, "Cubie positions are not a permutation"),
+            CubeError::InvalidOrientation => write!(f, "Cubie orientation is out of range"),
+            CubeError::Illegal => write!(f, "Cube state is not reachable from the solved state"),
+            CubeError::InvalidFormat => write!(f, "Input is not formatted correctly"),
+            CubeError::InvalidCharacter(ch) => write!(f, "Invalid face name: {}", ch),
+            CubeError::InvalidCubie => write!(f, "Stickers do not match any cubie"),
+            CubeError::TooDeep => write!(f, "Search depth is too large"),
+            CubeError::TooLong => write!(f, "Too many moves"),
+            CubeError::SliceMove(m) => write!(f, "Slice move {} can't be written in this notation", m),
+        }
+    }
+}
+
+impl Error for CubeError {}
+
+impl From<MoveParseError> for CubeError {
+    fn from(e: MoveParseError) -> CubeError {
+        CubeError::InvalidCharacter(e.character)
+    }
+}
+
+/// An error describing why a set of stickers doesn't describe a cube
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum FaceletError {
+    /// The input has the wrong number of stickers or faces, or a center
+    /// sticker is in the wrong place
+    InvalidFormat,
+    /// The input contains a character that isn't a face name
+    InvalidCharacter(char),
+    /// A color doesn't appear on exactly nine stickers
+    ColorCount(Face),
+    /// A set of stickers doesn't match any real cubie
+    InvalidCubie,
+    /// The stickers match real cubies, but the cube they make is invalid
+    InvalidState(CubeError),
+}
+
+impl fmt::Display for FaceletError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FaceletError::InvalidFormat => write!(f, "Stickers are not formatted correctly"),
+            FaceletError::InvalidCharacter(ch) => write!(f, "Invalid face name: {}", ch),
+            FaceletError::ColorCount(face) => write!(f, "There are not nine {:?} stickers", face),
+            FaceletError::InvalidCubie => write!(f, "Stickers do not match any cubie"),
+            FaceletError::InvalidState(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for FaceletError {}
+
+/// The stickers on every face of a cube, in a form `Cube::from_faces` can read
+pub trait FaceStickers {
+    /// Get the colors of the stickers on each face, laid out like
+    /// `Cube::get_face`, checking that each face is given exactly once
+    fn face_stickers(&self) -> Result<HashMap<Face, [Face; 9]>, FaceletError>;
+}
+
+/// The center stickers tell us which character belongs to which face
+impl FaceStickers for HashMap<Face, [char; 9]> {
+    fn face_stickers(&self) -> Result<HashMap<Face, [Face; 9]>, FaceletError> {
+        if FACES.iter().any(|f| !self.contains_key(f)) {
+            return Err(FaceletError::InvalidFormat);
+        }
+
+        let mut colors: HashMap<char, Face> = HashMap::new();
+        for &face in &FACES {
+            if colors.insert(self[&face][4], face).is_some() {
+                return Err(FaceletError::InvalidFormat);
+            }
+        }
+
+        let mut stickers: HashMap<Face, [Face; 9]> = HashMap::new();
+        for &face in &FACES {
+            let mut converted = [face; 9];
+            for (i, ch) in self[&face].iter().enumerate() {
+                converted[i] = *colors.get(ch).ok_or(FaceletError::InvalidCharacter(*ch))?;
+            }
+            stickers.insert(face, converted);
+        }
+        Ok(stickers)
+    }
+}
+
+/// The faces can be in any order, but each must have its own color in the center
+impl FaceStickers for [(Face, [Face; 9]); 6] {
+    fn face_stickers(&self) -> Result<HashMap<Face, [Face; 9]>, FaceletError> {
+        let mut stickers: HashMap<Face, [Face; 9]> = HashMap::new();
+        for &(face, face_stickers) in self {
+            if face_stickers[4] != face || stickers.insert(face, face_stickers).is_some() {
+                return Err(FaceletError::InvalidFormat);
+            }
+        }
+        Ok(stickers)
+    }
+}
+
+/// A reason that a cube state can't be reached from the solved state
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Illegality {
+    /// The same corner cubie is in more than one cubicle
+    DuplicateCorner,
+    /// The same edge cubie is in more than one cubicle
+    DuplicateEdge,
+    /// Fewer than nine stickers of a color are visible
+    MissingColor(Face),
+    /// The corner twists don't cancel out, e.g. a single corner is twisted
+    CornerTwist,
+    /// The edge flips don't cancel out, e.g. a single edge is flipped
+    EdgeFlip,
+    /// The corner and edge permutations have different parities, e.g. two
+    /// pieces are swapped
+    PermutationParity,
+    /// The edges in two cubicles are swapped and every other piece is in
+    /// place. The cubicles are given by their faces.
+    SwappedEdges([Face; 2], [Face; 2]),
+    /// The corners in two cubicles are swapped and every other piece is in
+    /// place. The cubicles are given by their faces.
+    SwappedCorners([Face; 3], [Face; 3]),
+}
+
+impl fmt::Display for Illegality {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Illegality::DuplicateCorner => write!(f, "A corner appears more than once"),
+            Illegality::DuplicateEdge => write!(f, "An edge appears more than once"),
+            Illegality::MissingColor(face) => write!(f, "Fewer than nine {:?} stickers", face),
+            Illegality::CornerTwist => write!(f, "A corner is twisted"),
+            Illegality::EdgeFlip => write!(f, "An edge is flipped"),
+            Illegality::PermutationParity => write!(f, "Two pieces are swapped"),
+            Illegality::SwappedEdges(a, b) => write!(f, "The {:?}{:?} and {:?}{:?} edges are swapped", a[0], a[1], b[0], b[1]),
+            Illegality::SwappedCorners(a, b) => {
+                write!(f, "The {:?}{:?}{:?} and {:?}{:?}{:?} corners are swapped", a[0], a[1], a[2], b[0], b[1], b[2])
+            },
+        }
+    }
+}
+
+impl Error for Illegality {}
+
+/// Determine whether an array contains every number in `0..len` exactly once
+fn is_permutation(values: &[u8]) -> bool {
+    let mut seen = vec![false; values.len()];
+    for &v in values {
+        if v as usize >= values.len() || seen[v as usize] {
+            return false;
+        }
+        seen[v as usize] = true;
+    }
+    true
+}
+
+/// The serialized form of a cube. Unlike the internal representation, this
+/// layout is stable across versions of this crate.
+///
+/// Corners are listed in the cubicle order UFL, URF, UBR, ULB, DBL, DLF, DFR,
+/// DRB and edges in the cubicle order UB, UR, UF, UL, LB, RB, RF, LF, DB, DR,
+/// DF, DL. Each permutation entry is the index (in the same order) of the
+/// cubie located in that cubicle, and each orientation entry is the
+/// orientation of that cubie as defined in the Chen paper: 0..3 for corners
+/// and 0..2 for edges. The solved cube serializes as
+///
+/// ```text
+/// {
+///   "corner_permutation": [0, 1, 2, 3, 4, 5, 6, 7],
+///   "corner_orientation": [0, 0, 0, 0, 0, 0, 0, 0],
+///   "edge_permutation": [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+///   "edge_orientation": [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+/// }
+/// ```
+///
+/// Deserializing a `Cube` fails if the state isn't a legal cube.
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CubeState {
+    pub corner_permutation: [u8; 8],
+    pub corner_orientation: [u8; 8],
+    pub edge_permutation: [u8; 12],
+    pub edge_orientation: [u8; 12],
+}
+
+#[cfg(feature = "serde")]
+impl From<Cube> for CubeState {
+    fn from(cube: Cube) -> CubeState {
+        CubeState {
+            corner_permutation: cube.sigma.to_array(),
+            corner_orientation: cube.x.to_array(),
+            edge_permutation: cube.tau.to_array(),
+            edge_orientation: cube.y.to_array(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<CubeState> for Cube {
+    type Error = CubeError;
+
+    fn try_from(state: CubeState) -> Result<Cube, CubeError> {
+        Cube::from_arrays(
+            &state.corner_permutation,
+            &state.corner_orientation,
+            &state.edge_permutation,
+            &state.edge_orientation,
+        )
+    }
+}
+
+#[derive(Copy, Clone, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "CubeState", try_from = "CubeState"))]
+/// Maintain the state information for a Rubik's cube.
+///
+/// The state is a handful of small arrays, so `Cube` is `Copy` and cheap to
+/// pass around by value. Equal cubes hash equally, so cubes can be used as
+/// `HashSet` or `HashMap` keys, e.g. to track states a search has visited.
+pub struct Cube {
+    sigma: CornerPermutation,
+    tau: EdgePermutation,
+    x: X,
+    y: Y,
+}
+
+impl fmt::Debug for Cube {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\u{03c3} = {:?}\n\u{03c4} = {:?}\nx = {:?}\ny = {:?}",
+               self.sigma, self.tau, self.x, self.y)
+    }
+}
+
+impl fmt::Display for Cube {
+    /// Show the unfolded net of the cube, with each sticker as the letter of
+    /// the face it belongs on. The faces are laid out as follows:
+    ///   U
+    ///  LFRB
+    ///   D
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let net = format!("{}", Cube::new());
+    /// assert_eq!(net.lines().nth(4), Some("LLLFFFRRRBBB"));
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lines = self.net_lines();
+        for (i, line) in lines.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for Cube {
+    /// The default cube is in the solved state
+    fn default() -> Cube {
+        Cube {
+            sigma: CornerPermutation::new(),
+            tau: EdgePermutation::new(),
+            x: X::default(),
+            y: Y::default(),
+        }
+    }
+}
+
+impl Cube {
+    /// Create a new cube in the solved state.
+    pub fn new() -> Cube {
+        Cube::default()
+    }
+
+    /// Create a cube by applying a string of moves to a solved cube, and also
+    /// get a cleaned up version of the moves. Consecutive turns of the same
+    /// face are merged and turns that cancel out are removed, and the result
+    /// is written in standard notation with spaces between the moves.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let (cube, moves) = Cube::from_moves_canonical("RUU'FFFR2").unwrap();
+    /// assert_eq!(moves, "R F' R2");
+    ///
+    /// let mut expected = Cube::new();
+    /// expected.apply_moves("RF'R2");
+    /// assert_eq!(cube, expected);
+    /// ```
+    pub fn from_moves_canonical(s: &str) -> Result<(Cube, String), MoveParseError> {
+        let moves = parse_moves(s)?;
+        let mut cube = Cube::new();
+        for &m in &moves {
+            cube.apply_move(m);
+        }
+
+        let canonical: Vec<String> = merge_layer_turns(&moves).iter().map(
+            |&(layer, count)| layer_notation(layer, count)
+        ).collect();
+
+        Ok((cube, canonical.join(" ")))
+    }
+
+    /// Apply a random series of moves to scramble the cube and return the
+    /// moves that were applied
+    /// # Arguments
+    /// move_count: The number of random moves to apply to the cube.
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    /// 
+    /// let mut cube = Cube::new();
+    /// let moves = cube.scramble(20);
+    /// assert_eq!(moves.len(), 20);
+    /// assert!(!cube.is_solved());
+    /// ```
+    pub fn scramble(&mut self, move_count: u8) -> Vec<Move> {
+        self.scramble_with_rng(&mut thread_rng(), move_count)
+    }
+
+    /// Scramble the cube like `scramble`, but with random moves generated from
+    /// a seed so the scramble can be reproduced, e.g. in tests. Use
+    /// `scramble_from_seed` if scrambles need to stay the same in future
+    /// versions of this crate.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut a = Cube::new();
+    /// let moves = a.scramble_with_seed(20, 1234);
+    ///
+    /// let mut b = Cube::new();
+    /// assert_eq!(b.scramble_with_seed(20, 1234), moves);
+    /// assert_eq!(a, b);
+    /// ```
+    pub fn scramble_with_seed(&mut self, move_count: u8, seed: u64) -> Vec<Move> {
+        self.scramble_with_rng(&mut SplitMix64 { state: seed }, move_count)
+    }
+
+    /// Scramble the cube like `scramble`, but with random moves chosen by the
+    /// given random number generator. Passing a seeded generator makes the
+    /// scramble reproducible.
+    ///
+    /// # Example
+    /// ```
+    /// extern crate rand;
+    /// extern crate rubik;
+    ///
+    /// use rand::{SeedableRng, StdRng};
+    /// use rubik::cube::*;
+    ///
+    /// # fn main() {
+    /// let seed: &[_] = &[1, 2, 3, 4];
+    /// let mut a = Cube::new();
+    /// let moves = a.scramble_with_rng(&mut StdRng::from_seed(seed), 20);
+    ///
+    /// let mut b = Cube::new();
+    /// assert_eq!(b.scramble_with_rng(&mut StdRng::from_seed(seed), 20), moves);
+    /// assert_eq!(a, b);
+    /// # }
+    /// ```
+    pub fn scramble_with_rng<R: Rng>(&mut self, rng: &mut R, move_count: u8) -> Vec<Move> {
+        let mut moves = Vec::with_capacity(move_count as usize);
+        for _ in 0..move_count {
+            let m = *rng.choose(&QUARTER_TURNS).unwrap();
+            self.apply_move(m);
+            moves.push(m);
+        }
+        moves
+    }
+
+    /// Replace the cube's state with a legal state chosen uniformly at random
+    /// from all 43,252,003,274,489,856,000 of them. Unlike `scramble`, this
+    /// isn't biased towards states that are close to the current one.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut cube = Cube::new();
+    /// cube.scramble_random_state();
+    /// assert!(cube.is_valid());
+    /// ```
+    pub fn scramble_random_state(&mut self) {
+        let mut rng = thread_rng();
+
+        let mut corners = [0, 1, 2, 3, 4, 5, 6, 7];
+        let mut edges = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+        rng.shuffle(&mut corners);
+        rng.shuffle(&mut edges);
+        // Swapping two edges fixes the parity without favoring any state
+        if permutation_parity(&corners) != permutation_parity(&edges) {
+            edges.swap(0, 1);
+        }
+
+        // The last orientation is determined by the others
+        let mut x = [0u8; 8];
+        for o in x.iter_mut().take(7) {
+            *o = rng.gen_range(0, 3);
+        }
+        x[7] = (3 - x[..7].iter().sum::<u8>() % 3) % 3;
+
+        let mut y = [0u8; 12];
+        for o in y.iter_mut().take(11) {
+            *o = rng.gen_range(0, 2);
+        }
+        y[11] = y[..11].iter().sum::<u8>() % 2;
+
+        *self = Cube::from_arrays(&corners, &x, &edges, &y).unwrap();
+    }
+
+    /// Apply a reproducible series of random moves generated from a seed and
+    /// return the moves. The moves are generated by `ScrambleV1`, so a seed
+    /// gives the same scramble in every version of this crate.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut a = Cube::new();
+    /// let mut b = Cube::new();
+    /// assert_eq!(a.scramble_from_seed(42, 20), b.scramble_from_seed(42, 20));
+    /// assert_eq!(a, b);
+    /// ```
+    pub fn scramble_from_seed(&mut self, seed: u64, move_count: u8) -> Vec<Move> {
+        let moves = ScrambleV1::generate(seed, move_count);
+        for &m in &moves {
+            self.apply_move(m);
+        }
+        moves
+    }
+
+    /// Apply a string of moves to a cube. Notation here: [http://rubiks.wikia.com/wiki/Notation](http://rubiks.wikia.com/wiki/Notation) 
+    ///
+    /// # Arguments
+    /// moves: A move or moves to apply to the cube, e.g. FRUU'R'F'
+    ///
+    /// Wide moves turn a face along with the slice next to it, and can be
+    /// written either as `Rw` or `r`. Like slice moves, they're applied as if
+    /// the centers stayed put, so `Rw` has the same effect on the cube as `RM'`.
+    ///
+    /// Groups of moves in parentheses can be repeated by following them with a count, e.g. `(RUR'U')6` or
+    /// `(RUR'U')x6`.
+    ///
+    /// Whitespace between moves is ignored, so `R U R' U'` is the same as `RUR'U'`. A suffix has to come right
+    /// after its move, though: `R '` is an error. Lowercase face letters are always wide moves; no other lowercase
+    /// letters are moves.
+    ///
+    /// # Panics
+    /// This function will panic if it encounters an invalid character. Allowed characters are FRUBLD, MES, w, frubld
+    /// (wide moves), ', `, \u{2032} (prime), 2, parentheses with a repeat count, and whitespace. Use
+    /// `try_apply_moves` to handle invalid input without panicking.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut cube = Cube::new();
+    /// // Scramble the cube a little
+    /// cube.apply_moves("FUR");
+    /// assert!(!cube.is_solved());
+    ///
+    /// // Undo the moves
+    /// cube.apply_moves("R'U'F'");
+    /// assert!(cube.is_solved());
+    /// ```
+    pub fn apply_moves(&mut self, moves: &str) {
+        if let Err(e) = self.try_apply_moves(moves) {
+            panic!("{}", e);
+        }
+    }
+
+    /// Apply a string of moves to the cube and return it, for building up a
+    /// cube in one expression. See `apply_moves`.
+    ///
+    /// # Panics
+    /// This function will panic if it encounters an invalid character.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let chained = Cube::new().with_moves("RUR'U'");
+    ///
+    /// let mut in_place = Cube::new();
+    /// in_place.apply_moves("RUR'U'");
+    /// assert_eq!(chained, in_place);
+    ///
+    /// assert!(chained.with_moves("URU'R'").is_solved());
+    /// ```
+    pub fn with_moves(mut self, moves: &str) -> Cube {
+        self.apply_moves(moves);
+        self
+    }
+
+    /// Apply an algorithm to the cube and report the pieces it moves. The
+    /// effect only depends on the algorithm, not on the state of the cube
+    /// beforehand, and lists the cycles the algorithm moves cubies around in
+    /// the same way as `corner_cycles` and `edge_cycles` do for a cube.
+    /// Cubies that are only twisted or flipped in place aren't in any cycle.
+    ///
+    /// # Panics
+    /// This function will panic if it encounters an invalid character.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut cube = Cube::new();
+    /// let effect = cube.apply_algorithm("U");
+    /// assert_eq!(effect.corners, vec![vec![Corner::UFL, Corner::URF, Corner::UBR, Corner::ULB]]);
+    /// assert_eq!(effect.edges, vec![vec![Edge::UB, Edge::UL, Edge::UF, Edge::UR]]);
+    ///
+    /// // Undoing it moves the same pieces the other way
+    /// let effect = cube.apply_algorithm("U'");
+    /// assert_eq!(effect.corners, vec![vec![Corner::UFL, Corner::ULB, Corner::UBR, Corner::URF]]);
+    /// assert!(cube.is_solved());
+    /// ```
+    pub fn apply_algorithm(&mut self, moves: &str) -> AlgorithmEffect {
+        let algorithm = Cube::new().with_moves(moves);
+        *self = self.compose(&algorithm);
+
+        AlgorithmEffect {
+            corners: algorithm.corner_cycles(),
+            edges: algorithm.edge_cycles(),
+        }
+    }
+
+    /// Apply a string of moves to a cube, returning an error instead of
+    /// panicking if the string contains an invalid move. The whole string is
+    /// parsed before any moves are applied, so the cube is unchanged if there
+    /// is an error. Empty or whitespace-only input is a no-op.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut cube = Cube::new();
+    /// assert!(cube.try_apply_moves("FRU").is_ok());
+    /// assert!(cube.try_apply_moves("   ").is_ok());
+    ///
+    /// let err = cube.try_apply_moves("FRXU").unwrap_err();
+    /// assert_eq!(err.character, 'X');
+    /// assert_eq!(err.offset, 2);
+    /// ```
+    pub fn try_apply_moves(&mut self, moves: &str) -> Result<(), MoveParseError> {
+        for m in parse_moves(moves)? {
+            self.apply_move(m);
+        }
+
+        Ok(())
+    }
+
+    /// Apply a scramble written the way the WCA scramble program writes them:
+    /// face turns separated by spaces, with `'` for counterclockwise turns and
+    /// `2` for half turns. Anything else, like slice moves, wide moves, or
+    /// moves that aren't separated, is an error. The cube is unchanged if
+    /// there is an error.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut cube = Cube::new();
+    /// assert!(cube.import_wca("R U' F2").is_ok());
+    /// assert_eq!(cube, Cube::new().with_moves("RU'F2"));
+    ///
+    /// let err = cube.import_wca("R M").unwrap_err();
+    /// assert_eq!(err.character, 'M');
+    /// assert_eq!(err.offset, 2);
+    /// ```
+    pub fn import_wca(&mut self, scramble: &str) -> Result<(), MoveParseError> {
+        for m in parse_wca(scramble)? {
+            self.apply_move(m);
+        }
+
+        Ok(())
+    }
+
+    /// Apply a single move written in standard notation, e.g. `R'`, `F2`, or
+    /// `Rw`.
+    /// It's an error if the string is empty, has more than one move in it, or
+    /// is a group of moves, in which case the cube is unchanged.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut cube = Cube::new();
+    /// for token in "R U R' U'".split_whitespace() {
+    ///     cube.apply_move_str(token).unwrap();
+    /// }
+    ///
+    /// let err = cube.apply_move_str("RU").unwrap_err();
+    /// assert_eq!(err.character, 'U');
+    /// assert_eq!(err.offset, 1);
+    /// ```
+    pub fn apply_move_str(&mut self, m: &str) -> Result<(), MoveParseError> {
+        let mut tokens = Vec::new();
+        for_each_token(m, |offset, token| tokens.push((offset, token.to_vec())))?;
+
+        match tokens.len() {
+            0 => Err(MoveParseError { character: '\0', offset: 0 }),
+            1 => {
+                for &mv in &tokens[0].1 {
+                    self.apply_move(mv);
+                }
+                Ok(())
+            },
+            _ => {
+                let offset = tokens[1].0;
+                Err(MoveParseError { character: m[offset..].chars().next().unwrap(), offset })
+            },
+        }
+    }
+
+    /// Turn a face a number of quarter turns. Positive numbers turn clockwise
+    /// and negative numbers turn counterclockwise; only the number of turns
+    /// mod 4 matters, so e.g. 4 turns does nothing and 3 is the same as -1.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut a = Cube::new();
+    /// a.turn(Face::R, -1);
+    /// a.turn(Face::U, 2);
+    ///
+    /// let mut b = Cube::new();
+    /// b.apply_moves("R'U2");
+    /// assert_eq!(a, b);
+    /// ```
+    pub fn turn(&mut self, face: Face, quarters: i32) {
+        let layer = match face {
+            Face::F => Move::F,
+            Face::R => Move::R,
+            Face::U => Move::U,
+            Face::B => Move::B,
+            Face::L => Move::L,
+            Face::D => Move::D,
+        };
+        for m in layer_moves(layer, quarters.rem_euclid(4) as u8) {
+            self.apply_move(m);
+        }
+    }
+
+    /// Apply a move, a sequence of moves, or a string of moves to the cube.
+    /// Only strings can fail to parse; if they do, the cube is unchanged.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut a = Cube::new();
+    /// a.apply_any("RU").unwrap();
+    ///
+    /// let mut b = Cube::new();
+    /// b.apply_any(Move::R).unwrap();
+    /// b.apply_any(&[Move::U][..]).unwrap();
+    /// assert_eq!(a, b);
+    ///
+    /// assert!(a.apply_any("RX").is_err());
+    /// ```
+    pub fn apply_any<M: IntoMoves>(&mut self, moves: M) -> Result<(), MoveParseError> {
+        for m in moves.into_moves()? {
+            self.apply_move(m);
+        }
+
+        Ok(())
+    }
+
+    /// Apply a string of moves to a cube, refusing strings with more than
+    /// `max_moves` moves. Like `try_apply_moves`, the cube is unchanged if
+    /// there is an error. Parsing stops as soon as the limit is passed, so
+    /// huge inputs don't take long or use much memory, and anything after
+    /// the limit isn't checked for invalid moves.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut cube = Cube::new();
+    /// assert_eq!(cube.apply_moves_limited("FRU2", 3), Ok(()));
+    /// assert_eq!(cube.apply_moves_limited("FRU2", 2), Err(CubeError::TooLong));
+    /// assert_eq!(cube.apply_moves_limited("FRX", 3), Err(CubeError::InvalidCharacter('X')));
+    /// ```
+    pub fn apply_moves_limited(&mut self, moves: &str, max_moves: usize) -> Result<(), CubeError> {
+        let mut parsed = Vec::new();
+        let mut count = 0;
+        for_each_move(moves, |m| {
+            count += 1;
+            if count <= max_moves {
+                parsed.push(m);
+            }
+            count <= max_moves
+        })?;
+
+        if count > max_moves {
+            return Err(CubeError::TooLong);
+        }
+        for m in parsed {
+            self.apply_move(m);
+        }
+
+        Ok(())
+    }
+
+    /// Apply a string of moves to a cube, applying each move as soon as it has
+    /// been parsed instead of collecting them first. Like `try_apply_moves`,
+    /// the cube is unchanged if the string contains an invalid move.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut c = Cube::new();
+    /// let mut d = Cube::new();
+    /// c.apply_moves("R2U'FLB2");
+    /// d.apply_moves_streaming("R2U'FLB2").unwrap();
+    /// assert_eq!(c, d);
+    /// ```
+    pub fn apply_moves_streaming(&mut self, moves: &str) -> Result<(), MoveParseError> {
+        let mut cube = *self;
+        for_each_move(moves, |m| {
+            cube.apply_move(m);
+            true
+        })?;
+        *self = cube;
+
+        Ok(())
+    }
+
+    /// Apply a single move to the cube. See lemma 11.4 in the Chen paper for details about how x and y are calculated.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut cube = Cube::new();
+    /// cube.apply_move(Move::F);
+    /// assert!(!cube.is_solved());
+    ///
+    /// cube.apply_move(Move::F);
+    /// cube.apply_move(Move::F);
+    /// cube.apply_move(Move::F);
+    /// assert!(cube.is_solved());
+    /// ```
+    pub fn apply_move(&mut self, m: Move) {
+        use self::Move;
+        // Compute sigma and tau
+        self.sigma.permute(m);
+        self.tau.permute(m);
+
+        // Compute X and Y
+        let (swap_indices, addends) = corner_orientation_change(m);
+        self.x = swap_x(self.x, &swap_indices);
+        self.x = add_x(self.x, &addends);
+
+        let (swap_indices, addends) = match m {
+            Move::F => ([0,1,7,3,4,5,2,10,8,9,6,11], [0,0,1,0,0,0,1,1,0,0,1,0]),
+            Move::R => ([0,6,2,3,4,1,9,7,8,5,10,11], [0u8; 12]),
+            Move::U => ([3,0,1,2,4,5,6,7,8,9,10,11], [0u8; 12]),
+            Move::B => ([5,1,2,3,0,8,6,7,4,9,10,11], [1,0,0,0,1,1,0,0,1,0,0,0]),
+            Move::L => ([0,1,2,4,11,5,6,3,8,9,10,7], [0u8; 12]),
+            Move::D => ([0,1,2,3,4,5,6,7,9,10,11,8], [0u8; 12]),
+            Move::FPrime => ([0,1,6,3,4,5,10,2,8,9,7,11], [0,0,1,0,0,0,1,1,0,0,1,0]),
+            Move::RPrime => ([0,5,2,3,4,9,1,7,8,6,10,11], [0u8; 12]),
+            Move::UPrime => ([1,2,3,0,4,5,6,7,8,9,10,11], [0u8; 12]),
+            Move::BPrime => ([4,1,2,3,8,0,6,7,5,9,10,11], [1,0,0,0,1,1,0,0,1,0,0,0]),
+            Move::LPrime => ([0,1,2,7,3,5,6,11,8,9,10,4], [0u8; 12]),
+            Move::DPrime => ([0,1,2,3,4,5,6,7,11,8,9,10], [0u8; 12]),
+            Move::F2 => ([0,1,10,3,4,5,7,6,8,9,2,11], [0u8; 12]),
+            Move::R2 => ([0,9,2,3,4,6,5,7,8,1,10,11], [0u8; 12]),
+            Move::U2 => ([2,3,0,1,4,5,6,7,8,9,10,11], [0u8; 12]),
+            Move::B2 => ([8,1,2,3,5,4,6,7,0,9,10,11], [0u8; 12]),
+            Move::L2 => ([0,1,2,11,7,5,6,4,8,9,10,3], [0u8; 12]),
+            Move::D2 => ([0,1,2,3,4,5,6,7,10,11,8,9], [0u8; 12]),
+            Move::M => ([8,1,0,3,4,5,6,7,10,9,2,11], [1,0,1,0,0,0,0,0,1,0,1,0]),
+            Move::E => ([0,1,2,3,5,6,7,4,8,9,10,11], [0,0,0,0,1,1,1,1,0,0,0,0]),
+            Move::S => ([0,3,2,11,4,5,6,7,8,1,10,9], [0,1,0,1,0,0,0,0,0,1,0,1]),
+            Move::MPrime => ([2,1,10,3,4,5,6,7,0,9,8,11], [1,0,1,0,0,0,0,0,1,0,1,0]),
+            Move::EPrime => ([0,1,2,3,7,4,5,6,8,9,10,11], [0,0,0,0,1,1,1,1,0,0,0,0]),
+            Move::SPrime => ([0,9,2,1,4,5,6,7,8,11,10,3], [0,1,0,1,0,0,0,0,0,1,0,1]),
+        };
+        self.y = swap_y(self.y, &swap_indices);
+        self.y = add_y(self.y, &addends);
+    }
+
+    /// Determine whether the cube is in the solved state
+    pub fn is_solved(&self) -> bool {
+        self.sigma == CornerPermutation::default() &&
+        self.tau == EdgePermutation::default() &&
+        self.x == X::default() &&
+        self.y == Y::default()
+    }
+
+    /// Classify how hard the cube is to solve. See `Difficulty` for details.
+    /// Returns `None` for a solved cube.
+    pub fn difficulty(&self) -> Option<Difficulty> {
+        let (unsolved_corners, unsolved_edges) = self.unsolved_pieces();
+
+        match unsolved_corners + unsolved_edges {
+            0 => None,
+            1..=8 => Some(Difficulty::Easy),
+            9..=15 => Some(Difficulty::Medium),
+            _ => Some(Difficulty::Hard),
+        }
+    }
+
+    /// Count the corners and edges that aren't in their home cubicle with the
+    /// correct orientation
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut cube = Cube::new();
+    /// cube.apply_moves("R");
+    /// assert_eq!(cube.unsolved_pieces(), (4, 4));
+    /// ```
+    pub fn unsolved_pieces(&self) -> (usize, usize) {
+        let unsolved_corners = CORNERS.iter().filter(|&&c| !self.corner_solved(c)).count();
+        let unsolved_edges = EDGES.iter().filter(|&&e| !self.edge_solved(e)).count();
+        (unsolved_corners, unsolved_edges)
+    }
+
+    /// Determine whether the cube has reached a goal. See `Goal` for the
+    /// available goals.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// // U doesn't disturb the first two layers, but does mess up the U cross
+    /// let mut cube = Cube::new();
+    /// cube.apply_moves("U");
+    /// assert!(cube.satisfies(&Goal::F2L));
+    /// assert!(cube.satisfies(&Goal::OrientedLastLayer));
+    /// assert!(!cube.satisfies(&Goal::Cross(Face::U)));
+    /// assert!(!cube.satisfies(&Goal::Solved));
+    /// ```
+    pub fn satisfies(&self, goal: &Goal) -> bool {
+        let slots_solved = self.f2l_pairs_solved();
+
+        match *goal {
+            Goal::Solved => self.is_solved(),
+            Goal::Cross(face) => face_edges(face).iter().all(|&e| self.edge_solved(e)),
+            Goal::FirstLayer(face) => {
+                face_edges(face).iter().all(|&e| self.edge_solved(e))
+                && face_corners(face).iter().all(|&c| self.corner_solved(c))
+            },
+            Goal::Keyhole => self.satisfies(&Goal::Cross(Face::D)) && slots_solved >= 3,
+            Goal::F2L => self.satisfies(&Goal::Cross(Face::D)) && slots_solved == 4,
+            Goal::OrientedLastLayer => {
+                self.satisfies(&Goal::F2L) && self.get_face(Face::U) == [Face::U; 9]
+            },
+        }
+    }
+
+    /// Recognize the [OLL](https://www.speedsolving.com/wiki/index.php/OLL)
+    /// case of the last layer, numbered 1 to 57 in the usual way. The last
+    /// layer can be turned any way. Returns `None` if the first two layers
+    /// aren't solved (with the last layer on U) or the last layer is already
+    /// oriented.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// // An antisune sets up the case a sune solves, case 27
+    /// let mut cube = Cube::new();
+    /// cube.apply_moves("RU2R'U'RU'R'");
+    /// assert_eq!(cube.oll_case(), Some(27));
+    ///
+    /// assert_eq!(Cube::new().oll_case(), None);
+    /// ```
+    pub fn oll_case(&self) -> Option<u8> {
+        if !self.satisfies(&Goal::F2L) {
+            return None;
+        }
+        last_layer_tables().oll.get(&oll_pattern(self)).cloned()
+    }
+
+    /// Recognize the [PLL](https://www.speedsolving.com/wiki/index.php/PLL)
+    /// case of the last layer, e.g. `"T"` or `"Ga"`. The last layer can be
+    /// turned any way, and doesn't have to line up with the rest of the cube
+    /// once it's solved. Returns `None` if the first two layers aren't solved
+    /// (with the last layer on U), the last layer isn't oriented, or it only
+    /// needs a U turn to solve.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut cube = Cube::new();
+    /// cube.apply_moves("RUR'U'R'FR2U'R'U'RUR'F'");
+    /// assert_eq!(cube.pll_case(), Some("T"));
+    ///
+    /// cube.apply_moves("U");
+    /// assert_eq!(cube.pll_case(), Some("T"));
+    /// ```
+    pub fn pll_case(&self) -> Option<&'static str> {
+        last_layer_tables().pll.get(self).cloned()
+    }
+
+    /// Apply the inverse of a string of moves to the cube and return it, i.e.
+    /// set up the case that the moves solve
+    fn with_moves_inverted(mut self, moves: &str) -> Cube {
+        for m in invert_moves(&parse_moves(moves).unwrap()) {
+            self.apply_move(m);
+        }
+        self
+    }
+
+    /// Determine whether the four edges on a face are solved. This is the
+    /// same as `satisfies(&Goal::Cross(face))`.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut cube = Cube::new();
+    /// cube.apply_moves("R");
+    /// assert!(cube.is_cross_solved(Face::L));
+    /// assert!(!cube.is_cross_solved(Face::U));
+    /// ```
+    pub fn is_cross_solved(&self, face: Face) -> bool {
+        self.satisfies(&Goal::Cross(face))
+    }
+
+    /// Determine whether all of the corners and edges on a face are solved.
+    /// This is the same as `satisfies(&Goal::FirstLayer(face))`.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut cube = Cube::new();
+    /// cube.apply_moves("U");
+    /// assert!(cube.is_layer_solved(Face::D));
+    /// assert!(!cube.is_layer_solved(Face::F));
+    /// ```
+    pub fn is_layer_solved(&self, face: Face) -> bool {
+        self.satisfies(&Goal::FirstLayer(face))
+    }
+
+    /// Count the first-two-layers pairs that are solved, i.e. the D layer
+    /// corners whose middle layer edge is also solved
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut cube = Cube::new();
+    /// assert_eq!(cube.f2l_pairs_solved(), 4);
+    ///
+    /// // R moves the front right and back right pairs
+    /// cube.apply_moves("R");
+    /// assert_eq!(cube.f2l_pairs_solved(), 2);
+    /// ```
+    pub fn f2l_pairs_solved(&self) -> u8 {
+        F2L_SLOTS.iter().filter(
+            |&&(c, e)| self.corner_solved(c) && self.edge_solved(e)
+        ).count() as u8
+    }
+
+    /// Determine whether the corner cubie that belongs in a cubicle is there
+    /// and correctly oriented
+    fn corner_solved(&self, cubicle: Corner) -> bool {
+        self.sigma.get(cubicle) == cubicle && self.get_corner_orientation(cubicle) == 0
+    }
+
+    /// Determine whether the edge cubie that belongs in a cubicle is there and
+    /// correctly oriented
+    fn edge_solved(&self, cubicle: Edge) -> bool {
+        self.tau.get(cubicle) == cubicle && self.get_edge_orientation(cubicle) == 0
+    }
+
+    /// Get the corner cubie in each corner cubicle. The array is indexed by
+    /// cubicle in the order the `Corner` variants are declared, so the cubie
+    /// in the UFL cubicle is at index `Corner::UFL as usize`.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut cube = Cube::new();
+    /// assert_eq!(cube.corner_permutation()[Corner::URF as usize], Corner::URF);
+    ///
+    /// // U moves the UFL corner into the ULB cubicle
+    /// cube.apply_moves("U");
+    /// assert_eq!(cube.corner_permutation()[Corner::ULB as usize], Corner::UFL);
+    /// ```
+    pub fn corner_permutation(&self) -> [Corner; 8] {
+        self.sigma.map
+    }
+
+    /// Get the edge cubie in each edge cubicle. The array is indexed by
+    /// cubicle in the order the `Edge` variants are declared, so the cubie in
+    /// the UB cubicle is at index `Edge::UB as usize`.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut cube = Cube::new();
+    /// assert_eq!(cube.edge_permutation()[Edge::UF as usize], Edge::UF);
+    ///
+    /// // U moves the UF edge into the UL cubicle
+    /// cube.apply_moves("U");
+    /// assert_eq!(cube.edge_permutation()[Edge::UL as usize], Edge::UF);
+    /// ```
+    pub fn edge_permutation(&self) -> [Edge; 12] {
+        self.tau.map
+    }
+
+    /// Get the cycles of the corner permutation, longest first, leaving out
+    /// corners that are already in their own cubicle. Each cycle lists
+    /// cubicles such that the cubie in each one belongs in the next, and the
+    /// cubie in the last one belongs in the first. Orientations are ignored,
+    /// so a corner twisted in place isn't part of any cycle.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut cube = Cube::new();
+    /// assert!(cube.corner_cycles().is_empty());
+    ///
+    /// cube.apply_moves("U");
+    /// assert_eq!(cube.corner_cycles(), vec![vec![Corner::UFL, Corner::URF, Corner::UBR, Corner::ULB]]);
+    /// ```
+    pub fn corner_cycles(&self) -> Vec<Vec<Corner>> {
+        disjoint_cycle_decompose(&CORNERS, |c| self.sigma.get(c)).into_iter()
+            .filter(|cycle| cycle.len() > 1)
+            .collect()
+    }
+
+    /// Get the cycles of the edge permutation, longest first, leaving out
+    /// edges that are already in their own cubicle. The cycles are listed the
+    /// same way as in `corner_cycles`.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut cube = Cube::new();
+    /// assert!(cube.edge_cycles().is_empty());
+    ///
+    /// cube.apply_moves("U");
+    /// assert_eq!(cube.edge_cycles(), vec![vec![Edge::UB, Edge::UL, Edge::UF, Edge::UR]]);
+    /// ```
+    pub fn edge_cycles(&self) -> Vec<Vec<Edge>> {
+        disjoint_cycle_decompose(&EDGES, |e| self.tau.get(e)).into_iter()
+            .filter(|cycle| cycle.len() > 1)
+            .collect()
+    }
+
+    /// Determine whether two cubes have the same permutation, i.e. every cubie
+    /// is in the same cubicle on both cubes. Orientations are ignored, so a
+    /// cube with a twisted corner has the same permutation as a solved cube.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut c = Cube::new();
+    /// let superc = superflip(&c);
+    /// // The superflip flips every edge in place
+    /// assert!(superc.same_permutation(&c));
+    ///
+    /// c.apply_moves("U");
+    /// assert!(!superc.same_permutation(&c));
+    /// ```
+    pub fn same_permutation(&self, other: &Cube) -> bool {
+        self.sigma == other.sigma && self.tau == other.tau
+    }
+
+    /// Determine whether two cubes are the same apart from how the whole cube
+    /// is held, i.e. some rotation of the whole cube (with the stickers
+    /// recolored so the centers match) turns one into the other.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// // The same moves done on the right and on the front
+    /// let mut a = Cube::new();
+    /// let mut b = Cube::new();
+    /// a.apply_moves("RUR'U'");
+    /// b.apply_moves("FUF'U'");
+    /// assert!(a.equivalent_up_to_rotation(&b));
+    /// assert!(!a.equivalent_up_to_rotation(&Cube::new()));
+    /// ```
+    pub fn equivalent_up_to_rotation(&self, other: &Cube) -> bool {
+        cube_rotations().iter().any(|rotation| self.rotated(rotation) == *other)
+    }
+
+    /// Get the cube as it looks after rotating the whole thing (see
+    /// `cube_rotations`) and recoloring the stickers so the centers match
+    /// their faces again. This is the cube that the rotated versions of the
+    /// moves that produced this one would produce.
+    fn rotated(&self, rotation: &[Move; 6]) -> Cube {
+        let faces = [Face::F, Face::R, Face::U, Face::B, Face::L, Face::D];
+        let image = |face: Face| rotation[faces.iter().position(|&f| f == face).unwrap()].face();
+        let preimage = |face: Face| faces[rotation.iter().position(|m| m.face() == face).unwrap()];
+
+        let rotated = Cube::from_stickers(|face, i| {
+            // Find the cubicle that's rotated into this one
+            if let Some(j) = FACE_CORNER_INDICES.iter().position(|&k| k == i) {
+                let (a, b, c) = decompose_corner(face_corners(face)[j]);
+                let from = [preimage(a), preimage(b), preimage(c)];
+                let cubicle = *CORNERS.iter().find(|&&corner| {
+                    let (a, b, c) = decompose_corner(corner);
+                    from.contains(&a) && from.contains(&b) && from.contains(&c)
+                }).unwrap();
+                image(self.corner_sticker(cubicle, preimage(face)))
+            } else if let Some(j) = FACE_EDGE_INDICES.iter().position(|&k| k == i) {
+                let (a, b) = decompose_edge(face_edges(face)[j]);
+                let from = [preimage(a), preimage(b)];
+                let cubicle = *EDGES.iter().find(|&&edge| {
+                    let (a, b) = decompose_edge(edge);
+                    from.contains(&a) && from.contains(&b)
+                }).unwrap();
+                image(self.edge_sticker(cubicle, preimage(face)))
+            } else {
+                face
+            }
+        });
+
+        rotated.expect("A rotated cube is always valid")
+    }
+
+    /// Determine whether every cubie is in its home cubicle, ignoring how the
+    /// cubies are oriented
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// assert!(superflip_cube().permutation_solved());
+    /// ```
+    pub fn permutation_solved(&self) -> bool {
+        self.same_permutation(&Cube::new())
+    }
+
+    /// Determine whether every cubie is oriented correctly, ignoring which
+    /// cubicles they're in. U and D turns don't change any orientations.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("UD'");
+    /// assert!(c.orientation_solved());
+    /// assert!(!superflip_cube().orientation_solved());
+    /// ```
+    pub fn orientation_solved(&self) -> bool {
+        self.x == X::default() && self.y == Y::default()
+    }
+
+    /// Determine whether the stickers on this cube match the stickers on a
+    /// target cube, only looking at the faces that aren't ignored. Centers
+    /// never move, so they always match.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// // Only the bottom two layers are turned, so U is still solved
+    /// let mut c = Cube::new();
+    /// c.apply_moves("DE2D'");
+    /// assert!(c.matches(&Cube::new(), &[Face::D, Face::F, Face::R, Face::B, Face::L]));
+    /// assert!(!c.matches(&Cube::new(), &[Face::D]));
+    /// ```
+    pub fn matches(&self, target: &Cube, ignore_faces: &[Face]) -> bool {
+        FACES.iter()
+            .filter(|face| !ignore_faces.contains(face))
+            .all(|&face| self.get_face(face) == target.get_face(face))
+    }
+
+    /// Get the index of this cube's state among all legal cube states, a number
+    /// in `0..STATE_COUNT`. The solved cube has rank zero.
+    ///
+    /// The rank combines the corner permutation, the edge permutation, the
+    /// corner orientations, and the edge orientations (most to least
+    /// significant). Because the edge permutation parity and the orientation of
+    /// the last corner and edge are determined by the rest of the state, they
+    /// don't contribute to the rank. This means the rank is only meaningful for
+    /// legal cubes.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut c = Cube::new();
+    /// assert_eq!(c.rank(), 0);
+    ///
+    /// c.apply_moves("RUF");
+    /// assert_eq!(Cube::unrank(c.rank()), Some(c));
+    /// ```
+    pub fn rank(&self) -> u128 {
+        let cp = self.corner_permutation_coordinate() as u128;
+        // The lowest bit of the edge permutation rank is fixed by its parity
+        let ep = (rank_permutation(&self.tau.to_array()) / 2) as u128;
+        let co = self.corner_orientation_coordinate() as u128;
+        let eo = self.edge_orientation_coordinate() as u128;
+
+        ((cp * EDGE_PERMUTATIONS_BY_PARITY + ep) * CORNER_ORIENTATIONS + co) * EDGE_ORIENTATIONS + eo
+    }
+
+    /// Get the rank of the corner permutation alone, a number in `0..40320`.
+    /// This is zero when every corner is in its home cubicle, regardless of
+    /// orientation. Turning a face changes it in a way that only depends on
+    /// its current value, which makes it useful for building move tables.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("U2");
+    /// assert_ne!(c.corner_permutation_coordinate(), 0);
+    /// c.apply_moves("U2");
+    /// assert_eq!(c.corner_permutation_coordinate(), 0);
+    /// ```
+    pub fn corner_permutation_coordinate(&self) -> u16 {
+        rank_permutation(&self.sigma.to_array()) as u16
+    }
+
+    /// Get the corner orientations alone as a number in `0..2187`. The
+    /// orientation of the last corner is determined by the others, so it
+    /// doesn't contribute. This is zero when no corner is twisted.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("U");
+    /// assert_eq!(c.corner_orientation_coordinate(), 0);
+    /// c.apply_moves("R");
+    /// assert_ne!(c.corner_orientation_coordinate(), 0);
+    /// ```
+    pub fn corner_orientation_coordinate(&self) -> u16 {
+        self.x.to_array()[..7].iter().fold(0, |r, &o| r * 3 + o as u16)
+    }
+
+    /// Get the edge orientations alone as a number in `0..2048`. The
+    /// orientation of the last edge is determined by the others, so it doesn't
+    /// contribute. This is zero when no edge is flipped.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("R");
+    /// assert_eq!(c.edge_orientation_coordinate(), 0);
+    /// c.apply_moves("F");
+    /// assert_ne!(c.edge_orientation_coordinate(), 0);
+    /// ```
+    pub fn edge_orientation_coordinate(&self) -> u16 {
+        self.y.to_array()[..11].iter().fold(0, |r, &o| r * 2 + o as u16)
+    }
+
+    /// Get the cubicles holding the four edges of the E slice (LB, RB, RF, and
+    /// LF) as a number in `0..495`, ignoring which of those edges is where.
+    /// This is zero when all four are somewhere in the E slice.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("UDR2F2");
+    /// assert_eq!(c.ud_slice_coordinate(), 0);
+    /// c.apply_moves("R");
+    /// assert_ne!(c.ud_slice_coordinate(), 0);
+    /// ```
+    pub fn ud_slice_coordinate(&self) -> u16 {
+        // Number the cubicles so the E slice comes first, then rank the set
+        // of cubicles holding slice edges in colexicographic order
+        let mut positions = self.tau.to_array().iter().enumerate()
+            .filter(|&(_, &e)| (4..8).contains(&e))
+            .map(|(i, _)| (i + 8) % 12)
+            .collect::<Vec<usize>>();
+        positions.sort();
+
+        positions.iter().enumerate().map(|(k, &p)| binomial(p, k + 1)).sum::<usize>() as u16
+    }
+
+    /// Get the arrangement of the eight U and D layer edges as a number in
+    /// `0..40320`. This is zero when each of them is in its home cubicle. It's
+    /// only meaningful when all of the E slice edges are in the E slice.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("E");
+    /// assert_eq!(c.ud_edge_permutation_coordinate(), 0);
+    /// c.apply_moves("U");
+    /// assert_ne!(c.ud_edge_permutation_coordinate(), 0);
+    /// ```
+    pub fn ud_edge_permutation_coordinate(&self) -> u16 {
+        let edges = self.tau.to_array();
+        let ud_edges = edges[..4].iter().chain(&edges[8..])
+            .map(|&e| if e < 8 { e % 4 } else { e - 4 })
+            .collect::<Vec<u8>>();
+        rank_permutation(&ud_edges) as u16
+    }
+
+    /// Get the arrangement of the four E slice edges as a number in `0..24`.
+    /// This is zero when each of them is in its home cubicle. It's only
+    /// meaningful when all of them are in the E slice.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("U");
+    /// assert_eq!(c.slice_edge_permutation_coordinate(), 0);
+    /// c.apply_moves("E");
+    /// assert_ne!(c.slice_edge_permutation_coordinate(), 0);
+    /// ```
+    pub fn slice_edge_permutation_coordinate(&self) -> u8 {
+        let slice_edges = self.tau.to_array()[4..8].iter().map(|&e| e % 4).collect::<Vec<u8>>();
+        rank_permutation(&slice_edges) as u8
+    }
+
+    /// Create the cube with the given rank. This is the inverse of `rank`, so
+    /// every number in `0..STATE_COUNT` corresponds to exactly one legal cube.
+    ///
+    /// Returns `None` if the rank is not less than `STATE_COUNT`.
+    pub fn unrank(n: u128) -> Option<Cube> {
+        if n >= STATE_COUNT {
+            return None;
+        }
+
+        let eo = n % EDGE_ORIENTATIONS;
+        let n = n / EDGE_ORIENTATIONS;
+        let co = n % CORNER_ORIENTATIONS;
+        let n = n / CORNER_ORIENTATIONS;
+        let ep = n % EDGE_PERMUTATIONS_BY_PARITY;
+        let cp = n / EDGE_PERMUTATIONS_BY_PARITY;
+
+        let mut corners = [0u8; 8];
+        unrank_permutation(cp as u64, &mut corners);
+
+        // Pick the edge permutation with the same parity as the corners
+        let mut edges = [0u8; 12];
+        unrank_permutation(ep as u64 * 2, &mut edges);
+        if permutation_parity(&edges) != permutation_parity(&corners) {
+            unrank_permutation(ep as u64 * 2 + 1, &mut edges);
+        }
+
+        // The last orientation is whatever makes the total come out to zero
+        let mut x = [0u8; 8];
+        let mut rest = co;
+        for i in (0..7).rev() {
+            x[i] = (rest % 3) as u8;
+            rest /= 3;
+        }
+        x[7] = (3 - x[..7].iter().sum::<u8>() % 3) % 3;
+
+        let mut y = [0u8; 12];
+        let mut rest = eo;
+        for i in (0..11).rev() {
+            y[i] = (rest % 2) as u8;
+            rest /= 2;
+        }
+        y[11] = y[..11].iter().sum::<u8>() % 2;
+
+        Cube::from_arrays(&corners, &x, &edges, &y).ok()
+    }
+
+    /// Find all of the reasons this cube can't be reached from the solved
+    /// state. A legal cube has no problems, so the result is empty.
+    ///
+    /// Permutation parity is only checked if there are no duplicate cubies
+    /// since it's meaningless otherwise. If the parity is wrong because two
+    /// edges or two corners have been swapped and every other piece is in
+    /// place, the swapped pair is reported too.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("FRUBLD");
+    /// assert_eq!(c.diagnose(), vec![]);
+    /// ```
+    pub fn diagnose(&self) -> Vec<Illegality> {
+        let mut problems = Vec::new();
+        let corners = self.sigma.to_array();
+        let edges = self.tau.to_array();
+
+        let duplicates = !is_permutation(&corners) || !is_permutation(&edges);
+        if !is_permutation(&corners) {
+            problems.push(Illegality::DuplicateCorner);
+        }
+        if !is_permutation(&edges) {
+            problems.push(Illegality::DuplicateEdge);
+        }
+
+        let mut counts: HashMap<Face, usize> = HashMap::new();
+        for &face in &FACES {
+            for &sticker in self.get_face(face).iter() {
+                *counts.entry(sticker).or_insert(0) += 1;
+            }
+        }
+        for &face in &FACES {
+            if counts.get(&face).cloned().unwrap_or(0) < 9 {
+                problems.push(Illegality::MissingColor(face));
+            }
+        }
+
+        if self.x.to_array().iter().sum::<u8>() % 3 != 0 {
+            problems.push(Illegality::CornerTwist);
+        }
+        if self.y.to_array().iter().sum::<u8>() % 2 != 0 {
+            problems.push(Illegality::EdgeFlip);
+        }
+        if !duplicates && self.has_odd_permutation() {
+            problems.push(Illegality::PermutationParity);
+
+            let moved_corners: Vec<Corner> = CORNERS.iter().cloned().filter(|&c| self.sigma.get(c) != c).collect();
+            let moved_edges: Vec<Edge> = EDGES.iter().cloned().filter(|&e| self.tau.get(e) != e).collect();
+            if moved_corners.is_empty() && moved_edges.len() == 2 {
+                let (a, b) = (decompose_edge(moved_edges[0]), decompose_edge(moved_edges[1]));
+                problems.push(Illegality::SwappedEdges([a.0, a.1], [b.0, b.1]));
+            } else if moved_edges.is_empty() && moved_corners.len() == 2 {
+                let (a, b) = (decompose_corner(moved_corners[0]), decompose_corner(moved_corners[1]));
+                problems.push(Illegality::SwappedCorners([a.0, a.1, a.2], [b.0, b.1, b.2]));
+            }
+        }
+
+        problems
+    }
+
+    /// Determine whether the corners and edges together are an odd
+    /// permutation, which can't be reached by turning faces. This usually
+    /// means two pieces have been swapped, e.g. by a scanning mistake. Cubes
+    /// with duplicate cubies don't have a permutation, so they're never odd.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("FRU");
+    /// assert!(!c.has_odd_permutation());
+    /// ```
+    pub fn has_odd_permutation(&self) -> bool {
+        let corners = self.sigma.to_array();
+        let edges = self.tau.to_array();
+        is_permutation(&corners) && is_permutation(&edges)
+            && permutation_parity(&corners) != permutation_parity(&edges)
+    }
+
+    /// Determine whether the cube can be reached from the solved state. See
+    /// `diagnose` to find out why a cube is invalid.
+    pub fn is_valid(&self) -> bool {
+        self.diagnose().is_empty()
+    }
+
+    /// Check that the cube can be reached from the solved state, returning
+    /// the first problem found if it can't. The corners and edges must each
+    /// appear once, their permutations must have the same parity, the corner
+    /// twists must add up to a multiple of three, and the edge flips must add
+    /// up to a multiple of two. See `diagnose` to get every problem at once.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("FRU");
+    /// assert_eq!(c.check_legality(), Ok(()));
+    ///
+    /// // A lone slice move leaves the edges in an odd permutation
+    /// c.apply_moves("M");
+    /// assert_eq!(c.check_legality(), Err(Illegality::PermutationParity));
+    /// ```
+    pub fn check_legality(&self) -> Result<(), Illegality> {
+        match self.diagnose().first() {
+            Some(&problem) => Err(problem),
+            None => Ok(()),
+        }
+    }
+
+    /// Make the smallest change needed to turn an illegal cube into a legal
+    /// one, trusting the cubie positions over their orientations. If the
+    /// permutation parity is wrong, the edges in the DF and DL cubicles are
+    /// swapped. Then the corner in the DRB cubicle is twisted and the edge in
+    /// the DL cubicle is flipped as needed to make the orientations add up.
+    ///
+    /// Cubes with duplicate or missing cubies can't be repaired this way and
+    /// only have their orientations fixed.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("FRU");
+    /// let before = c;
+    ///
+    /// // Legal cubes are left alone
+    /// c.make_valid();
+    /// assert_eq!(c, before);
+    /// ```
+    pub fn make_valid(&mut self) {
+        let corners = self.sigma.to_array();
+        let mut edges = self.tau.to_array();
+        let mut x = self.x.to_array();
+        let mut y = self.y.to_array();
+
+        if is_permutation(&corners) && is_permutation(&edges)
+        && permutation_parity(&corners) != permutation_parity(&edges) {
+            edges.swap(10, 11);
+            y.swap(10, 11);
+        }
+
+        x[7] = (x[7] + 3 - x.iter().sum::<u8>() % 3) % 3;
+        y[11] = (y[11] + y.iter().sum::<u8>()) % 2;
+
+        self.tau = EdgePermutation::from_array(&edges);
+        self.x = X::from_array(&x);
+        self.y = Y::from_array(&y);
+    }
+
+    /// Create a cube from its permutations and orientations in cubicle order,
+    /// checking that the result is a legal cube
+    fn from_arrays(corners: &[u8; 8], x: &[u8; 8], edges: &[u8; 12], y: &[u8; 12]) -> Result<Cube, CubeError> {
+        if !is_permutation(corners) || !is_permutation(edges) {
+            return Err(CubeError::InvalidPermutation);
+        }
+        if x.iter().any(|&o| o > 2) || y.iter().any(|&o| o > 1) {
+            return Err(CubeError::InvalidOrientation);
+        }
+
+        let cube = Cube {
+            sigma: CornerPermutation::from_array(corners),
+            tau: EdgePermutation::from_array(edges),
+            x: X::from_array(x),
+            y: Y::from_array(y),
+        };
+        if cube.is_valid() {
+            Ok(cube)
+        } else {
+            Err(CubeError::Illegal)
+        }
+    }
+
+    /// Get the cubie faces visible on one face of the cube. Faces are stored out
+    /// in the array such that the top row of the face is in the first three
+    /// elements, the next row is stored in the next three elements, and the
+    /// bottom row is stored in the last three elements.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut cube = Cube::new();
+    /// // On a solved cube, every cubie face on the up face should also be an up face
+    /// assert_eq!(cube.get_face(Face::U), [Face::U; 9]);
+    /// ```
+    pub fn get_face(&self, face: Face) -> [Face; 9] {
+        let corners = face_corners(face);
+        let edges = face_edges(face);
+
+        // Get the corner and edge cubies in each cubicle of interest
+        let corner_cubies: Vec<_> = corners.iter().map(|&c| self.sigma.get(c)).collect();
+        let edge_cubies: Vec<_> = edges.iter().map(|&e| self.tau.get(e)).collect();
+
+        // Now get the orientations for the cubies we care about
+        let corner_orient: Vec<_> = corners.iter().map(|&c| self.get_corner_orientation(c)).collect();
+        let edge_orient: Vec<_> = edges.iter().map(|&e| self.get_edge_orientation(e)).collect();
+
+        // Finally find the visible face of each cubie
+        let corner_faces: Vec<_> = corners.iter().enumerate().map(
+            |(i, &c)| get_corner_face(c, corner_cubies[i], face, corner_orient[i])
+        ).collect();
+
+        let edge_faces: Vec<_> = edges.iter().enumerate().map(
+            |(i, &e)| get_edge_face(e, edge_cubies[i], face, edge_orient[i])
+        ).collect();
+
+        // Returned structure lists the faces clockwise from top left
+        [corner_faces[0],   edge_faces[0],  corner_faces[1],
+        edge_faces[3],      face,           edge_faces[1],
+        corner_faces[3],    edge_faces[2],  corner_faces[2]]
+    }
+
+    /// Get a single sticker from one face of the cube, without building the
+    /// whole face. The stickers are indexed the same way as `get_face`, row
+    /// by row from the top left:
+    ///
+    /// ```text
+    /// 0 1 2
+    /// 3 4 5
+    /// 6 7 8
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `index` is 9 or more.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut cube = Cube::new();
+    /// cube.apply_moves("F");
+    ///
+    /// // The U sticker of the UF edge is now on R
+    /// assert_eq!(cube.get_sticker(Face::R, 3), Face::U);
+    /// assert_eq!(cube.get_sticker(Face::U, 7), Face::L);
+    /// ```
+    pub fn get_sticker(&self, face: Face, index: usize) -> Face {
+        let corners = face_corners(face);
+        let edges = face_edges(face);
+
+        match index {
+            0 => self.corner_sticker(corners[0], face),
+            1 => self.edge_sticker(edges[0], face),
+            2 => self.corner_sticker(corners[1], face),
+            3 => self.edge_sticker(edges[3], face),
+            4 => face,
+            5 => self.edge_sticker(edges[1], face),
+            6 => self.corner_sticker(corners[3], face),
+            7 => self.edge_sticker(edges[2], face),
+            8 => self.corner_sticker(corners[2], face),
+            _ => panic!("Sticker index {} is out of range (0-8)", index),
+        }
+    }
+
+    /// Get every sticker on the cube at once. The faces are listed in the order
+    /// URFDLB, and the stickers on each face are listed in the same order as
+    /// `get_face`, so e.g. the R face is at indices 9 to 17.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let stickers = Cube::new().all_stickers();
+    /// assert_eq!(stickers[..9], [Face::U; 9]);
+    /// assert_eq!(stickers[45..], [Face::B; 9]);
+    /// ```
+    pub fn all_stickers(&self) -> [Face; 54] {
+        let mut stickers = [Face::U; 54];
+        for (i, &face) in FACES.iter().enumerate() {
+            stickers[i * 9..(i + 1) * 9].copy_from_slice(&self.get_face(face));
+        }
+        stickers
+    }
+
+    /// Get the real color of every sticker on the cube, in the same order as
+    /// `all_stickers`
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("R");
+    ///
+    /// let colors = c.to_colors(&ColorScheme::western());
+    /// assert_eq!(colors[0], Color::White);
+    /// // R brings a green sticker up to the top right of U
+    /// assert_eq!(colors[2], Color::Green);
+    /// ```
+    pub fn to_colors(&self, scheme: &ColorScheme) -> [Color; 54] {
+        let mut colors = [Color::White; 54];
+        for (color, &face) in colors.iter_mut().zip(self.all_stickers().iter()) {
+            *color = scheme.color(face);
+        }
+        colors
+    }
+
+    /// Count the stickers that don't match the center of the face they're on.
+    /// This is a rough measure of how scrambled the cube looks, but it isn't
+    /// a lower bound on the number of moves needed: one turn can move up to
+    /// 12 stickers.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut c = Cube::new();
+    /// assert_eq!(c.misplaced_stickers(), 0);
+    ///
+    /// // Three stickers on each of U, F, D, and B move away
+    /// c.apply_moves("R");
+    /// assert_eq!(c.misplaced_stickers(), 12);
+    /// ```
+    pub fn misplaced_stickers(&self) -> u8 {
+        FACES.iter().map(
+            |&face| self.get_face(face).iter().filter(|&&f| f != face).count() as u8
+        ).sum()
+    }
+
+    /// Describe the cube as a 54-character facelet string, as used by Kociemba's
+    /// solver and many other tools. The faces are listed in the order URFDLB,
+    /// and the stickers on each face are listed in the same order as
+    /// `get_face`.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// assert_eq!(Cube::new().to_facelet_string(),
+    ///            "UUUUUUUUURRRRRRRRRFFFFFFFFFDDDDDDDDDLLLLLLLLLBBBBBBBBB");
+    /// ```
+    pub fn to_facelet_string(&self) -> String {
+        self.all_stickers().iter().map(|f| format!("{:?}", f)).collect()
+    }
+
+    /// Create a cube from a 54-character facelet string as produced by
+    /// `to_facelet_string`. Every color must appear exactly nine times, the
+    /// centers must be in their usual places, and the stickers must describe a
+    /// legal cube.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("FRU");
+    /// assert_eq!(Cube::from_facelet_string(&c.to_facelet_string()), Ok(c));
+    ///
+    /// let solved = "UUUUUUUUURRRRRRRRRFFFFFFFFFDDDDDDDDDLLLLLLLLLBBBBBBBBB";
+    /// assert_eq!(Cube::from_facelet_string(solved), Ok(Cube::new()));
+    /// ```
+    pub fn from_facelet_string(s: &str) -> Result<Cube, FaceletError> {
+        let mut stickers: Vec<Face> = Vec::with_capacity(54);
+        for ch in s.chars() {
+            match ch {
+                'F' | 'R' | 'U' | 'B' | 'L' | 'D' => stickers.push(Face::from(ch)),
+                _ => return Err(FaceletError::InvalidCharacter(ch)),
+            }
+        }
+        if stickers.len() != 54 {
+            return Err(FaceletError::InvalidFormat);
+        }
+
+        for &face in &FACES {
+            if stickers.iter().filter(|&&f| f == face).count() != 9 {
+                return Err(FaceletError::ColorCount(face));
+            }
+        }
+        if FACES.iter().any(|&f| stickers[sticker_offset(f) + 4] != f) {
+            return Err(FaceletError::InvalidFormat);
+        }
+
+        Cube::from_stickers(|face, i| stickers[sticker_offset(face) + i])
+    }
+
+    /// Describe the cube as a facelet string (see `to_facelet_string`) with a
+    /// separator between the faces. This is easier to read, but most tools
+    /// expect the plain version.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// assert_eq!(Cube::new().to_facelet_string_grouped('|'),
+    ///            "UUUUUUUUU|RRRRRRRRR|FFFFFFFFF|DDDDDDDDD|LLLLLLLLL|BBBBBBBBB");
+    /// ```
+    pub fn to_facelet_string_grouped(&self, sep: char) -> String {
+        FACES.iter().map(|&f| self.face_string(f)).collect::<Vec<_>>().join(&sep.to_string())
+    }
+
+    /// Get the stickers on one face as a string
+    fn face_string(&self, face: Face) -> String {
+        self.get_face(face).iter().map(|f| format!("{:?}", f)).collect()
+    }
+
+    /// Describe the cube in Reid notation. The state is written as twenty
+    /// space-separated pieces, giving the colors of the stickers in each
+    /// cubicle. The cubicles are listed in the order
+    ///
+    /// ```text
+    /// UF UR UB UL DF DR DB DL FR FL BR BL UFR URB UBL ULF DRF DFL DLB DBR
+    /// ```
+    ///
+    /// and the stickers of each cubicle are listed in the same order as the
+    /// faces in its name, so the solved cube is written exactly as above.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("U");
+    /// assert_eq!(c.to_reid(), "UR UB UL UF DF DR DB DL FR FL BR BL URB UBL ULF UFR DRF DFL DLB DBR");
+    /// ```
+    pub fn to_reid(&self) -> String {
+        let edges = REID_EDGES.iter().zip(REID_EDGE_FACES.iter()).map(
+            |(&e, faces)| faces.iter().map(|&f| format!("{:?}", self.edge_sticker(e, f))).collect::<String>()
+        );
+        let corners = REID_CORNERS.iter().zip(REID_CORNER_FACES.iter()).map(
+            |(&c, faces)| faces.iter().map(|&f| format!("{:?}", self.corner_sticker(c, f))).collect::<String>()
+        );
+
+        edges.chain(corners).collect::<Vec<_>>().join(" ")
+    }
+
+    /// Create a cube from its Reid notation. See `to_reid` for the format.
+    /// Pieces may be separated by any amount of whitespace.
+    ///
+    /// Returns an error if the input isn't valid Reid notation or describes a
+    /// cube that can't be reached from the solved state.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("FRU");
+    /// assert_eq!(Cube::from_reid(&c.to_reid()), Ok(c));
+    /// ```
+    pub fn from_reid(s: &str) -> Result<Cube, CubeError> {
+        let pieces: Vec<&str> = s.split_whitespace().collect();
+        if pieces.len() != 20
+        || pieces[..12].iter().any(|p| p.len() != 2)
+        || pieces[12..].iter().any(|p| p.len() != 3) {
+            return Err(CubeError::InvalidFormat);
+        }
+
+        let mut colors: Vec<Vec<Face>> = Vec::new();
+        for piece in &pieces {
+            let mut piece_colors = Vec::new();
+            for ch in piece.chars() {
+                match ch {
+                    'F' | 'R' | 'U' | 'B' | 'L' | 'D' => piece_colors.push(Face::from(ch)),
+                    _ => return Err(CubeError::InvalidCharacter(ch)),
+                }
+            }
+            colors.push(piece_colors);
+        }
+
+        let mut edges = [0u8; 12];
+        let mut y = [0u8; 12];
+        for (i, &e) in REID_EDGES.iter().enumerate() {
+            let faces = REID_EDGE_FACES[i];
+            let stickers = [(faces[0], colors[i][0]), (faces[1], colors[i][1])];
+            let (cubie, orientation) = identify_edge(e, &stickers).ok_or(CubeError::InvalidCubie)?;
+            edges[e as usize] = cubie as u8;
+            y[e as usize] = orientation;
+        }
+
+        let mut corners = [0u8; 8];
+        let mut x = [0u8; 8];
+        for (i, &c) in REID_CORNERS.iter().enumerate() {
+            let faces = REID_CORNER_FACES[i];
+            let piece = &colors[i + 12];
+            let stickers = [(faces[0], piece[0]), (faces[1], piece[1]), (faces[2], piece[2])];
+            let (cubie, orientation) = identify_corner(c, &stickers).ok_or(CubeError::InvalidCubie)?;
+            corners[c as usize] = cubie as u8;
+            x[c as usize] = orientation;
+        }
+
+        Cube::from_arrays(&corners, &x, &edges, &y)
+    }
+
+    /// Create a cube from the stickers on each face, e.g. as read by a scanner.
+    /// Each face is laid out like `get_face`, and the faces can be given in
+    /// either of the forms that implement `FaceStickers`:
+    ///
+    /// * a map from each face to characters for its colors. Any characters
+    ///   can be used as long as they match the center sticker of the face
+    ///   they belong to.
+    /// * an array of each face and its colors, in any order. Each face must
+    ///   appear exactly once with its own color in the center, so this is the
+    ///   inverse of calling `get_face` on every face.
+    ///
+    /// Every color must appear on exactly nine stickers, and the stickers must
+    /// describe a legal cube.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    /// use std::collections::HashMap;
+    ///
+    /// let mut faces = HashMap::new();
+    /// faces.insert(Face::U, ['w'; 9]);
+    /// faces.insert(Face::D, ['y'; 9]);
+    /// faces.insert(Face::F, ['g'; 9]);
+    /// faces.insert(Face::B, ['b'; 9]);
+    /// faces.insert(Face::L, ['o'; 9]);
+    /// faces.insert(Face::R, ['r'; 9]);
+    /// assert_eq!(Cube::from_faces(faces), Ok(Cube::new()));
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("FRU");
+    ///
+    /// let faces = [
+    ///     (Face::U, c.get_face(Face::U)), (Face::D, c.get_face(Face::D)),
+    ///     (Face::F, c.get_face(Face::F)), (Face::B, c.get_face(Face::B)),
+    ///     (Face::L, c.get_face(Face::L)), (Face::R, c.get_face(Face::R)),
+    /// ];
+    /// assert_eq!(Cube::from_faces(faces), Ok(c));
+    /// ```
+    pub fn from_faces<T: FaceStickers>(faces: T) -> Result<Cube, FaceletError> {
+        let stickers = faces.face_stickers()?;
+        for &face in &FACES {
+            let count: usize = stickers.values().map(|s| s.iter().filter(|&&f| f == face).count()).sum();
+            if count != 9 {
+                return Err(FaceletError::ColorCount(face));
+            }
+        }
+
+        Cube::from_stickers(|face, i| stickers[&face][i])
+    }
+
+    /// Create a cube from the color of each sticker, given as a function of
+    /// the face and the sticker's index in the `get_face` layout
+    fn from_stickers<F: Fn(Face, usize) -> Face>(sticker: F) -> Result<Cube, FaceletError> {
+        let mut edges = [0u8; 12];
+        let mut y = [0u8; 12];
+        for &e in &EDGES {
+            let (a, b) = decompose_edge(e);
+            let stickers = [
+                (a, sticker(a, edge_sticker_index(e, a))),
+                (b, sticker(b, edge_sticker_index(e, b))),
+            ];
+            let (cubie, orientation) = identify_edge(e, &stickers).ok_or(FaceletError::InvalidCubie)?;
+            edges[e as usize] = cubie as u8;
+            y[e as usize] = orientation;
+        }
+
+        let mut corners = [0u8; 8];
+        let mut x = [0u8; 8];
+        for &c in &CORNERS {
+            let (a, b, d) = decompose_corner(c);
+            let stickers = [
+                (a, sticker(a, corner_sticker_index(c, a))),
+                (b, sticker(b, corner_sticker_index(c, b))),
+                (d, sticker(d, corner_sticker_index(c, d))),
+            ];
+            let (cubie, orientation) = identify_corner(c, &stickers).ok_or(FaceletError::InvalidCubie)?;
+            corners[c as usize] = cubie as u8;
+            x[c as usize] = orientation;
+        }
+
+        Cube::from_arrays(&corners, &x, &edges, &y).map_err(FaceletError::InvalidState)
+    }
+
+    /// Compose two cubes as elements of the cube group. The result is the state
+    /// reached by applying the moves that produced `self` to a solved cube and
+    /// then applying the moves that produced `other`.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut a = Cube::new();
+    /// let mut b = Cube::new();
+    /// let mut ab = Cube::new();
+    /// a.apply_moves("FR");
+    /// b.apply_moves("UL'");
+    /// ab.apply_moves("FRUL'");
+    /// assert_eq!(a.compose(&b), ab);
+    /// ```
+    pub fn compose(&self, other: &Cube) -> Cube {
+        let (sigma, x) = (self.sigma.to_array(), self.x.to_array());
+        let (tau, y) = (self.tau.to_array(), self.y.to_array());
+        let (other_sigma, other_x) = (other.sigma.to_array(), other.x.to_array());
+        let (other_tau, other_y) = (other.tau.to_array(), other.y.to_array());
+
+        // Whatever is in the cubicle other's cubie came from ends up in its place
+        let mut corners = [0u8; 8];
+        let mut new_x = [0u8; 8];
+        for i in 0..8 {
+            let from = other_sigma[i] as usize;
+            corners[i] = sigma[from];
+            new_x[i] = (x[from] + other_x[i]) % 3;
+        }
+
+        let mut edges = [0u8; 12];
+        let mut new_y = [0u8; 12];
+        for i in 0..12 {
+            let from = other_tau[i] as usize;
+            edges[i] = tau[from];
+            new_y[i] = (y[from] + other_y[i]) % 2;
+        }
+
+        Cube {
+            sigma: CornerPermutation::from_array(&corners),
+            tau: EdgePermutation::from_array(&edges),
+            x: X::from_array(&new_x),
+            y: Y::from_array(&new_y),
+        }
+    }
+
+    /// Raise the cube to a power as an element of the cube group, i.e. apply
+    /// the transformation that produced the cube `k` times. Uses repeated
+    /// squaring, so large powers are cheap.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut c = Cube::new();
+    /// let mut d = Cube::new();
+    /// c.apply_moves("RU");
+    /// d.apply_moves("RURURU");
+    /// assert_eq!(c.pow(3), d);
+    ///
+    /// // RU has order 105
+    /// assert!(c.pow(105).is_solved());
+    /// ```
+    pub fn pow(&self, k: u32) -> Cube {
+        let mut result = Cube::new();
+        let mut base = *self;
+        let mut k = k;
+        while k > 0 {
+            if k % 2 == 1 {
+                result = result.compose(&base);
+            }
+            base = base.compose(&base);
+            k /= 2;
+        }
+
+        result
+    }
+
+    /// Determine whether two cubes are inverses of each other as elements of
+    /// the cube group, i.e. applying the moves that produced one cube to the
+    /// other one solves it. This relation is symmetric: `a.is_inverse_of(&b)`
+    /// is always the same as `b.is_inverse_of(&a)`.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut a = Cube::new();
+    /// let mut b = Cube::new();
+    /// a.apply_moves("FRU");
+    /// b.apply_moves("U'R'F'");
+    /// assert!(a.is_inverse_of(&b));
+    /// assert!(b.is_inverse_of(&a));
+    /// ```
+    pub fn is_inverse_of(&self, other: &Cube) -> bool {
+        self.compose(other).is_solved()
+    }
+
+    /// Get the inverse of the cube as an element of the cube group, i.e. the
+    /// state reached by undoing the moves that produced this one on a solved
+    /// cube. Composing a cube with its inverse gives the solved cube.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("FRU");
+    ///
+    /// let mut undo = Cube::new();
+    /// undo.apply_moves("U'R'F'");
+    /// assert_eq!(c.inverse(), undo);
+    /// assert!(c.compose(&c.inverse()).is_solved());
+    /// ```
+    pub fn inverse(&self) -> Cube {
+        let (sigma, x) = (self.sigma.to_array(), self.x.to_array());
+        let (tau, y) = (self.tau.to_array(), self.y.to_array());
+
+        let mut corners = [0u8; 8];
+        let mut new_x = [0u8; 8];
+        for i in 0..8 {
+            corners[sigma[i] as usize] = i as u8;
+            new_x[sigma[i] as usize] = (3 - x[i]) % 3;
+        }
+
+        let mut edges = [0u8; 12];
+        let mut new_y = [0u8; 12];
+        for i in 0..12 {
+            edges[tau[i] as usize] = i as u8;
+            new_y[tau[i] as usize] = y[i];
+        }
+
+        Cube {
+            sigma: CornerPermutation::from_array(&corners),
+            tau: EdgePermutation::from_array(&edges),
+            x: X::from_array(&new_x),
+            y: Y::from_array(&new_y),
+        }
+    }
+
+    /// Get the cube produced by applying the inverse of this cube's scramble
+    /// to a solved cube. Fewest-moves solvers switch between this view and the
+    /// normal one: a solution to either view can be turned into a solution to
+    /// the other by inverting it.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut scrambled = Cube::new();
+    /// let mut inverse = Cube::new();
+    /// scrambled.apply_moves("FRU");
+    /// inverse.apply_moves("U'R'F'");
+    /// assert_eq!(scrambled.inverse_scramble_view(), inverse);
+    /// ```
+    pub fn inverse_scramble_view(&self) -> Cube {
+        self.inverse()
+    }
+
+    /// Find the moves that take this cube to another state, using the given
+    /// solver. If the solver finds optimal solutions, so does this.
+    ///
+    /// This is useful for recovering from mistakes: given the state a cube is
+    /// actually in and the state it should be in, it finds the moves that fix
+    /// it. An empty vector is returned if the cubes are already equal (or the
+    /// solver can't find a solution).
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    /// use rubik::solver::IDSolver;
+    ///
+    /// // The plan was to turn F, but the user turned F' instead
+    /// let mut intended = Cube::new();
+    /// let mut actual = Cube::new();
+    /// intended.apply_moves("RF");
+    /// actual.apply_moves("RF'");
+    ///
+    /// let fix = actual.moves_to(&intended, &mut IDSolver::new());
+    /// assert_eq!(fix, vec![Move::F2]);
+    /// ```
+    pub fn moves_to<T: Solver>(&self, target: &Cube, solver: &mut T) -> Vec<Move> {
+        // Solving target^-1 * self gives self^-1 * target, which takes us there
+        let relative = target.inverse().compose(self);
+        solver.find_solution(&relative)
+    }
+
+    /// Get the cubie faces visible on one face of the cube, indexed starting
+    /// from the given corner of the face. `Origin::TopLeft` gives the same
+    /// layout as `get_face`.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut cube = Cube::new();
+    /// cube.apply_moves("F");
+    /// assert_eq!(cube.get_face(Face::U)[6], Face::L);
+    /// assert_eq!(cube.get_face_with_origin(Face::U, Origin::BottomLeft)[0], Face::L);
+    /// ```
+    pub fn get_face_with_origin(&self, face: Face, origin: Origin) -> [Face; 9] {
+        let stickers = self.get_face(face);
+        let (flip_rows, flip_cols) = match origin {
+            Origin::TopLeft => (false, false),
+            Origin::TopRight => (false, true),
+            Origin::BottomLeft => (true, false),
+            Origin::BottomRight => (true, true),
+        };
+
+        let mut result = stickers;
+        for row in 0..3 {
+            for col in 0..3 {
+                let from_row = if flip_rows { 2 - row } else { row };
+                let from_col = if flip_cols { 2 - col } else { col };
+                result[row * 3 + col] = stickers[from_row * 3 + from_col];
+            }
+        }
+
+        result
+    }
+
+    /// Get the cubie faces visible on one face of the cube, oriented the way the
+    /// face is drawn in a net of the given style. Only the back face differs
+    /// between styles; the others are the same as `get_face`.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut cube = Cube::new();
+    /// cube.apply_moves("R");
+    /// // U's right column ends up on the side of B next to R
+    /// assert_eq!(cube.get_face(Face::B)[0], Face::U);
+    /// assert_eq!(cube.get_face_for_net(Face::B, NetStyle::Vertical)[8], Face::U);
+    /// assert_eq!(cube.get_face_for_net(Face::B, NetStyle::Transparent)[2], Face::U);
+    /// ```
+    pub fn get_face_for_net(&self, face: Face, style: NetStyle) -> [Face; 9] {
+        let origin = match (face, style) {
+            (Face::B, NetStyle::Vertical) => Origin::BottomRight,
+            (Face::B, NetStyle::Transparent) => Origin::TopRight,
+            _ => Origin::TopLeft,
+        };
+        self.get_face_with_origin(face, origin)
+    }
+
+    /// Solve the cube using the given method
+    ///
+    /// Returns a vector of the moves used to solve the cube
+    pub fn solve<T: Solver>(&mut self, solver: &mut T) -> Vec<Move> {
+        let moves = solver.find_solution(self);
+        for m in moves.clone() {
+            self.apply_move(m);
+        }
+
+        moves
+    }
+
+    /// Get this cube with a superflip applied as well. The superflip commutes
+    /// with every move, so it doesn't matter whether it's applied before or
+    /// after the moves that produced this cube. Since the superflip needs 20
+    /// face turns to solve, a cube that's `n` face turns from solved is at
+    /// least `20 - n` face turns from solved once it's superflipped.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("FR");
+    ///
+    /// let mut expected = superflip_cube();
+    /// expected.apply_moves("FR");
+    /// assert_eq!(c.superflip_composed(), expected);
+    /// assert_eq!(Cube::new().superflip_composed(), superflip_cube());
+    /// ```
+    pub fn superflip_composed(&self) -> Cube {
+        self.compose(&superflip_cube())
+    }
+
+    /// Get the length of the solution the given solver finds, without
+    /// changing the cube. This is handy for measuring how hard a scramble is.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    /// use rubik::solver::IDSolver;
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("FR");
+    ///
+    /// assert_eq!(c.solution_length(&mut IDSolver::new()), 2);
+    /// assert!(!c.is_solved());
+    /// ```
+    pub fn solution_length<T: Solver>(&self, solver: &mut T) -> usize {
+        let mut c = *self;
+        c.solve(solver).len()
+    }
+
+    /// Solve a copy of the cube and get every state along the way, e.g. for
+    /// animating the solution. The first frame is the cube as it is now, with
+    /// no move; each frame after that is the state after one more move of the
+    /// solution. The cube itself isn't changed.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    /// use rubik::solver::IDSolver;
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("F");
+    ///
+    /// let frames = c.solve_frames(&mut IDSolver::new());
+    /// assert_eq!(frames.len(), 2);
+    /// assert_eq!(frames[0].move_applied, None);
+    /// assert_eq!(frames[0].state, c);
+    /// assert_eq!(frames[1].move_applied, Some(Move::FPrime));
+    /// assert_eq!(frames[1].rotation, (Axis::Z, Layer::Positive, 1));
+    /// assert!(frames[1].state.is_solved());
+    /// ```
+    pub fn solve_frames<T: Solver>(&self, solver: &mut T) -> Vec<Frame> {
+        let mut state = *self;
+        let mut frames = vec![Frame {
+            move_applied: None,
+            state,
+            rotation: (Axis::X, Layer::Middle, 0),
+        }];
+
+        for m in solver.find_solution(self) {
+            state.apply_move(m);
+            frames.push(Frame {
+                move_applied: Some(m),
+                state,
+                rotation: m.rotation(),
+            });
+        }
+
+        frames
+    }
+
+    /// Suggest a good next move, i.e. the first move of the solution found by
+    /// the given solver. The cube itself isn't changed.
+    ///
+    /// Returns `None` if the cube is already solved or the solver can't find a
+    /// solution.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    /// use rubik::solver::IDSolver;
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("FU");
+    /// assert_eq!(c.best_next_move(&mut IDSolver::new()), Some(Move::UPrime));
+    /// ```
+    pub fn best_next_move<T: Solver>(&self, solver: &mut T) -> Option<Move> {
+        if self.is_solved() {
+            return None;
+        }
+
+        solver.find_solution(self).first().cloned()
+    }
+
+    /// Solve the cube using the given method, calling `on_solution` with each
+    /// solution the solver finds while it's still searching. See
+    /// `Solver::find_solution_streaming`.
+    ///
+    /// Returns a vector of the moves used to solve the cube
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    /// use rubik::solver::IDSolver;
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("FU");
+    ///
+    /// let mut found = Vec::new();
+    /// let solution = c.solve_streaming(&mut IDSolver::new(), |s| found.push(s.len()));
+    /// assert_eq!(found, vec![2]);
+    /// assert_eq!(solution.len(), 2);
+    /// assert!(c.is_solved());
+    /// ```
+    pub fn solve_streaming<T: Solver, F: FnMut(&[Move])>(&mut self, solver: &mut T, mut on_solution: F) -> Vec<Move> {
+        let moves = solver.find_solution_streaming(self, &mut on_solution);
+        for &m in &moves {
+            self.apply_move(m);
+        }
+
+        moves
+    }
+
+    /// Solve the cube using the solver described by a configuration
+    ///
+    /// Returns a vector of the moves used to solve the cube, or an error if
+    /// the solver couldn't find a solution. The cube is unchanged on error.
+    pub fn solve_with(&mut self, config: &SolverConfig) -> Result<Vec<Move>, SolveError> {
+        let moves = config.find_solution(self)?;
+        for &m in &moves {
+            self.apply_move(m);
+        }
+
+        Ok(moves)
+    }
+
+    /// Attach a function that is called with every move applied to the cube
+    /// from now on. Only moves applied through the returned `ObservedCube` are
+    /// reported, so copies of the cube (e.g. the ones a solver makes while
+    /// searching) stay silent.
+    ///
+    /// The observer lives in a wrapper rather than in the cube itself because
+    /// `Cube` is `Copy` and is used as a hash key; storing a closure in it
+    /// would make it neither, and every copy would share the observer. Cubes
+    /// that aren't observed pay nothing for it.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut applied = Vec::new();
+    /// {
+    ///     let mut cube = Cube::new().with_move_observer(|m| applied.push(m));
+    ///     cube.apply_moves("FR'");
+    /// }
+    /// assert_eq!(applied, vec![Move::F, Move::RPrime]);
+    /// ```
+    pub fn with_move_observer<F: FnMut(Move)>(self, observer: F) -> ObservedCube<F> {
+        ObservedCube {
+            cube: self,
+            observer,
+        }
+    }
+
+    /// Start recording the moves applied to the cube, so they can be undone.
+    /// Only moves applied through the returned `HistoryCube` are recorded;
+    /// a plain `Cube` never keeps a history.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut cube = Cube::new().with_history();
+    /// cube.apply_moves("FR'");
+    /// assert_eq!(cube.history(), &[Move::F, Move::RPrime]);
+    /// ```
+    pub fn with_history(self) -> HistoryCube {
+        HistoryCube {
+            cube: self,
+            history: Vec::new(),
+        }
+    }
+
+    /// Start editing the cube's stickers one at a time, e.g. to enter the
+    /// colors of a real cube. The edits only take effect once
+    /// `StickerEditor::rebuild_from_stickers` accepts them.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("F");
+    ///
+    /// let mut editor = Cube::new().edit_stickers();
+    /// for &face in &[Face::U, Face::R, Face::F, Face::D, Face::L, Face::B] {
+    ///     for (i, &color) in c.get_face(face).iter().enumerate() {
+    ///         editor.set_sticker(face, i, color);
+    ///     }
+    /// }
+    /// assert_eq!(editor.rebuild_from_stickers(), Ok(()));
+    /// assert_eq!(editor.cube(), &c);
+    /// ```
+    pub fn edit_stickers(self) -> StickerEditor {
+        StickerEditor {
+            cube: self,
+            stickers: self.all_stickers(),
+        }
+    }
+
+    /// Print the current state of the cube
+    ///
+    /// Displays all the faces laid out as follows (see the `Display` impl):
+    ///   U
+    ///  LFRB
+    ///   D
+    pub fn print(&self) {
+        println!("\n{}", self);
+    }
+
+    /// Print the current state of the cube like `print`, but with each
+    /// sticker's background colored using ANSI escape codes. If `no_color` is
+    /// true the plain letters are printed instead, e.g. for terminals that
+    /// don't support color.
+    pub fn print_colored(&self, no_color: bool) {
+        println!("\n{}", self.colored(no_color));
+    }
+
+    /// Get a wrapper that displays the cube's net with ANSI colors, the same
+    /// way as `print_colored`
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let c = Cube::new();
+    /// let net = c.colored(false).to_string();
+    /// assert!(net.starts_with(&format!("   {}U", face_ansi_code(Face::U))));
+    ///
+    /// assert_eq!(c.colored(true).to_string(), c.to_string());
+    /// ```
+    pub fn colored(&self, no_color: bool) -> ColoredDisplay<'_> {
+        ColoredDisplay {
+            cube: self,
+            no_color,
+        }
+    }
+
+    /// Render the cube's net as a standalone SVG image using the default
+    /// `SvgOptions`. The faces are laid out the same way as `print`.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let svg = Cube::new().to_svg();
+    /// assert!(svg.starts_with("<svg"));
+    /// assert_eq!(svg.matches("<rect").count(), 54);
+    /// ```
+    pub fn to_svg(&self) -> String {
+        self.to_svg_with(&SvgOptions::default())
+    }
+
+    /// Render the cube's net as a standalone SVG image with the given sticker
+    /// size and outline
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let options = SvgOptions {
+    ///     sticker_size: 10,
+    ///     ..SvgOptions::default()
+    /// };
+    /// let svg = Cube::new().to_svg_with(&options);
+    /// assert!(svg.contains("width=\"120\" height=\"90\""));
+    /// ```
+    pub fn to_svg_with(&self, options: &SvgOptions) -> String {
+        // Each face's position in the net, in faces from the top left
+        let layout = [
+            (Face::U, 1, 0),
+            (Face::L, 0, 1),
+            (Face::F, 1, 1),
+            (Face::R, 2, 1),
+            (Face::B, 3, 1),
+            (Face::D, 1, 2),
+        ];
+        let size = options.sticker_size;
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">\n",
+            size * 12,
+            size * 9,
+        );
+        for &(face, col, row) in &layout {
+            for (i, &sticker) in self.get_face(face).iter().enumerate() {
+                svg.push_str(&format!(
+                    "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"{}\" stroke-width=\"{}\"/>\n",
+                    (col * 3 + i as u32 % 3) * size,
+                    (row * 3 + i as u32 / 3) * size,
+                    size,
+                    size,
+                    face_svg_color(sticker),
+                    options.stroke_color,
+                    options.stroke_width,
+                ));
+            }
+        }
+        svg.push_str("</svg>\n");
+
+        svg
+    }
+
+    /// Group the 54 stickers into the 26 cubies they belong to. Each sticker is
+    /// given as a face and an index into that face (see `get_face`).
+    ///
+    /// The corner cubies are listed first, then the edges, then the centers.
+    /// Within each group, the stickers are listed in the order of the cubie's
+    /// faces, e.g. the UFL corner lists the position of its U sticker, then
+    /// its F sticker, then its L sticker, wherever they currently are.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut cube = Cube::new();
+    /// assert_eq!(cube.cubie_stickers()[0], vec![(Face::U, 6), (Face::F, 0), (Face::L, 2)]);
+    ///
+    /// // U moves the UFL corner to the ULB cubicle
+    /// cube.apply_moves("U");
+    /// assert_eq!(cube.cubie_stickers()[0], vec![(Face::U, 0), (Face::L, 0), (Face::B, 2)]);
+    /// ```
+    pub fn cubie_stickers(&self) -> Vec<Vec<(Face, usize)>> {
+        let mut groups = Vec::new();
+
+        for &cubie in &CORNERS {
+            let cubicle = *CORNERS.iter().find(|&&c| self.sigma.get(c) == cubie).unwrap();
+            let orientation = self.get_corner_orientation(cubicle);
+            let (a, b, c) = decompose_corner(cubicle);
+            let (p, q, r) = decompose_corner(cubie);
+            groups.push([p, q, r].iter().map(|&cubie_face| {
+                let face = *[a, b, c].iter().find(
+                    |&&f| get_corner_face(cubicle, cubie, f, orientation) == cubie_face
+                ).unwrap();
+                (face, corner_sticker_index(cubicle, face))
+            }).collect());
+        }
+
+        for &cubie in &EDGES {
+            let cubicle = *EDGES.iter().find(|&&e| self.tau.get(e) == cubie).unwrap();
+            let orientation = self.get_edge_orientation(cubicle);
+            let (a, b) = decompose_edge(cubicle);
+            let (p, q) = decompose_edge(cubie);
+            groups.push([p, q].iter().map(|&cubie_face| {
+                let face = *[a, b].iter().find(
+                    |&&f| get_edge_face(cubicle, cubie, f, orientation) == cubie_face
+                ).unwrap();
+                (face, edge_sticker_index(cubicle, face))
+            }).collect());
+        }
+
+        for &face in &FACES {
+            groups.push(vec![(face, 4)]);
+        }
+
+        groups
+    }
+
+    /// Get the color of the sticker on one face of a corner cubicle
+    fn corner_sticker(&self, cubicle: Corner, face: Face) -> Face {
+        get_corner_face(cubicle, self.sigma.get(cubicle), face, self.get_corner_orientation(cubicle))
+    }
+
+    /// Get the color of the sticker on one face of an edge cubicle
+    fn edge_sticker(&self, cubicle: Edge, face: Face) -> Face {
+        get_edge_face(cubicle, self.tau.get(cubicle), face, self.get_edge_orientation(cubicle))
+    }
+
+    /// Render the net shown by `print` as a list of lines, each padded to the
+    /// full width of the net
+    fn net_lines(&self) -> Vec<String> {
+        self.net_lines_with(|f| format!("{:?}", f))
+    }
+
+    /// Render the net shown by `print` with each sticker formatted by the
+    /// given function
+    fn net_lines_with<F: Fn(Face) -> String>(&self, sticker: F) -> Vec<String> {
+        let faces = [
+            self.get_face(Face::U),
+            self.get_face(Face::L),
+            self.get_face(Face::F),
+            self.get_face(Face::R),
+            self.get_face(Face::B),
+            self.get_face(Face::D),
+        ];
+        let row = |face: &[Face; 9], y: usize| -> String {
+            face[y*3..y*3+3].iter().map(|&f| sticker(f)).collect()
+        };
+
+        let mut lines = Vec::new();
+        for y in 0..3 {
+            lines.push(format!("   {}      ", row(&faces[0], y)));
+        }
+        for y in 0..3 {
+            lines.push(faces[1..5].iter().map(|f| row(f, y)).collect());
+        }
+        for y in 0..3 {
+            lines.push(format!("   {}      ", row(&faces[5], y)));
+        }
+
+        lines
+    }
+
+    /// Get the orientation of a corner cubicle
+    fn get_corner_orientation(&self, c: Corner) -> u8 {
+        use self::Corner::*;
+        match c {
+            UFL => self.x.0,
+            URF => self.x.1,
+            UBR => self.x.2,
+            ULB => self.x.3,
+            DBL => self.x.4,
+            DLF => self.x.5,
+            DFR => self.x.6,
+            DRB => self.x.7,
+        }
+    }
+
+    /// Get the orientation of an edge cubicle
+    fn get_edge_orientation(&self, e: Edge) -> u8 {
+        use self::Edge::*;
+        match e {
+            UB => self.y.0,
+            UR => self.y.1,
+            UF => self.y.2,
+            UL => self.y.3,
+            LB => self.y.4,
+            RB => self.y.5,
+            RF => self.y.6,
+            LF => self.y.7,
+            DB => self.y.8,
+            DR => self.y.9,
+            DF => self.y.10,
+            DL => self.y.11,
+        }
+    }
+}
+
+/// The ANSI escape code that resets the text color
+const ANSI_RESET: &str = "\x1b[0m";
+
+/// Get the ANSI escape code that sets the background to a face's color, with
+/// black text on top. The colors follow the usual scheme: white U, red R,
+/// green F, yellow D, orange L, and blue B. Orange isn't one of the basic
+/// terminal colors, so it uses the 256 color palette.
+///
+/// # Example
+/// ```
+/// use rubik::cube::*;
+///
+/// let sticker = format!("{}{:?}\x1b[0m", face_ansi_code(Face::R), Face::R);
+/// assert_eq!(sticker, "\x1b[30;41mR\x1b[0m");
+/// ```
+pub fn face_ansi_code(face: Face) -> &'static str {
+    match face {
+        Face::U => "\x1b[30;47m",
+        Face::R => "\x1b[30;41m",
+        Face::F => "\x1b[30;42m",
+        Face::D => "\x1b[30;43m",
+        Face::L => "\x1b[30;48;5;208m",
+        Face::B => "\x1b[30;44m",
+    }
+}
+
+/// Displays a cube's net with ANSI colors. See `Cube::colored`.
+pub struct ColoredDisplay<'a> {
+    cube: &'a Cube,
+    no_color: bool,
+}
+
+impl fmt::Display for ColoredDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.no_color {
+            return write!(f, "{}", self.cube);
+        }
+
+        let lines = self.cube.net_lines_with(|face| format!("{}{:?}{}", face_ansi_code(face), face, ANSI_RESET));
+        for (i, line) in lines.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
             }
-            prevch = ch;
+            write!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// Settings for rendering a cube as SVG. See `Cube::to_svg_with`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SvgOptions {
+    /// The width and height of each sticker in pixels
+    pub sticker_size: u32,
+    /// The width of the outline around each sticker in pixels
+    pub stroke_width: u32,
+    /// The color of the outline around each sticker, in any form SVG accepts
+    pub stroke_color: String,
+}
+
+impl Default for SvgOptions {
+    /// By default, stickers are 30 pixels wide with a 2 pixel black outline
+    fn default() -> SvgOptions {
+        SvgOptions {
+            sticker_size: 30,
+            stroke_width: 2,
+            stroke_color: String::from("#000000"),
+        }
+    }
+}
+
+/// Get the color used for a face's stickers in SVG output. This is the same
+/// scheme as `face_ansi_code`.
+fn face_svg_color(face: Face) -> &'static str {
+    match face {
+        Face::U => "#ffffff",
+        Face::R => "#b71234",
+        Face::F => "#009b48",
+        Face::D => "#ffd500",
+        Face::L => "#ff5800",
+        Face::B => "#0046ad",
+    }
+}
+
+/// The color of a sticker on a real cube
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Color {
+    White,
+    Yellow,
+    Red,
+    Orange,
+    Green,
+    Blue,
+}
+
+/// The color of the stickers on each face of a real cube. See
+/// `Cube::to_colors`.
+///
+/// # Example
+/// ```
+/// use rubik::cube::*;
+///
+/// // A cube with the blue and green stickers swapped
+/// let scheme = ColorScheme { f: Color::Blue, b: Color::Green, ..ColorScheme::western() };
+/// assert_eq!(scheme.color(Face::F), Color::Blue);
+/// assert_eq!(scheme.color(Face::U), Color::White);
+/// ```
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ColorScheme {
+    /// The color of the U face
+    pub u: Color,
+    /// The color of the R face
+    pub r: Color,
+    /// The color of the F face
+    pub f: Color,
+    /// The color of the D face
+    pub d: Color,
+    /// The color of the L face
+    pub l: Color,
+    /// The color of the B face
+    pub b: Color,
+}
+
+impl Default for ColorScheme {
+    /// By default, use the western color scheme
+    fn default() -> ColorScheme {
+        ColorScheme::western()
+    }
+}
+
+impl ColorScheme {
+    /// The usual color scheme: white U, red R, green F, yellow D, orange L,
+    /// and blue B. This is the scheme used by `face_ansi_code` and `to_svg`.
+    pub fn western() -> ColorScheme {
+        ColorScheme {
+            u: Color::White,
+            r: Color::Red,
+            f: Color::Green,
+            d: Color::Yellow,
+            l: Color::Orange,
+            b: Color::Blue,
+        }
+    }
+
+    /// Get the color of a face's stickers
+    pub fn color(&self, face: Face) -> Color {
+        match face {
+            Face::U => self.u,
+            Face::R => self.r,
+            Face::F => self.f,
+            Face::D => self.d,
+            Face::L => self.l,
+            Face::B => self.b,
+        }
+    }
+}
+
+/// A cube that reports every move applied to it. See `Cube::with_move_observer`.
+pub struct ObservedCube<F: FnMut(Move)> {
+    cube: Cube,
+    observer: F,
+}
+
+impl<F: FnMut(Move)> ObservedCube<F> {
+    /// Get the current state of the cube
+    pub fn cube(&self) -> &Cube {
+        &self.cube
+    }
+
+    /// Stop observing the cube and get its current state
+    pub fn into_inner(self) -> Cube {
+        self.cube
+    }
+
+    /// Apply a single move to the cube and report it to the observer
+    pub fn apply_move(&mut self, m: Move) {
+        self.cube.apply_move(m);
+        (self.observer)(m);
+    }
+
+    /// Apply a string of moves to the cube, reporting each one. See
+    /// `Cube::apply_moves`.
+    ///
+    /// # Panics
+    /// This function will panic if it encounters an invalid character.
+    pub fn apply_moves(&mut self, moves: &str) {
+        if let Err(e) = self.try_apply_moves(moves) {
+            panic!("{}", e);
+        }
+    }
+
+    /// Apply a string of moves to the cube, reporting each one. Nothing is
+    /// applied or reported if the string contains an invalid move. See
+    /// `Cube::try_apply_moves`.
+    pub fn try_apply_moves(&mut self, moves: &str) -> Result<(), MoveParseError> {
+        for m in parse_moves(moves)? {
+            self.apply_move(m);
+        }
+
+        Ok(())
+    }
+
+    /// Solve the cube using the given method, reporting each move of the
+    /// solution. See `Cube::solve`.
+    pub fn solve<T: Solver>(&mut self, solver: &mut T) -> Vec<Move> {
+        let moves = solver.find_solution(&self.cube);
+        for &m in &moves {
+            self.apply_move(m);
+        }
+
+        moves
+    }
+}
+
+/// A cube that records every move applied to it so they can be undone. See
+/// `Cube::with_history`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HistoryCube {
+    cube: Cube,
+    history: Vec<Move>,
+}
+
+impl HistoryCube {
+    /// Get the current state of the cube
+    pub fn cube(&self) -> &Cube {
+        &self.cube
+    }
+
+    /// Stop recording moves and get the current state of the cube
+    pub fn into_inner(self) -> Cube {
+        self.cube
+    }
+
+    /// Get the moves applied since the history started, oldest first. Moves
+    /// that have been undone aren't included.
+    pub fn history(&self) -> &[Move] {
+        &self.history
+    }
+
+    /// Apply a single move to the cube and record it
+    pub fn apply_move(&mut self, m: Move) {
+        self.cube.apply_move(m);
+        self.history.push(m);
+    }
+
+    /// Apply a string of moves to the cube, recording each one. See
+    /// `Cube::apply_moves`.
+    ///
+    /// # Panics
+    /// This function will panic if it encounters an invalid character.
+    pub fn apply_moves(&mut self, moves: &str) {
+        if let Err(e) = self.try_apply_moves(moves) {
+            panic!("{}", e);
         }
-        
-        for m in movelist {
+    }
+
+    /// Apply a string of moves to the cube, recording each one. Nothing is
+    /// applied or recorded if the string contains an invalid move. See
+    /// `Cube::try_apply_moves`.
+    pub fn try_apply_moves(&mut self, moves: &str) -> Result<(), MoveParseError> {
+        for m in parse_moves(moves)? {
             self.apply_move(m);
         }
+
+        Ok(())
+    }
+
+    /// Undo the last move in the history by applying its inverse. Returns
+    /// the move that was undone, or `None` if the history is empty.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// let mut cube = Cube::new().with_history();
+    /// cube.apply_moves("FR");
+    /// assert_eq!(cube.undo(), Some(Move::R));
+    /// assert_eq!(cube.undo(), Some(Move::F));
+    /// assert_eq!(cube.undo(), None);
+    /// assert!(cube.cube().is_solved());
+    /// ```
+    pub fn undo(&mut self) -> Option<Move> {
+        let m = self.history.pop()?;
+        self.cube.apply_move(m.inverse());
+        Some(m)
+    }
+}
+
+/// A cube whose stickers can be changed one at a time, then checked and turned
+/// back into a cube. See `Cube::edit_stickers`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct StickerEditor {
+    cube: Cube,
+    stickers: [Face; 54],
+}
+
+impl StickerEditor {
+    /// Get the cube as of the last successful rebuild, or the cube editing
+    /// started with if there hasn't been one
+    pub fn cube(&self) -> &Cube {
+        &self.cube
+    }
+
+    /// Get the edited stickers on one face, laid out like `Cube::get_face`
+    pub fn get_face(&self, face: Face) -> [Face; 9] {
+        let offset = sticker_offset(face);
+        let mut stickers = [face; 9];
+        stickers.copy_from_slice(&self.stickers[offset..offset + 9]);
+        stickers
+    }
+
+    /// Set the color of one sticker. The index is the sticker's position in
+    /// the `Cube::get_face` layout.
+    ///
+    /// # Panics
+    /// This function will panic if the index is more than 8.
+    pub fn set_sticker(&mut self, face: Face, index: usize, color: Face) {
+        if index > 8 {
+            panic!("Sticker index {} is out of range (0-8)", index);
+        }
+        self.stickers[sticker_offset(face) + index] = color;
+    }
+
+    /// Turn the edited stickers back into a cube. The stickers are checked the
+    /// same way as `Cube::from_faces`; if they're valid they become the
+    /// new `cube`, otherwise the cube is unchanged and the edits are kept so
+    /// they can be fixed.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::*;
+    ///
+    /// // Flipping a single edge is impossible
+    /// let mut editor = Cube::new().edit_stickers();
+    /// editor.set_sticker(Face::U, 7, Face::F);
+    /// editor.set_sticker(Face::F, 1, Face::U);
+    /// assert_eq!(editor.rebuild_from_stickers(), Err(FaceletError::InvalidState(CubeError::Illegal)));
+    /// assert!(editor.cube().is_solved());
+    ///
+    /// // Flipping two of them is fine
+    /// editor.set_sticker(Face::U, 5, Face::R);
+    /// editor.set_sticker(Face::R, 1, Face::U);
+    /// assert_eq!(editor.rebuild_from_stickers(), Ok(()));
+    /// assert!(!editor.cube().is_solved());
+    /// ```
+    pub fn rebuild_from_stickers(&mut self) -> Result<(), FaceletError> {
+        let faces = [
+            (Face::U, self.get_face(Face::U)), (Face::R, self.get_face(Face::R)),
+            (Face::F, self.get_face(Face::F)), (Face::D, self.get_face(Face::D)),
+            (Face::L, self.get_face(Face::L)), (Face::B, self.get_face(Face::B)),
+        ];
+        self.cube = Cube::from_faces(faces)?;
+        Ok(())
+    }
+}
+
+/// Get the index of the first sticker on a face in `Cube::all_stickers`
+fn sticker_offset(face: Face) -> usize {
+    FACES.iter().position(|&f| f == face).unwrap() * 9
+}
+
+/// The pieces an algorithm moves. See `Cube::apply_algorithm`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AlgorithmEffect {
+    /// The cycles of corners that are moved, longest first
+    pub corners: Vec<Vec<Corner>>,
+    /// The cycles of edges that are moved, longest first
+    pub edges: Vec<Vec<Edge>>,
+}
+
+/// Perform a [superflip](https://en.wikipedia.org/wiki/Superflip) on a cube
+///
+/// # Example
+/// ```
+/// use rubik::cube::{Cube, superflip};
+///
+/// let c = Cube::new();
+/// let superc = superflip(&c);
+///
+/// assert!(c.is_solved());
+/// assert!(!superc.is_solved());
+/// ```
+pub fn superflip(cube: &Cube) -> Cube {
+    apply_pattern(cube, "superflip").unwrap()
+}
+
+/// Get a solved cube with a [superflip](https://en.wikipedia.org/wiki/Superflip)
+/// applied, for composing with other patterns
+///
+/// # Example
+/// ```
+/// use rubik::cube::*;
+///
+/// // The superflip is its own inverse
+/// assert!(superflip_cube().compose(&superflip_cube()).is_solved());
+/// ```
+pub fn superflip_cube() -> Cube {
+    superflip(&Cube::new())
+}
+
+/// Get a solved cube with the four spot pattern applied, which makes the
+/// centers of F, B, L, and R look swapped with their opposites
+///
+/// # Example
+/// ```
+/// use rubik::cube::*;
+///
+/// let combined = superflip_cube().compose(&four_spot_cube());
+/// assert!(!combined.is_solved());
+/// ```
+pub fn four_spot_cube() -> Cube {
+    apply_pattern(&Cube::new(), "four_spot").unwrap()
+}
+
+/// Apply the checkerboard pattern to a cube, which swaps every edge with the
+/// one on the opposite side of its face
+///
+/// # Example
+/// ```
+/// use rubik::cube::{Cube, checkerboard};
+///
+/// let c = checkerboard(&Cube::new());
+/// assert!(!c.is_solved());
+///
+/// // The checkerboard is its own inverse
+/// assert!(checkerboard(&c).is_solved());
+/// ```
+pub fn checkerboard(cube: &Cube) -> Cube {
+    apply_pattern(cube, "checkerboard").unwrap()
+}
+
+/// Apply the cube in cube pattern to a cube, which makes a 2x2x2 block in the
+/// UFR corner look like a smaller cube nested in the big one
+///
+/// # Example
+/// ```
+/// use rubik::cube::{Cube, cube_in_cube};
+///
+/// let c = cube_in_cube(&Cube::new());
+/// assert!(!c.is_solved());
+/// ```
+pub fn cube_in_cube(cube: &Cube) -> Cube {
+    apply_pattern(cube, "cube_in_cube").unwrap()
+}
+
+/// Apply the six spot pattern to a cube, which leaves each center surrounded
+/// by stickers of a different color
+///
+/// # Example
+/// ```
+/// use rubik::cube::{Cube, Face, six_spot};
+///
+/// let c = six_spot(&Cube::new());
+/// assert_eq!(c.get_face(Face::U)[4], Face::U);
+/// assert_eq!(c.get_face(Face::U)[0], Face::F);
+/// ```
+pub fn six_spot(cube: &Cube) -> Cube {
+    apply_pattern(cube, "six_spot").unwrap()
+}
+
+/// The named patterns that can be applied with `apply_pattern`, and the moves
+/// that make them
+const PATTERNS: [(&str, &str); 5] = [
+    ("superflip", "UR2FBRB2RU2LB2RU'D'R2FR'LB2U2F2"),
+    ("four_spot", "F2B2UD'R2L2UD'"),
+    ("checkerboard", "R2L2U2D2F2B2"),
+    ("cube_in_cube", "FLFU'RUF2L2U'L'BD'B'L2U"),
+    ("six_spot", "UD'RL'FB'UD'"),
+];
+
+/// An error returned when asking for a pattern that doesn't exist
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PatternError {
+    /// The name of the pattern that was asked for
+    pub name: String,
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unknown pattern '{}'; available patterns are {}", self.name, list_patterns().join(", "))
     }
+}
 
-    /// Apply a single move to the cube. See lemma 11.4 in the Chen paper for details about how x and y are calculated.
-    ///
-    /// # Example
-    /// ```
-    /// use rubik::cube::*;
-    ///
-    /// let mut cube = Cube::new();
-    /// cube.apply_move(Move::F);
-    /// assert!(!cube.is_solved());
-    ///
-    /// cube.apply_move(Move::F);
-    /// cube.apply_move(Move::F);
-    /// cube.apply_move(Move::F);
-    /// assert!(cube.is_solved());
-    /// ```
-    pub fn apply_move(&mut self, m: Move) {
-        use self::Move;
-        // Compute sigma and tau
-        self.sigma.permute(m);
-        self.tau.permute(m);
+impl Error for PatternError {}
 
-        // Compute X and Y
-        let (swap_indices, addends) = match m {
-            Move::F => ([5,0,2,3,4,6,1,7], [1,2,0,0,0,2,1,0]),
-            Move::R => ([0,6,1,3,4,5,7,2], [0,1,2,0,0,0,2,1]),
-            Move::U => ([1,2,3,0,4,5,6,7], [0u8; 8]),
-            Move::B => ([0,1,7,2,3,5,6,4], [0,0,1,2,1,0,0,2]),
-            Move::L => ([3,1,2,4,5,0,6,7], [2,0,0,1,2,1,0,0]),
-            Move::D => ([0,1,2,3,7,4,5,6], [0u8; 8]),
-            Move::FPrime => ([1,6,2,3,4,0,5,7], [1,2,0,0,0,2,1,0]),
-            Move::RPrime => ([0,2,7,3,4,5,1,6], [0,1,2,0,0,0,2,1]),
-            Move::UPrime => ([3,0,1,2,4,5,6,7], [0u8; 8]),
-            Move::BPrime => ([0,1,3,4,7,5,6,2], [0,0,1,2,1,0,0,2]),
-            Move::LPrime => ([5,1,2,0,3,4,6,7], [2,0,0,1,2,1,0,0]),
-            Move::DPrime => ([0,1,2,3,5,6,7,4], [0u8; 8]),
-        };
-        self.x = swap_x(self.x, &swap_indices);
-        self.x = add_x(self.x, &addends);
+/// Apply a named pattern to a cube. See `list_patterns` for the names.
+///
+/// # Example
+/// ```
+/// use rubik::cube::*;
+///
+/// let c = Cube::new();
+/// assert_eq!(apply_pattern(&c, "checkerboard"), Ok(checkerboard(&c)));
+///
+/// let err = apply_pattern(&c, "plaid").unwrap_err();
+/// assert_eq!(err.name, "plaid");
+/// ```
+pub fn apply_pattern(cube: &Cube, name: &str) -> Result<Cube, PatternError> {
+    let moves = PATTERNS.iter().find(|&&(n, _)| n == name).map(|&(_, moves)| moves);
+    match moves {
+        Some(moves) => {
+            let mut c = *cube;
+            c.apply_moves(moves);
+            Ok(c)
+        },
+        None => Err(PatternError { name: name.to_string() }),
+    }
+}
 
-        let (swap_indices, addends) = match m {
-            Move::F => ([0,1,7,3,4,5,2,10,8,9,6,11], [0,0,1,0,0,0,1,1,0,0,1,0]),
-            Move::R => ([0,6,2,3,4,1,9,7,8,5,10,11], [0u8; 12]),
-            Move::U => ([3,0,1,2,4,5,6,7,8,9,10,11], [0u8; 12]),
-            Move::B => ([5,1,2,3,0,8,6,7,4,9,10,11], [1,0,0,0,1,1,0,0,1,0,0,0]),
-            Move::L => ([0,1,2,4,11,5,6,3,8,9,10,7], [0u8; 12]),
-            Move::D => ([0,1,2,3,4,5,6,7,9,10,11,8], [0u8; 12]),
-            Move::FPrime => ([0,1,6,3,4,5,10,2,8,9,7,11], [0,0,1,0,0,0,1,1,0,0,1,0]),
-            Move::RPrime => ([0,5,2,3,4,9,1,7,8,6,10,11], [0u8; 12]),
-            Move::UPrime => ([1,2,3,0,4,5,6,7,8,9,10,11], [0u8; 12]),
-            Move::BPrime => ([4,1,2,3,8,0,6,7,5,9,10,11], [1,0,0,0,1,1,0,0,1,0,0,0]),
-            Move::LPrime => ([0,1,2,7,3,5,6,11,8,9,10,4], [0u8; 12]),
-            Move::DPrime => ([0,1,2,3,4,5,6,7,11,8,9,10], [0u8; 12]),
-        };
-        self.y = swap_y(self.y, &swap_indices);
-        self.y = add_y(self.y, &addends);
+/// Get the names of the patterns that can be applied with `apply_pattern`
+///
+/// # Example
+/// ```
+/// use rubik::cube::*;
+///
+/// assert!(list_patterns().contains(&"superflip"));
+/// ```
+pub fn list_patterns() -> Vec<&'static str> {
+    PATTERNS.iter().map(|&(name, _)| name).collect()
+}
+
+/// One step of an animated move sequence. See `Cube::solve_frames`.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Frame {
+    /// The move that produced this state, or `None` for the starting state
+    pub move_applied: Option<Move>,
+    /// The state of the cube after the move
+    pub state: Cube,
+    /// The rotation to animate to get from the previous state to this one, as
+    /// returned by `Move::rotation`. The starting state has no rotation, i.e.
+    /// zero quarter turns.
+    pub rotation: (Axis, Layer, i8),
+}
+
+/// A rough measure of how hard a cube is to solve, based on how many of its 20
+/// corner and edge pieces are out of place or misoriented
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Difficulty {
+    /// 1 to 8 unsolved pieces
+    Easy,
+    /// 9 to 15 unsolved pieces
+    Medium,
+    /// 16 to 20 unsolved pieces
+    Hard,
+}
+
+/// SplitMix64, a small PRNG whose output is fully specified so seeded
+/// scrambles don't depend on the `rand` crate's implementation.
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
     }
 
-    /// Determine whether the cube is in the solved state
-    pub fn is_solved(&self) -> bool {
-        self.sigma == CornerPermutation::default() &&
-        self.tau == EdgePermutation::default() &&
-        self.x == X::default() &&
-        self.y == Y::default()
+    /// Pick a number in `0..n`
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
     }
+}
 
-    /// Get the cubie faces visible on one face of the cube. Faces are stored out
-    /// in the array such that the top row of the face is in the first three
-    /// elements, the next row is stored in the next three elements, and the
-    /// bottom row is stored in the last three elements.
+impl Rng for SplitMix64 {
+    fn next_u32(&mut self) -> u32 {
+        (self.next() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.next()
+    }
+}
+
+/// Version 1 of the seeded scramble algorithm. Its output for a given seed is
+/// frozen: if the algorithm ever needs to change it will be added as a new
+/// version instead, so old seeds always reproduce the same scrambles.
+///
+/// The algorithm is:
+///
+/// 1. The random numbers come from SplitMix64 seeded with the seed. A number
+///    in `0..n` is the next output modulo `n`.
+/// 2. For each move, list the faces F, R, U, B, L, D in that order and remove
+///    the face of the previous move. If the previous two moves turned opposite
+///    faces, remove both of them.
+/// 3. Pick a face from the remaining list with one random number, then pick
+///    the direction with another: 0 is clockwise and 1 is counterclockwise.
+///
+/// Only quarter turns are generated.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ScrambleV1;
+
+impl ScrambleV1 {
+    /// The version number of this algorithm
+    pub const VERSION: u32 = 1;
+
+    /// Generate a scramble with the given number of moves
     ///
     /// # Example
     /// ```
     /// use rubik::cube::*;
     ///
-    /// let mut cube = Cube::new();
-    /// // On a solved cube, every cubie face on the up face should also be an up face
-    /// assert_eq!(cube.get_face(Face::U), [Face::U; 9]);
+    /// let moves = ScrambleV1::generate(2018, 25);
+    /// assert_eq!(moves.len(), 25);
+    /// assert_eq!(moves, ScrambleV1::generate(2018, 25));
     /// ```
-    pub fn get_face(&self, face: Face) -> [Face; 9] {
-        // Find the corner cubicles located in this face clockwise from top left
-        use self::Corner::*;
-        let corners = match face {
-            Face::F => [UFL, URF, DFR, DLF],
-            Face::R => [URF, UBR, DRB, DFR],
-            Face::U => [ULB, UBR, URF, UFL],
-            Face::B => [UBR, ULB, DBL, DRB],
-            Face::L => [ULB, UFL, DLF, DBL],
-            Face::D => [DLF, DFR, DRB, DBL],
-        };
+    pub fn generate(seed: u64, move_count: u8) -> Vec<Move> {
+        let mut rng = SplitMix64 { state: seed };
+        let mut moves: Vec<Move> = Vec::with_capacity(move_count as usize);
+        for _ in 0..move_count {
+            let candidates = scramble_faces(&moves);
+            let face = candidates[rng.below(candidates.len())];
+            let direction = rng.below(2);
+            moves.push(if direction == 0 { face } else { invert_move(face) });
+        }
+        moves
+    }
+}
 
-        // Find the edge cubicles in the face clockwise from the top
-        use self::Edge::*;
-        let edges = match face {
-            Face::F => [UF, RF, DF, LF],
-            Face::R => [UR, RB, DR, RF],
-            Face::U => [UB, UR, UF, UL],
-            Face::B => [UB, LB, DB, RB],
-            Face::L => [UL, LF, DL, LB],
-            Face::D => [DF, DR, DB, DL],
-        };
+/// Generate a random scramble of face turns and apply it to a solved cube.
+/// Turns of the same face are never next to each other, and neither are three
+/// turns on the same axis (e.g. `R L R`), so no moves in the scramble cancel
+/// out or merge. Passing a seed makes the scramble reproducible.
+///
+/// Returns the scramble in standard notation with spaces between the moves,
+/// and the scrambled cube.
+///
+/// # Example
+/// ```
+/// use rubik::cube::*;
+///
+/// let (scramble, cube) = generate_scramble(25, Some(2018));
+/// assert_eq!(scramble.split(' ').count(), 25);
+///
+/// let mut expected = Cube::new();
+/// expected.apply_moves(&scramble);
+/// assert_eq!(cube, expected);
+/// ```
+pub fn generate_scramble(length: usize, seed: Option<u64>) -> (String, Cube) {
+    let moves = match seed {
+        Some(seed) => scramble_moves(length, &mut SplitMix64 { state: seed }),
+        None => scramble_moves(length, &mut thread_rng()),
+    };
 
-        // Get the corner and edge cubies in each cubicle of interest
-        let corner_cubies: Vec<_> = corners.iter().map(|&c| self.sigma.get(c)).collect();
-        let edge_cubies: Vec<_> = edges.iter().map(|&e| self.tau.get(e)).collect();
+    let mut cube = Cube::new();
+    for &m in &moves {
+        cube.apply_move(m);
+    }
 
-        // Now get the orientations for the cubies we care about
-        let corner_orient: Vec<_> = corners.iter().map(|&c| self.get_corner_orientation(c)).collect();
-        let edge_orient: Vec<_> = edges.iter().map(|&e| self.get_edge_orientation(e)).collect();
+    (moves_to_string(&moves), cube)
+}
 
-        // Finally find the visible face of each cubie
-        let corner_faces: Vec<_> = corners.iter().enumerate().map(
-            |(i, &c)| get_corner_face(c, corner_cubies[i], face, corner_orient[i])
-        ).collect();
+/// The number of moves `generate_hard_scramble` makes after the superflip
+const HARD_SCRAMBLE_MOVES: usize = 3;
 
-        let edge_faces: Vec<_> = edges.iter().enumerate().map(
-            |(i, &e)| get_edge_face(e, edge_cubies[i], face, edge_orient[i])
-        ).collect();
+/// Generate a cube that's hard to solve, for stress testing solvers. The cube
+/// is a superflip, which needs 20 face turns to solve, followed by a few
+/// random face turns. Each face turn can only bring the cube one move closer
+/// to solved, so it always needs at least 17 face turns. The same seed always
+/// gives the same cube.
+///
+/// # Example
+/// ```
+/// use rubik::cube::*;
+///
+/// let cube = generate_hard_scramble(2018);
+/// assert!(!cube.is_solved());
+/// assert_eq!(cube, generate_hard_scramble(2018));
+/// ```
+pub fn generate_hard_scramble(seed: u64) -> Cube {
+    let mut cube = superflip_cube();
+    for m in scramble_moves(HARD_SCRAMBLE_MOVES, &mut SplitMix64 { state: seed }) {
+        cube.apply_move(m);
+    }
+    cube
+}
 
-        // Returned structure lists the faces clockwise from top left
-        [corner_faces[0],   edge_faces[0],  corner_faces[1],
-        edge_faces[3],      face,           edge_faces[1],
-        corner_faces[3],    edge_faces[2],  corner_faces[2]]
+/// Pick random face turns for `generate_scramble`
+fn scramble_moves<R: Rng>(length: usize, rng: &mut R) -> Vec<Move> {
+    let mut moves: Vec<Move> = Vec::with_capacity(length);
+    for _ in 0..length {
+        let faces = scramble_faces(&moves);
+        let face = *rng.choose(&faces).unwrap();
+        let turns = rng.gen_range(1, 4);
+        moves.extend(layer_moves(face, turns));
     }
+    moves
+}
 
-    /// Solve the cube using the given method
-    ///
-    /// Returns a vector of the moves used to solve the cube
-    pub fn solve<T: Solver>(&mut self, solver: &mut T) -> Vec<Move> {
-        let moves = solver.find_solution(&self);
-        for m in moves.clone() {
-            self.apply_move(m);
+/// Get the faces a scramble can turn next without undoing or merging with the
+/// moves before it, as clockwise quarter turns in the order F, R, U, B, L, D.
+/// The last move's face is left out, and so is the face before it if the last
+/// two moves turned opposite faces.
+fn scramble_faces(moves: &[Move]) -> Vec<Move> {
+    let mut excluded: Vec<Move> = Vec::new();
+    if let Some(&last) = moves.last() {
+        excluded.push(layer_turns(last).0);
+        if moves.len() > 1 {
+            // Consecutive moves never share a face, so sharing an axis means
+            // they're on opposite faces
+            let before = moves[moves.len() - 2];
+            if before.rotation().0 == last.rotation().0 {
+                excluded.push(layer_turns(before).0);
+            }
         }
-
-        moves
     }
 
-    /// Print the current state of the cube
-    ///
-    /// Displays all the faces laid out as follows:
-    ///   U
-    ///  LFRB
-    ///   D
-    pub fn print(&self) {
-        let faces = [
-            self.get_face(Face::U),
-            self.get_face(Face::L),
-            self.get_face(Face::F),
-            self.get_face(Face::R),
-            self.get_face(Face::B),
-            self.get_face(Face::D),
-        ];
-        print!("\n   ");
-        for i in 0..9 {
-            print!("{:?}", faces[0][i]);
+    QUARTER_TURNS[..6].iter().cloned().filter(|f| !excluded.contains(f)).collect()
+}
+
+/// A state to solve the cube to. Partial goals describe the intermediate steps
+/// of solving methods like CFOP; goals that don't take a face assume the first
+/// layer is D.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Goal {
+    /// The whole cube is solved
+    Solved,
+    /// The four edges on a face are solved
+    Cross(Face),
+    /// All of the corners and edges on a face are solved
+    FirstLayer(Face),
+    /// The D cross and three of the four first-two-layer slots are solved,
+    /// leaving a "keyhole" to insert the last pair through
+    Keyhole,
+    /// The first two layers are solved
+    F2L,
+    /// The first two layers are solved and every sticker on the U face is U
+    OrientedLastLayer,
+}
+
+/// The first-two-layers slots on the D layer, as a corner and the middle layer
+/// edge next to it
+const F2L_SLOTS: [(Corner, Edge); 4] = [
+    (Corner::DFR, Edge::RF),
+    (Corner::DLF, Edge::LF),
+    (Corner::DBL, Edge::LB),
+    (Corner::DRB, Edge::RB),
+];
+
+/// An algorithm for each OLL case, in order from case 1 to case 57. Each one
+/// solves the case it's listed for, so the case itself is the inverse.
+const OLL_ALGORITHMS: [&str; 57] = [
+    "RU2R2FRF'U2R'FRF'",
+    "FRUR'U'F'fRUR'U'f'",
+    "fRUR'U'f'U'FRUR'U'F'",
+    "fRUR'U'f'UFRUR'U'F'",
+    "r'U2RUR'Ur",
+    "rU2R'U'RU'r'",
+    "rUR'URU2r'",
+    "l'U'LU'L'U2l",
+    "RUR'U'R'FR2UR'U'F'",
+    "RUR'UR'FRF'RU2R'",
+    "rUR'UR'FRF'RU2r'",
+    "M'R'U'RU'R'U2RU'M",
+    "FURU'R2F'RURU'R'",
+    "R'FRUR'F'RFU'F'",
+    "r'U'rR'U'RUr'Ur",
+    "rUr'RUR'U'rU'r'",
+    "RUR'UR'FRF'U2R'FRF'",
+    "rUR'URU2r2U'RU'R'U2r",
+    "MURUR'U'M'R'FRF'",
+    "rUR'U'M2URU'R'U'M'",
+    "RU2R'U'RUR'U'RU'R'",
+    "RU2R2U'R2U'R2U2R",
+    "R2D'RU2R'DRU2R",
+    "rUR'U'r'FRF'",
+    "F'rUR'U'r'FR",
+    "RU2R'U'RU'R'",
+    "RUR'URU2R'",
+    "rUR'U'MURU'R'",
+    "RUR'U'RU'R'F'U'FRUR'",
+    "FR'FR2U'R'U'RUR'F2",
+    "R'U'FURU'R'F'R",
+    "LUF'U'L'ULFL'",
+    "RUR'U'R'FRF'",
+    "RUR2U'R'FRURU'F'",
+    "RU2R2FRF'RU2R'",
+    "L'U'LU'L'ULULF'L'F",
+    "FR'F'RURU'R'",
+    "RUR'URU'R'U'R'FRF'",
+    "LF'L'U'LUFU'L'",
+    "R'FRUR'U'F'UR",
+    "RUR'URU2R'FRUR'U'F'",
+    "R'U'RU'R'U2RFRUR'U'F'",
+    "F'U'L'ULF",
+    "FURU'R'F'",
+    "FRUR'U'F'",
+    "R'U'R'FRF'UR",
+    "R'U'R'FRF'R'FRF'UR",
+    "FRUR'U'RUR'U'F'",
+    "rU'r2Ur2Ur2U'r",
+    "r'Ur2U'r2U'r2Ur'",
+    "FURU'R'URU'R'F'",
+    "RUR'URU'BU'B'R'",
+    "r'U'RU'R'URU'R'U2r",
+    "rUR'URU'R'URU2r'",
+    "R'FRURU'R2F'R2U'R'URUR'",
+    "r'U'rU'R'URU'R'URr'Ur",
+    "RUR'U'M'URU'r'",
+];
 
-            if i > 0 && (i+1) % 3 == 0 {
-                print!("\n   ");
+/// An algorithm for each PLL case, by the usual name of the case. Each one
+/// solves the case it's listed for.
+const PLL_ALGORITHMS: [(&str, &str); 21] = [
+    ("Aa", "R'FR'B2RF'R'B2R2"),
+    ("Ab", "R2B2RFR'B2RF'R"),
+    ("E", "RB'R'FRBR'F'RBR'FRB'R'F'"),
+    ("F", "R'U'F'RUR'U'R'FR2U'R'U'RUR'UR"),
+    ("Ga", "R2UR'UR'U'RU'R2U'DR'URD'"),
+    ("Gb", "R'U'RUD'R2UR'URU'RU'R2D"),
+    ("Gc", "R2U'RU'RUR'UR2UD'RU'R'D"),
+    ("Gd", "RUR'U'DR2U'RU'R'UR'UR2D'"),
+    ("H", "M2UM2U2M2UM2"),
+    ("Ja", "R'UL'U2RU'R'U2RL"),
+    ("Jb", "RUR'F'RUR'U'R'FR2U'R'"),
+    ("Na", "RUR'URUR'F'RUR'U'R'FR2U'R'U2RU'R'"),
+    ("Nb", "R'URU'R'F'U'FRUR'FR'F'RU'R"),
+    ("Ra", "RU'R'U'RURDR'U'RD'R'U2R'"),
+    ("Rb", "R2FRURU'R'F'RU2R'U2R"),
+    ("T", "RUR'U'R'FR2U'R'U'RUR'F'"),
+    ("Ua", "M2UMU2M'UM2"),
+    ("Ub", "M2U'MU2M'U'M2"),
+    ("V", "RU'RUR'DRD'RU'DR2UR2D'R2"),
+    ("Y", "FRU'R'U'RUR'F'RUR'U'R'FRF'"),
+    ("Z", "M'UM2UM2UM'U2M2"),
+];
+
+/// Lookup tables for recognizing last layer cases
+struct LastLayerTables {
+    /// The OLL case for each pattern of U stickers (see `oll_pattern`)
+    oll: HashMap<u32, u8>,
+    /// The PLL case for each cube with the first two layers solved and the
+    /// last layer oriented
+    pll: HashMap<Cube, &'static str>,
+}
+
+impl LastLayerTables {
+    fn new() -> LastLayerTables {
+        let mut oll = HashMap::new();
+        for (i, alg) in OLL_ALGORITHMS.iter().enumerate() {
+            // The pattern can be seen from any side
+            let mut case = Cube::new().with_moves_inverted(alg);
+            for _ in 0..4 {
+                oll.insert(oll_pattern(&case), i as u8 + 1);
+                case.apply_move(Move::U);
             }
         }
-        print!("\r");
-        for y in 0..3 {
-            for &face in &faces {
-                for x in 0..3 {
-                    print!("{:?}", face[x+y*3]);
+
+        let mut pll = HashMap::new();
+        for &(name, alg) in PLL_ALGORITHMS.iter() {
+            // The case can be seen from any side, and the last layer doesn't
+            // have to line up with the rest of the cube once it's solved
+            let case = Cube::new().with_moves_inverted(alg);
+            let mut before = Cube::new();
+            for _ in 0..4 {
+                let mut after = before.compose(&case);
+                for _ in 0..4 {
+                    pll.insert(after, name);
+                    after.apply_move(Move::U);
                 }
+                before.apply_move(Move::U);
             }
-            print!("\n");
         }
-        
-        print!("   ");
-        for i in 0..9 {
-            print!("{:?}", faces[5][i]);
 
-            if i > 0 && (i+1) % 3 == 0 {
-                print!("\n   ");
-            }
+        LastLayerTables {
+            oll,
+            pll,
         }
-        print!("\r");
     }
+}
 
-    /// Get the orientation of a corner cubicle
-    fn get_corner_orientation(&self, c: Corner) -> u8 {
-        use self::Corner::*;
-        match c {
-            UFL => self.x.0,
-            URF => self.x.1,
-            UBR => self.x.2,
-            ULB => self.x.3,
-            DBL => self.x.4,
-            DLF => self.x.5,
-            DFR => self.x.6,
-            DRB => self.x.7,
+/// Get the last layer lookup tables, building them the first time
+fn last_layer_tables() -> &'static LastLayerTables {
+    static TABLES: OnceLock<LastLayerTables> = OnceLock::new();
+    TABLES.get_or_init(LastLayerTables::new)
+}
+
+/// Get a bit mask of the last layer stickers that are U colored: the U face
+/// followed by the top rows of F, R, B, and L
+fn oll_pattern(cube: &Cube) -> u32 {
+    let top = cube.get_face(Face::U);
+    let sides = [Face::F, Face::R, Face::B, Face::L].iter().flat_map(|&f| {
+        let stickers = cube.get_face(f);
+        vec![stickers[0], stickers[1], stickers[2]]
+    }).collect::<Vec<Face>>();
+
+    top.iter().chain(sides.iter()).enumerate().fold(
+        0,
+        |mask, (i, &f)| if f == Face::U { mask | 1 << i } else { mask }
+    )
+}
+
+/// The most scrambles `generate_curriculum` will try for each bucket
+pub const MAX_CURRICULUM_ATTEMPTS: usize = 10_000;
+
+/// Generate a batch of scrambles with a given mix of difficulties. The spec
+/// lists how many scrambles of each difficulty to make; scrambles of random
+/// length (up to 20 quarter turns) are generated and classified until each
+/// bucket is full. A bucket gives up after `MAX_CURRICULUM_ATTEMPTS` tries, so
+/// it may have fewer scrambles than requested.
+///
+/// Returns the scrambled cubes and the moves that produced them, grouped in
+/// the same order as the spec.
+///
+/// # Example
+/// ```
+/// extern crate rand;
+/// extern crate rubik;
+///
+/// use rand::{SeedableRng, XorShiftRng};
+/// use rubik::cube::*;
+///
+/// # fn main() {
+/// let spec = [(Difficulty::Easy, 3), (Difficulty::Hard, 2)];
+/// let batch = generate_curriculum(&spec, &mut XorShiftRng::from_seed([1, 2, 3, 4]));
+/// assert_eq!(batch.len(), 5);
+/// assert_eq!(batch[0].0.difficulty(), Some(Difficulty::Easy));
+/// assert_eq!(batch[4].0.difficulty(), Some(Difficulty::Hard));
+/// # }
+/// ```
+pub fn generate_curriculum<R: Rng>(spec: &[(Difficulty, usize)], rng: &mut R) -> Vec<(Cube, Vec<Move>)> {
+    let mut batch = Vec::new();
+    for &(difficulty, count) in spec {
+        let mut found = 0;
+        let mut attempts = 0;
+        while found < count && attempts < MAX_CURRICULUM_ATTEMPTS {
+            attempts += 1;
+
+            let mut cube = Cube::new();
+            let length = rng.gen_range(1, 21);
+            let moves: Vec<Move> = (0..length).map(|_| *rng.choose(&QUARTER_TURNS).unwrap()).collect();
+            for &m in &moves {
+                cube.apply_move(m);
+            }
+
+            if cube.difficulty() == Some(difficulty) {
+                batch.push((cube, moves));
+                found += 1;
+            }
         }
     }
 
-    /// Get the orientation of an edge cubicle
-    fn get_edge_orientation(&self, e: Edge) -> u8 {
-        use self::Edge::*;
-        match e {
-            UB => self.y.0,
-            UR => self.y.1,
-            UF => self.y.2,
-            UL => self.y.3,
-            LB => self.y.4,
-            RB => self.y.5,
-            RF => self.y.6,
-            LF => self.y.7,
-            DB => self.y.8,
-            DR => self.y.9,
-            DF => self.y.10,
-            DL => self.y.11,
+    batch
+}
+
+/// The largest distance `states_at_distance` will search
+pub const MAX_BFS_DISTANCE: u8 = 5;
+
+/// Find every cube state that is exactly `n` quarter turns away from solved,
+/// i.e. the states that can be reached in `n` quarter turns but not fewer.
+///
+/// The states are found with a breadth-first search that stores every state
+/// closer than `n` along the way, and the number of states grows by roughly a
+/// factor of nine with each move. Distances greater than `MAX_BFS_DISTANCE`
+/// (93,840 states) return `CubeError::TooDeep`.
+///
+/// # Example
+/// ```
+/// use rubik::cube::*;
+///
+/// assert_eq!(states_at_distance(1).unwrap().count(), 12);
+/// assert!(states_at_distance(MAX_BFS_DISTANCE + 1).is_err());
+/// ```
+pub fn states_at_distance(n: u8) -> Result<impl Iterator<Item = Cube>, CubeError> {
+    if n > MAX_BFS_DISTANCE {
+        return Err(CubeError::TooDeep);
+    }
+
+    let mut seen: HashSet<u128> = HashSet::new();
+    let mut frontier = vec![Cube::new()];
+    seen.insert(0);
+
+    for _ in 0..n {
+        let mut next = Vec::new();
+        for cube in &frontier {
+            for &m in &QUARTER_TURNS {
+                let mut c = *cube;
+                c.apply_move(m);
+                if seen.insert(c.rank()) {
+                    next.push(c);
+                }
+            }
         }
+        frontier = next;
     }
+
+    Ok(frontier.into_iter())
 }
 
-/// Perform a [superflip](https://en.wikipedia.org/wiki/Superflip) on a cube
+/// Print two cubes side by side with a caption above each one. Both nets are
+/// laid out the same way as `Cube::print`.
 ///
 /// # Example
 /// ```
-/// use rubik::cube::{Cube, superflip};
+/// use rubik::cube::*;
 ///
-/// let c = Cube::new();
-/// let superc = superflip(&c);
+/// let mut c = Cube::new();
+/// c.apply_moves("RU");
+/// print_pair(&c, &Cube::new(), ("your cube", "goal"));
+/// ```
+pub fn print_pair(a: &Cube, b: &Cube, labels: (&str, &str)) {
+    let stdout = io::stdout();
+    write_pair(&mut stdout.lock(), a, b, labels).unwrap();
+}
+
+/// Write two cubes side by side with a caption above each one. See
+/// `print_pair`.
 ///
-/// assert!(c.is_solved());
-/// assert!(!superc.is_solved());
+/// # Example
 /// ```
-pub fn superflip(cube: &Cube) -> Cube {
-    let mut c = cube.clone();
-    c.apply_moves("UR2FBRB2RU2LB2RU'D'R2FR'LB2U2F2");
-    c
+/// use rubik::cube::*;
+///
+/// let mut out = Vec::new();
+/// write_pair(&mut out, &Cube::new(), &Cube::new(), ("before", "after")).unwrap();
+/// assert!(String::from_utf8(out).unwrap().starts_with("before"));
+/// ```
+pub fn write_pair<W: io::Write>(w: &mut W, a: &Cube, b: &Cube, labels: (&str, &str)) -> io::Result<()> {
+    // Leave room for the left caption if it's wider than the net
+    let width = labels.0.chars().count().max(12) + 4;
+
+    writeln!(w, "{:width$}{}", labels.0, labels.1, width = width)?;
+    for (left, right) in a.net_lines().iter().zip(b.net_lines()) {
+        writeln!(w, "{:width$}{}", left, right.trim_end(), width = width)?;
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -0,0 +1,138 @@
+// This file is part of Rubik.
+// Copyright Peter Beard, licensed under the GPLv3. See LICENSE for details.
+//
+//! A 2x2x2 pocket cube. A pocket cube is just the corners of a Rubik's cube,
+//! so it's tracked with the same corner permutation and orientation state.
+use super::{add_x, corner_orientation_change, face_corners, get_corner_face, parse_moves, swap_x};
+use super::{CornerPermutation, Face, Move, MoveParseError, FACES, X};
+
+/// A 2x2x2 cube. Every face turn moves the corners the same way it does on a
+/// Rubik's cube. There are no centers, so turning L is the same as turning R
+/// and then rotating the whole cube; here the corners that don't move are
+/// the ones that stay put, so no rotation is needed. Slice moves don't turn
+/// any corners and have no effect.
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct PocketCube {
+    sigma: CornerPermutation,
+    x: X,
+}
+
+impl PocketCube {
+    /// Create a new pocket cube in the solved state
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::pocket::PocketCube;
+    ///
+    /// assert!(PocketCube::new().is_solved());
+    /// ```
+    pub fn new() -> PocketCube {
+        PocketCube::default()
+    }
+
+    /// Apply a move to the cube
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::Move;
+    /// use rubik::cube::pocket::PocketCube;
+    ///
+    /// let mut cube = PocketCube::new();
+    /// cube.apply_move(Move::F);
+    /// assert!(!cube.is_solved());
+    /// cube.apply_move(Move::FPrime);
+    /// assert!(cube.is_solved());
+    /// ```
+    pub fn apply_move(&mut self, m: Move) {
+        self.sigma.permute(m);
+
+        let (swap_indices, addends) = corner_orientation_change(m);
+        self.x = swap_x(self.x, &swap_indices);
+        self.x = add_x(self.x, &addends);
+    }
+
+    /// Apply a string of moves to the cube. Moves are written the same way as
+    /// for `Cube::apply_moves`.
+    ///
+    /// # Panics
+    /// This function will panic if it encounters an invalid character.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::pocket::PocketCube;
+    ///
+    /// let mut cube = PocketCube::new();
+    /// cube.apply_moves("RUR'U'");
+    /// assert!(!cube.is_solved());
+    /// cube.apply_moves("URU'R'");
+    /// assert!(cube.is_solved());
+    /// ```
+    pub fn apply_moves(&mut self, moves: &str) {
+        if let Err(e) = self.try_apply_moves(moves) {
+            panic!("{}", e);
+        }
+    }
+
+    /// Apply a string of moves to the cube, returning an error instead of
+    /// panicking if the string contains an invalid move. The cube is unchanged
+    /// if there is an error.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::pocket::PocketCube;
+    ///
+    /// let mut cube = PocketCube::new();
+    /// assert!(cube.try_apply_moves("FRX").is_err());
+    /// assert!(cube.is_solved());
+    /// ```
+    pub fn try_apply_moves(&mut self, moves: &str) -> Result<(), MoveParseError> {
+        for m in parse_moves(moves)? {
+            self.apply_move(m);
+        }
+
+        Ok(())
+    }
+
+    /// Determine whether the cube is in the solved state. There are no
+    /// centers to say which way up the cube is, so it's solved whenever every
+    /// face is a single color, even if the whole cube has been rotated.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::pocket::PocketCube;
+    ///
+    /// let mut cube = PocketCube::new();
+    /// cube.apply_moves("RL'");
+    /// assert!(cube.is_solved());
+    /// ```
+    pub fn is_solved(&self) -> bool {
+        FACES.iter().all(|&face| {
+            let stickers = self.get_face(face);
+            stickers.iter().all(|&s| s == stickers[0])
+        })
+    }
+
+    /// Get the stickers on one face of the cube, row by row from the top left.
+    /// Faces are seen the same way as in `Cube::get_face`.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::Face;
+    /// use rubik::cube::pocket::PocketCube;
+    ///
+    /// let mut cube = PocketCube::new();
+    /// assert_eq!(cube.get_face(Face::U), [Face::U; 4]);
+    ///
+    /// cube.apply_moves("R");
+    /// assert_eq!(cube.get_face(Face::U), [Face::U, Face::F, Face::U, Face::F]);
+    /// ```
+    pub fn get_face(&self, face: Face) -> [Face; 4] {
+        let orientations = self.x.to_array();
+        let stickers: Vec<Face> = face_corners(face).iter().map(
+            |&c| get_corner_face(c, self.sigma.get(c), face, orientations[c as usize])
+        ).collect();
+
+        // The corners are listed clockwise from top left
+        [stickers[0], stickers[1], stickers[3], stickers[2]]
+    }
+}
@@ -254,3 +254,2799 @@ fn test_moved_faces() {
     assert_eq!(faces[4], [R,L,F,R,L,B,B,D,D]);
     assert_eq!(faces[5], [R,B,F,D,B,F,D,R,R]);
 }
+
+#[test]
+fn test_same_permutation_ignores_orientation() {
+    let c = Cube::new();
+    assert!(superflip(&c).same_permutation(&c));
+}
+
+#[test]
+fn test_same_permutation_detects_moved_cubies() {
+    let mut c = Cube::new();
+    let mut d = Cube::new();
+    c.apply_moves("RU");
+    d.apply_moves("RU");
+    assert!(c.same_permutation(&d));
+
+    d.apply_moves("F");
+    assert!(!c.same_permutation(&d));
+}
+
+#[test]
+fn test_rank_solved() {
+    assert_eq!(Cube::new().rank(), 0);
+    assert_eq!(Cube::unrank(0), Some(Cube::new()));
+}
+
+#[test]
+fn test_rank_round_trip() {
+    use rand::{SeedableRng, XorShiftRng};
+
+    let mut rng = XorShiftRng::from_seed([203, 1, 2, 3]);
+    let mut cube = Cube::new();
+    for _ in 0..20 {
+        cube.scramble_with_rng(&mut rng, 5);
+        let rank = cube.rank();
+        assert!(rank < STATE_COUNT);
+        assert_eq!(Cube::unrank(rank), Some(cube));
+    }
+}
+
+#[test]
+fn test_rank_distinguishes_states() {
+    let c = Cube::new();
+    let mut d = Cube::new();
+    d.apply_moves("U");
+    assert_ne!(c.rank(), d.rank());
+    assert_ne!(superflip(&c).rank(), c.rank());
+}
+
+#[test]
+fn test_unrank_out_of_range() {
+    assert_eq!(Cube::unrank(STATE_COUNT), None);
+    assert_eq!(Cube::unrank(u128::MAX), None);
+}
+
+#[test]
+fn test_unrank_produces_legal_states() {
+    for &n in &[1, 12_345_678_901_234_567, STATE_COUNT / 3, STATE_COUNT - 1] {
+        let cube = Cube::unrank(n).unwrap();
+        let corners = cube.sigma.to_array();
+        let edges = cube.tau.to_array();
+        assert_eq!(permutation_parity(&corners), permutation_parity(&edges));
+        assert_eq!(cube.x.to_array().iter().sum::<u8>() % 3, 0);
+        assert_eq!(cube.y.to_array().iter().sum::<u8>() % 2, 0);
+        assert_eq!(cube.rank(), n);
+    }
+}
+
+#[test]
+fn test_write_pair() {
+    let mut c = Cube::new();
+    c.apply_moves("U");
+    let mut out = Vec::new();
+    write_pair(&mut out, &c, &Cube::new(), ("scramble", "goal")).unwrap();
+
+    let expected = "\
+scramble        goal
+   UUU             UUU
+   UUU             UUU
+   UUU             UUU
+FFFRRRBBBLLL    LLLFFFRRRBBB
+LLLFFFRRRBBB    LLLFFFRRRBBB
+LLLFFFRRRBBB    LLLFFFRRRBBB
+   DDD             DDD
+   DDD             DDD
+   DDD             DDD
+";
+    assert_eq!(String::from_utf8(out).unwrap(), expected);
+}
+
+#[test]
+fn test_write_pair_long_label() {
+    let mut out = Vec::new();
+    write_pair(&mut out, &Cube::new(), &Cube::new(), ("a very long caption", "b")).unwrap();
+
+    let out = String::from_utf8(out).unwrap();
+    let lines: Vec<_> = out.lines().collect();
+    assert_eq!(lines[0], "a very long caption    b");
+    assert_eq!(lines[1], "   UUU                    UUU");
+}
+
+#[test]
+fn test_rotation_opposite_faces_turn_opposite_ways() {
+    // Turning opposite faces clockwise rotates them in opposite directions
+    let pairs = [(Move::F, Move::B), (Move::R, Move::L), (Move::U, Move::D)];
+    for &(a, b) in &pairs {
+        let (axis_a, layer_a, turns_a) = a.rotation();
+        let (axis_b, layer_b, turns_b) = b.rotation();
+        assert_eq!(axis_a, axis_b);
+        assert_eq!(layer_a, Layer::Positive);
+        assert_eq!(layer_b, Layer::Negative);
+        assert_eq!(turns_a, -turns_b);
+    }
+}
+
+#[test]
+fn test_rotation_prime_reverses_direction() {
+    let pairs = [
+        (Move::F, Move::FPrime),
+        (Move::R, Move::RPrime),
+        (Move::U, Move::UPrime),
+        (Move::B, Move::BPrime),
+        (Move::L, Move::LPrime),
+        (Move::D, Move::DPrime),
+    ];
+    for &(m, prime) in &pairs {
+        let (axis, layer, turns) = m.rotation();
+        assert_eq!(prime.rotation(), (axis, layer, -turns));
+    }
+}
+
+#[test]
+fn test_try_apply_moves_empty() {
+    let mut cube = Cube::new();
+    assert_eq!(cube.try_apply_moves(""), Ok(()));
+    assert_eq!(cube.try_apply_moves(" \t\n"), Ok(()));
+    assert!(cube.is_solved());
+}
+
+#[test]
+fn test_try_apply_moves_modifiers_only() {
+    let mut cube = Cube::new();
+    assert_eq!(cube.try_apply_moves("'"), Err(MoveParseError { character: '\'', offset: 0 }));
+    assert_eq!(cube.try_apply_moves("2"), Err(MoveParseError { character: '2', offset: 0 }));
+    assert_eq!(cube.try_apply_moves("''"), Err(MoveParseError { character: '\'', offset: 0 }));
+}
+
+#[test]
+fn test_try_apply_moves_invalid_is_atomic() {
+    let mut cube = Cube::new();
+    assert_eq!(cube.try_apply_moves("FRXU"), Err(MoveParseError { character: 'X', offset: 2 }));
+    assert!(cube.is_solved());
+}
+
+#[test]
+fn test_try_apply_moves_matches_apply_moves() {
+    let mut c = Cube::new();
+    let mut d = Cube::new();
+    c.apply_moves("R2U'FLB2");
+    d.try_apply_moves("R2U'FLB2").unwrap();
+    assert_eq!(c, d);
+}
+
+#[test]
+#[should_panic]
+fn test_apply_moves_invalid_panics() {
+    Cube::new().apply_moves("FRXU");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serialize_solved() {
+    let json = serde_json::to_string(&Cube::new()).unwrap();
+    assert_eq!(json, "{\"corner_permutation\":[0,1,2,3,4,5,6,7],\
+                      \"corner_orientation\":[0,0,0,0,0,0,0,0],\
+                      \"edge_permutation\":[0,1,2,3,4,5,6,7,8,9,10,11],\
+                      \"edge_orientation\":[0,0,0,0,0,0,0,0,0,0,0,0]}");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serialize_round_trip() {
+    let mut cube = Cube::new();
+    cube.apply_moves("R2U'FLB2");
+    let json = serde_json::to_string(&cube).unwrap();
+    let parsed: Cube = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, cube);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_deserialize_rejects_invalid_states() {
+    use std::convert::TryFrom;
+
+    let solved = CubeState::from(Cube::new());
+
+    let mut state = solved.clone();
+    state.corner_permutation[0] = 1;
+    assert_eq!(Cube::try_from(state), Err(CubeError::InvalidPermutation));
+
+    let mut state = solved.clone();
+    state.edge_orientation[0] = 2;
+    assert_eq!(Cube::try_from(state), Err(CubeError::InvalidOrientation));
+
+    let mut state = solved.clone();
+    state.corner_orientation[0] = 1;
+    assert_eq!(Cube::try_from(state), Err(CubeError::Illegal));
+
+    let mut state = solved.clone();
+    state.edge_permutation.swap(0, 1);
+    assert_eq!(Cube::try_from(state), Err(CubeError::Illegal));
+
+    let json = "{\"corner_permutation\":[0,1,2,3,4,5,6,7],\
+                \"corner_orientation\":[0,0,0,0,0,0,0,0],\
+                \"edge_permutation\":[0,1,2,3,4,5,6,7,8,9,10,11],\
+                \"edge_orientation\":[1,0,0,0,0,0,0,0,0,0,0,0]}";
+    assert!(serde_json::from_str::<Cube>(json).is_err());
+}
+
+#[test]
+fn test_solve_with_default_config() {
+    use solver::SolverConfig;
+
+    let mut cube = Cube::new();
+    cube.apply_moves("FR'");
+    assert_eq!(cube.solve_with(&SolverConfig::default()), Ok(vec![Move::R, Move::FPrime]));
+    assert!(cube.is_solved());
+}
+
+#[test]
+fn test_solve_with_solved_cube() {
+    use solver::{Method, SolverConfig};
+
+    let config = SolverConfig { method: Method::Null, ..SolverConfig::default() };
+    assert_eq!(Cube::new().solve_with(&config), Ok(vec![]));
+}
+
+#[test]
+fn test_solve_with_null_method() {
+    use solver::{Method, SolveError, SolverConfig};
+
+    let mut cube = Cube::new();
+    cube.apply_moves("F");
+    let config = SolverConfig { method: Method::Null, ..SolverConfig::default() };
+    assert_eq!(cube.solve_with(&config), Err(SolveError::NotFound));
+    assert!(!cube.is_solved());
+}
+
+#[test]
+fn test_solve_with_move_set() {
+    use solver::{SolveError, SolverConfig};
+
+    let mut cube = Cube::new();
+    cube.apply_moves("UU");
+    let config = SolverConfig {
+        max_depth: 3,
+        move_set: Some(vec![Move::UPrime]),
+        ..SolverConfig::default()
+    };
+    assert_eq!(cube.solve_with(&config), Ok(vec![Move::UPrime, Move::UPrime]));
+
+    cube.apply_moves("R");
+    assert_eq!(cube.solve_with(&config), Err(SolveError::NotFound));
+}
+
+#[test]
+fn test_solve_with_metric() {
+    use solver::SolverConfig;
+
+    let config = SolverConfig { metric: Metric::QuarterTurn, max_depth: 26, ..SolverConfig::default() };
+    let mut cube = Cube::new();
+    cube.apply_moves("R2");
+    assert_eq!(cube.solve_with(&config), Ok(vec![Move::R, Move::R]));
+
+    cube.apply_moves("R2");
+    assert_eq!(cube.solve_with(&SolverConfig::default()), Ok(vec![Move::R2]));
+}
+
+#[test]
+fn test_solve_with_methods() {
+    use solver::{Method, SolverConfig};
+
+    for &method in &[Method::IDAStar, Method::TwoPhase] {
+        let mut cube = Cube::new();
+        cube.apply_moves("FRU");
+        let config = SolverConfig { method, max_depth: 3, ..SolverConfig::default() };
+        assert_eq!(cube.solve_with(&config).unwrap().len(), 3, "{:?}", method);
+        assert!(cube.is_solved());
+    }
+}
+
+#[test]
+fn test_solve_with_unsupported_settings() {
+    use std::time::Duration;
+    use solver::{Method, SolveError, SolverConfig};
+
+    let mut cube = Cube::new();
+    cube.apply_moves("FR");
+    for &method in &[Method::IDAStar, Method::TwoPhase] {
+        let timeout = SolverConfig { method, timeout: Some(Duration::from_secs(1)), ..SolverConfig::default() };
+        let move_set = SolverConfig { method, move_set: Some(vec![Move::F]), ..SolverConfig::default() };
+        let budget = SolverConfig { method, node_budget: Some(1000), ..SolverConfig::default() };
+        for config in &[timeout, move_set, budget] {
+            assert_eq!(cube.solve_with(config), Err(SolveError::Unsupported), "{:?}", method);
+            assert!(Cube::new().solve_with(config).is_err());
+        }
+    }
+    assert!(!cube.is_solved());
+}
+
+#[test]
+fn test_solve_with_default_matches_idsolver() {
+    use solver::{IDSolver, SolverConfig, Solver};
+
+    let mut cube = Cube::new();
+    cube.apply_moves("FR2U'");
+    assert_eq!(SolverConfig::default().find_solution(&cube), Ok(IDSolver::new().find_solution(&cube)));
+}
+
+#[test]
+fn test_solve_with_timeout() {
+    use std::time::Duration;
+    use solver::{SolveError, SolverConfig};
+
+    let mut cube = Cube::new();
+    cube.apply_moves("FRUBLD");
+    let config = SolverConfig {
+        timeout: Some(Duration::from_secs(0)),
+        ..SolverConfig::default()
+    };
+    assert_eq!(cube.solve_with(&config), Err(SolveError::TimedOut));
+}
+
+#[test]
+fn test_solve_with_node_budget() {
+    use solver::{SolveError, SolverConfig};
+
+    let mut cube = Cube::new();
+    cube.apply_moves("FRUBLD");
+    let config = SolverConfig {
+        node_budget: Some(1000),
+        ..SolverConfig::default()
+    };
+    assert_eq!(cube.solve_with(&config), Err(SolveError::BudgetExceeded));
+
+    let mut easy = Cube::new();
+    easy.apply_moves("FR");
+    assert_eq!(easy.solve_with(&config), Ok(vec![Move::RPrime, Move::FPrime]));
+}
+
+#[test]
+fn test_best_next_move_solved() {
+    use solver::IDSolver;
+
+    assert_eq!(Cube::new().best_next_move(&mut IDSolver::new()), None);
+}
+
+#[test]
+fn test_best_next_move_reduces_distance() {
+    use solver::IDSolver;
+
+    let mut cube = Cube::new();
+    cube.apply_moves("RUF");
+    let m = cube.best_next_move(&mut IDSolver::new()).unwrap();
+    cube.apply_move(m);
+    assert_eq!(cube.solve(&mut IDSolver::new()).len(), 2);
+}
+
+#[test]
+fn test_best_next_move_no_solution() {
+    use solver::NullSolver;
+
+    let mut cube = Cube::new();
+    cube.apply_moves("R");
+    assert_eq!(cube.best_next_move(&mut NullSolver::new()), None);
+}
+
+#[test]
+fn test_diagnose_valid() {
+    let mut cube = Cube::new();
+    assert!(cube.is_valid());
+    cube.apply_moves("R2U'FLB2");
+    assert!(cube.is_valid());
+    assert!(superflip(&cube).is_valid());
+}
+
+#[test]
+fn test_diagnose_corner_twist() {
+    let mut cube = Cube::new();
+    cube.x = X::from_array(&[1, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(cube.diagnose(), vec![Illegality::CornerTwist]);
+    assert!(!cube.is_valid());
+}
+
+#[test]
+fn test_diagnose_edge_flip() {
+    let mut cube = Cube::new();
+    cube.y = Y::from_array(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+    assert_eq!(cube.diagnose(), vec![Illegality::EdgeFlip]);
+}
+
+#[test]
+fn test_diagnose_parity() {
+    let mut cube = Cube::new();
+    cube.tau = EdgePermutation::from_array(&[1, 0, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]);
+    assert!(cube.has_odd_permutation());
+    assert_eq!(cube.diagnose(), vec![
+        Illegality::PermutationParity,
+        Illegality::SwappedEdges([Face::U, Face::B], [Face::U, Face::R]),
+    ]);
+}
+
+#[test]
+fn test_diagnose_swapped_corners() {
+    let mut cube = Cube::new();
+    cube.sigma = CornerPermutation::from_array(&[0, 1, 2, 3, 4, 6, 5, 7]);
+    assert!(cube.has_odd_permutation());
+    assert_eq!(cube.diagnose(), vec![
+        Illegality::PermutationParity,
+        Illegality::SwappedCorners([Face::D, Face::L, Face::F], [Face::D, Face::F, Face::R]),
+    ]);
+}
+
+#[test]
+fn test_diagnose_parity_without_pair() {
+    // A scrambled cube with a swap can't be traced back to a single pair
+    let mut cube = Cube::new();
+    cube.apply_moves("FRUBL");
+    let mut edges = cube.tau.to_array();
+    edges.swap(0, 1);
+    cube.tau = EdgePermutation::from_array(&edges);
+    assert_eq!(cube.diagnose(), vec![Illegality::PermutationParity]);
+
+    let mut duplicate = Cube::new();
+    duplicate.tau = EdgePermutation::from_array(&[0, 0, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]);
+    assert!(!duplicate.has_odd_permutation());
+}
+
+#[test]
+fn test_diagnose_duplicate_corner() {
+    // Replace the URF corner with a second copy of UFL
+    let mut cube = Cube::new();
+    cube.sigma = CornerPermutation::from_array(&[0, 0, 2, 3, 4, 5, 6, 7]);
+    assert_eq!(cube.diagnose(), vec![Illegality::DuplicateCorner, Illegality::MissingColor(Face::R)]);
+}
+
+#[test]
+fn test_diagnose_multiple_problems() {
+    let mut cube = Cube::new();
+    cube.x = X::from_array(&[2, 0, 0, 0, 0, 0, 0, 0]);
+    cube.y = Y::from_array(&[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(cube.diagnose(), vec![Illegality::CornerTwist, Illegality::EdgeFlip]);
+}
+
+#[test]
+fn test_apply_moves_streaming_matches_apply_moves() {
+    let moves = "UR2FBRB2RU2LB2RU'D'R2FR'LB2U2F2";
+    let mut c = Cube::new();
+    let mut d = Cube::new();
+    c.apply_moves(moves);
+    d.apply_moves_streaming(moves).unwrap();
+    assert_eq!(c, d);
+}
+
+#[test]
+fn test_apply_moves_streaming_invalid_is_atomic() {
+    let mut cube = Cube::new();
+    assert_eq!(cube.apply_moves_streaming("FRU2'"), Err(MoveParseError { character: '\'', offset: 4 }));
+    assert!(cube.is_solved());
+    assert_eq!(cube.apply_moves_streaming("F'2"), Err(MoveParseError { character: '2', offset: 2 }));
+    assert!(cube.is_solved());
+}
+
+#[test]
+fn test_apply_moves_streaming_empty() {
+    let mut cube = Cube::new();
+    assert_eq!(cube.apply_moves_streaming("  "), Ok(()));
+    assert!(cube.is_solved());
+}
+
+#[test]
+fn test_reid_solved() {
+    let reid = "UF UR UB UL DF DR DB DL FR FL BR BL UFR URB UBL ULF DRF DFL DLB DBR";
+    assert_eq!(Cube::new().to_reid(), reid);
+    assert_eq!(Cube::from_reid(reid), Ok(Cube::new()));
+}
+
+#[test]
+fn test_reid_move_f() {
+    let mut cube = Cube::new();
+    cube.apply_moves("F");
+    assert_eq!(cube.to_reid(), "LF UR UB UL RF DR DB DL FU FD BR BL LFU URB UBL LDF RUF RFD DLB DBR");
+}
+
+#[test]
+fn test_reid_round_trip() {
+    let mut cube = Cube::new();
+    for _ in 0..10 {
+        cube.scramble(10);
+        assert_eq!(Cube::from_reid(&cube.to_reid()), Ok(cube));
+    }
+    let sf = superflip(&Cube::new());
+    assert_eq!(Cube::from_reid(&sf.to_reid()), Ok(sf));
+}
+
+#[test]
+fn test_reid_errors() {
+    assert_eq!(Cube::from_reid("UF UR"), Err(CubeError::InvalidFormat));
+    assert_eq!(Cube::from_reid("UF UR UB UL DF DR DB DL FR FL BR BL UFR URB UBL ULF DRF DFL DLB DBRX"),
+               Err(CubeError::InvalidFormat));
+    assert_eq!(Cube::from_reid("UF UR UB UL DF DR DB DL FR FL BR BX UFR URB UBL ULF DRF DFL DLB DBR"),
+               Err(CubeError::InvalidCharacter('X')));
+    assert_eq!(Cube::from_reid("UD UR UB UL DF DR DB DL FR FL BR BL UFR URB UBL ULF DRF DFL DLB DBR"),
+               Err(CubeError::InvalidCubie));
+    // Two edges swapped
+    assert_eq!(Cube::from_reid("UR UF UB UL DF DR DB DL FR FL BR BL UFR URB UBL ULF DRF DFL DLB DBR"),
+               Err(CubeError::Illegal));
+    // The same edge twice
+    assert_eq!(Cube::from_reid("UF UF UB UL DF DR DB DL FR FL BR BL UFR URB UBL ULF DRF DFL DLB DBR"),
+               Err(CubeError::InvalidPermutation));
+    // One edge flipped
+    assert_eq!(Cube::from_reid("FU UR UB UL DF DR DB DL FR FL BR BL UFR URB UBL ULF DRF DFL DLB DBR"),
+               Err(CubeError::Illegal));
+}
+
+#[test]
+fn test_compose_identity() {
+    let mut cube = Cube::new();
+    cube.apply_moves("R2U'FLB2");
+    assert_eq!(cube.compose(&Cube::new()), cube);
+    assert_eq!(Cube::new().compose(&cube), cube);
+}
+
+#[test]
+fn test_compose_matches_move_sequence() {
+    let mut a = Cube::new();
+    let mut b = Cube::new();
+    let mut ab = Cube::new();
+    a.apply_moves("UR2FBRB2RU2LB2");
+    b.apply_moves("RU'D'R2FR'LB2U2F2");
+    ab.apply_moves("UR2FBRB2RU2LB2RU'D'R2FR'LB2U2F2");
+    assert_eq!(a.compose(&b), ab);
+}
+
+#[test]
+fn test_compose_with_inverse_is_solved() {
+    let mut cube = Cube::new();
+    cube.apply_moves("FRUBLDR'");
+    assert!(cube.compose(&cube.inverse()).is_solved());
+    assert!(cube.inverse().compose(&cube).is_solved());
+}
+
+#[test]
+fn test_moves_to_reaches_target() {
+    use solver::IDSolver;
+
+    let mut intended = Cube::new();
+    let mut actual = Cube::new();
+    intended.apply_moves("FRU");
+    actual.apply_moves("FRD");
+
+    let fix = actual.moves_to(&intended, &mut IDSolver::new());
+    assert_eq!(fix.len(), 2);
+    for m in fix {
+        actual.apply_move(m);
+    }
+    assert_eq!(actual, intended);
+}
+
+#[test]
+fn test_moves_to_same_state() {
+    use solver::IDSolver;
+
+    let mut cube = Cube::new();
+    cube.apply_moves("FRU");
+    assert_eq!(cube.moves_to(&cube, &mut IDSolver::new()), vec![]);
+}
+
+#[test]
+fn test_states_at_distance_counts() {
+    // Known number of positions at each distance in the quarter turn metric
+    let counts = [1, 12, 114, 1068];
+    for (n, &count) in counts.iter().enumerate() {
+        assert_eq!(states_at_distance(n as u8).unwrap().count(), count);
+    }
+}
+
+#[test]
+fn test_states_at_distance_are_unique() {
+    let states: Vec<_> = states_at_distance(2).unwrap().collect();
+    let ranks: HashSet<_> = states.iter().map(|c| c.rank()).collect();
+    assert_eq!(ranks.len(), states.len());
+    assert!(states.iter().all(|c| !c.is_solved()));
+}
+
+#[test]
+fn test_states_at_distance_too_deep() {
+    assert_eq!(states_at_distance(MAX_BFS_DISTANCE + 1).err(), Some(CubeError::TooDeep));
+}
+
+#[test]
+fn test_make_valid_legal_cube_unchanged() {
+    let mut cube = Cube::new();
+    cube.apply_moves("R2U'FLB2");
+    let before = cube;
+    cube.make_valid();
+    assert_eq!(cube, before);
+}
+
+#[test]
+fn test_make_valid_twist_and_flip() {
+    let mut cube = Cube::new();
+    cube.x = X::from_array(&[1, 0, 0, 0, 0, 0, 0, 0]);
+    cube.y = Y::from_array(&[0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    cube.make_valid();
+    assert!(cube.is_valid());
+    assert_eq!(cube.x.to_array(), [1, 0, 0, 0, 0, 0, 0, 2]);
+    assert_eq!(cube.y.to_array(), [0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+}
+
+#[test]
+fn test_make_valid_parity() {
+    let mut cube = Cube::new();
+    cube.tau = EdgePermutation::from_array(&[1, 0, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11]);
+    cube.make_valid();
+    assert!(cube.is_valid());
+    assert_eq!(cube.tau.to_array(), [1, 0, 2, 3, 4, 5, 6, 7, 8, 9, 11, 10]);
+    assert_eq!(cube.sigma, CornerPermutation::new());
+}
+
+#[test]
+fn test_inverse_scramble_view() {
+    let mut scrambled = Cube::new();
+    let mut inverse = Cube::new();
+    scrambled.apply_moves("R2U'FLB2");
+    inverse.apply_moves("B2L'F'UR2");
+    assert_eq!(scrambled.inverse_scramble_view(), inverse);
+    assert_eq!(inverse.inverse_scramble_view(), scrambled);
+}
+
+#[test]
+fn test_inverse_scramble_view_solution() {
+    use solver::IDSolver;
+
+    // Solving the inverse view and inverting the solution solves the original
+    let mut scrambled = Cube::new();
+    scrambled.apply_moves("FU'");
+    let mut view = scrambled.inverse_scramble_view();
+    let solution = view.solve(&mut IDSolver::new());
+    assert_eq!(solution, vec![Move::F, Move::UPrime]);
+}
+
+#[test]
+fn test_get_face_with_origin() {
+    use super::Face::*;
+
+    let mut cube = Cube::new();
+    cube.apply_moves("R2U'FLB2");
+    assert_eq!(cube.get_face_with_origin(U, Origin::TopLeft), cube.get_face(U));
+    assert_eq!(cube.get_face_with_origin(U, Origin::TopRight), [B,D,U,U,U,B,B,L,R]);
+    assert_eq!(cube.get_face_with_origin(U, Origin::BottomLeft), [R,L,B,B,U,U,U,D,B]);
+    assert_eq!(cube.get_face_with_origin(U, Origin::BottomRight), [B,L,R,U,U,B,B,D,U]);
+}
+
+#[test]
+fn test_pow_small() {
+    let mut cube = Cube::new();
+    cube.apply_moves("R2U'FLB2");
+    assert!(cube.pow(0).is_solved());
+    assert_eq!(cube.pow(1), cube);
+    assert_eq!(cube.pow(2), cube.compose(&cube));
+}
+
+#[test]
+fn test_pow_order() {
+    let mut cube = Cube::new();
+    cube.apply_moves("F");
+    assert!(cube.pow(4).is_solved());
+    assert_eq!(cube.pow(5), cube);
+
+    let mut sexy = Cube::new();
+    sexy.apply_moves("RUR'U'");
+    assert!(sexy.pow(6).is_solved());
+    assert!(!sexy.pow(3).is_solved());
+    assert!(sexy.pow(600).is_solved());
+}
+
+#[test]
+fn test_difficulty() {
+    let mut cube = Cube::new();
+    assert_eq!(cube.difficulty(), None);
+    // A single turn moves 8 pieces, the superflip flips 12, and two opposite
+    // turns move 16
+    cube.apply_moves("U");
+    assert_eq!(cube.difficulty(), Some(Difficulty::Easy));
+    assert_eq!(superflip(&Cube::new()).difficulty(), Some(Difficulty::Medium));
+    cube.apply_moves("D");
+    assert_eq!(cube.difficulty(), Some(Difficulty::Hard));
+}
+
+#[test]
+fn test_generate_curriculum() {
+    use rand::{SeedableRng, XorShiftRng};
+
+    let spec = [(Difficulty::Medium, 2), (Difficulty::Easy, 3), (Difficulty::Hard, 1)];
+    let batch = generate_curriculum(&spec, &mut XorShiftRng::from_seed([219, 1, 2, 3]));
+    let difficulties: Vec<_> = batch.iter().map(|(c, _)| c.difficulty().unwrap()).collect();
+    assert_eq!(difficulties, vec![
+        Difficulty::Medium, Difficulty::Medium,
+        Difficulty::Easy, Difficulty::Easy, Difficulty::Easy,
+        Difficulty::Hard,
+    ]);
+
+    for (cube, moves) in &batch {
+        let mut c = Cube::new();
+        for &m in moves {
+            c.apply_move(m);
+        }
+        assert_eq!(&c, cube);
+    }
+}
+
+#[test]
+fn test_move_observer() {
+    let mut applied = Vec::new();
+    let cube = {
+        let mut observed = Cube::new().with_move_observer(|m| applied.push(m));
+        observed.apply_moves("R2U'");
+        observed.apply_move(Move::F);
+        assert!(observed.try_apply_moves("FX").is_err());
+        observed.into_inner()
+    };
+
+    assert_eq!(applied, vec![Move::R2, Move::UPrime, Move::F]);
+    let mut expected = Cube::new();
+    expected.apply_moves("R2U'F");
+    assert_eq!(cube, expected);
+}
+
+#[test]
+fn test_move_observer_solve() {
+    use solver::IDSolver;
+
+    let mut scrambled = Cube::new();
+    scrambled.apply_moves("FU");
+
+    let mut applied = Vec::new();
+    let mut observed = scrambled.with_move_observer(|m| applied.push(m));
+    let solution = observed.solve(&mut IDSolver::new());
+    assert!(observed.cube().is_solved());
+    assert_eq!(applied, solution);
+}
+
+#[test]
+fn test_history_undo() {
+    let mut cube = Cube::new().with_history();
+    cube.apply_moves("R2U'FM(LD)2");
+    assert!(cube.try_apply_moves("BX").is_err());
+    assert_eq!(cube.history().len(), 8);
+
+    while cube.undo().is_some() {}
+    assert!(cube.cube().is_solved());
+    assert!(cube.history().is_empty());
+}
+
+#[test]
+fn test_history_starts_empty() {
+    let mut scrambled = Cube::new();
+    scrambled.apply_moves("FU");
+
+    let mut cube = scrambled.with_history();
+    assert_eq!(cube.undo(), None);
+    cube.apply_move(Move::D);
+    assert_eq!(cube.undo(), Some(Move::D));
+    assert_eq!(cube.into_inner(), scrambled);
+}
+
+#[test]
+fn test_is_inverse_of() {
+    let mut a = Cube::new();
+    let mut b = Cube::new();
+    a.apply_moves("R2U'FLB2");
+    b.apply_moves("B2L'F'UR2");
+    assert!(a.is_inverse_of(&b));
+    assert!(b.is_inverse_of(&a));
+
+    // Applying the same moves in the original order isn't an inverse
+    let mut c = Cube::new();
+    c.apply_moves("R2UF'L'B2");
+    assert!(!a.is_inverse_of(&c));
+    assert!(!c.is_inverse_of(&a));
+}
+
+#[test]
+fn test_is_inverse_of_self_inverse() {
+    let sf = superflip(&Cube::new());
+    assert!(sf.is_inverse_of(&sf));
+    assert!(Cube::new().is_inverse_of(&Cube::new()));
+}
+
+#[test]
+fn test_to_facelet_string() {
+    let mut cube = Cube::new();
+    cube.apply_moves("F");
+    assert_eq!(cube.to_facelet_string(),
+               "UUUUUULLLURRURRURRFFFFFFFFFRRRDDDDDDLLDLLDLLDBBBBBBBBB");
+}
+
+#[test]
+fn test_to_facelet_string_grouped() {
+    let mut cube = Cube::new();
+    cube.apply_moves("F");
+    assert_eq!(cube.to_facelet_string_grouped(' '),
+               "UUUUUULLL URRURRURR FFFFFFFFF RRRDDDDDD LLDLLDLLD BBBBBBBBB");
+    assert_eq!(cube.to_facelet_string_grouped('|').replace("|", ""), cube.to_facelet_string());
+}
+
+#[test]
+fn test_cubie_stickers_cover_every_sticker() {
+    let mut cube = Cube::new();
+    cube.apply_moves("R2U'FLB2");
+    let groups = cube.cubie_stickers();
+    assert_eq!(groups.len(), 26);
+    assert_eq!(groups.iter().filter(|g| g.len() == 3).count(), 8);
+    assert_eq!(groups.iter().filter(|g| g.len() == 2).count(), 12);
+    assert_eq!(groups.iter().filter(|g| g.len() == 1).count(), 6);
+
+    let all: HashSet<(Face, usize)> = groups.iter().flat_map(|g| g.iter().cloned()).collect();
+    assert_eq!(all.len(), 54);
+}
+
+#[test]
+fn test_cubie_stickers_match_colors() {
+    // Each sticker in a cubie's group shows the corresponding face of that cubie
+    let mut cube = Cube::new();
+    cube.apply_moves("R2U'FLB2");
+    let groups = cube.cubie_stickers();
+    for (group, &corner) in groups.iter().zip(CORNERS.iter()) {
+        let (a, b, c) = decompose_corner(corner);
+        let colors: Vec<_> = group.iter().map(|&(f, i)| cube.get_face(f)[i]).collect();
+        assert_eq!(colors, vec![a, b, c]);
+    }
+    for (group, &edge) in groups[8..].iter().zip(EDGES.iter()) {
+        let (a, b) = decompose_edge(edge);
+        let colors: Vec<_> = group.iter().map(|&(f, i)| cube.get_face(f)[i]).collect();
+        assert_eq!(colors, vec![a, b]);
+    }
+}
+
+#[test]
+fn test_solve_streaming_no_solution() {
+    use solver::NullSolver;
+
+    let mut cube = Cube::new();
+    cube.apply_moves("F");
+    let mut calls = 0;
+    assert_eq!(cube.solve_streaming(&mut NullSolver::new(), |_| calls += 1), vec![]);
+    assert_eq!(calls, 0);
+}
+
+#[test]
+fn test_solve_streaming_solved_cube() {
+    use solver::IDSolver;
+
+    let mut found = Vec::new();
+    Cube::new().solve_streaming(&mut IDSolver::new(), |s| found.push(s.to_vec()));
+    assert_eq!(found, vec![vec![]]);
+}
+
+#[test]
+fn test_from_moves_canonical_cancels_to_nothing() {
+    let (cube, moves) = Cube::from_moves_canonical("RUFF'U'R'").unwrap();
+    assert!(cube.is_solved());
+    assert_eq!(moves, "");
+
+    let (cube, moves) = Cube::from_moves_canonical("").unwrap();
+    assert!(cube.is_solved());
+    assert_eq!(moves, "");
+}
+
+#[test]
+fn test_from_moves_canonical_merges() {
+    let (cube, moves) = Cube::from_moves_canonical("FFRR'RUUUU2D'D'D'").unwrap();
+    assert_eq!(moves, "F2 R U D");
+
+    let mut expected = Cube::new();
+    expected.apply_moves("F2RUD");
+    assert_eq!(cube, expected);
+}
+
+#[test]
+fn test_from_moves_canonical_keeps_separate_faces() {
+    let (_, moves) = Cube::from_moves_canonical("FBF").unwrap();
+    assert_eq!(moves, "F B F");
+}
+
+#[test]
+fn test_from_moves_canonical_invalid() {
+    assert_eq!(Cube::from_moves_canonical("FXR"), Err(MoveParseError { character: 'X', offset: 1 }));
+}
+
+#[test]
+fn test_satisfies_solved_cube() {
+    let cube = Cube::new();
+    let goals = [
+        Goal::Solved, Goal::Cross(Face::U), Goal::FirstLayer(Face::L),
+        Goal::Keyhole, Goal::F2L, Goal::OrientedLastLayer,
+    ];
+    for goal in &goals {
+        assert!(cube.satisfies(goal));
+    }
+}
+
+#[test]
+fn test_satisfies_cross_not_layer() {
+    // R U R' keeps the D cross but takes out the DFR corner
+    let mut cube = Cube::new();
+    cube.apply_moves("RUR'");
+    assert!(cube.satisfies(&Goal::Cross(Face::D)));
+    assert!(!cube.satisfies(&Goal::FirstLayer(Face::D)));
+    assert!(!cube.satisfies(&Goal::F2L));
+}
+
+#[test]
+fn test_satisfies_keyhole() {
+    // Taking out one pair leaves a keyhole
+    let mut cube = Cube::new();
+    cube.apply_moves("RUR'");
+    assert!(cube.satisfies(&Goal::Keyhole));
+
+    // Taking out a second one doesn't
+    cube.apply_moves("L'U'L");
+    assert!(cube.satisfies(&Goal::Cross(Face::D)));
+    assert!(!cube.satisfies(&Goal::Keyhole));
+}
+
+#[test]
+fn test_satisfies_oriented_last_layer() {
+    let mut cube = Cube::new();
+    cube.apply_moves("U2");
+    assert!(cube.satisfies(&Goal::OrientedLastLayer));
+
+    // Sune twists the last layer corners
+    cube.apply_moves("RUR'URU2R'");
+    assert!(cube.satisfies(&Goal::F2L));
+    assert!(!cube.satisfies(&Goal::OrientedLastLayer));
+}
+
+#[test]
+fn test_cube_is_small_and_copy() {
+    assert_eq!(std::mem::size_of::<Cube>(), 40);
+
+    let cube = Cube::new();
+    let mut copy = cube;
+    copy.apply_move(Move::R);
+    assert!(cube.is_solved());
+    assert!(!copy.is_solved());
+}
+
+#[test]
+fn test_split_atomic_turns() {
+    for &m in QUARTER_TURNS.iter() {
+        let turns = m.atomic_turns();
+        assert_eq!(turns.len(), 1);
+        assert_eq!(turns[0], m.rotation());
+    }
+
+    let moves = parse_moves("RUR'U'").unwrap();
+    assert!(moves_are_physically_sequential(&moves));
+    assert_eq!(split_atomic(&moves).len(), 4);
+    assert!(moves_are_physically_sequential(&[]));
+    assert!(split_atomic(&[]).is_empty());
+}
+
+#[test]
+fn test_scramble_v1_is_frozen() {
+    // These must never change, or archived seeds won't reproduce
+    assert_eq!(ScrambleV1::VERSION, 1);
+    assert_eq!(ScrambleV1::generate(2018, 12), parse_moves("FL'F'BD'U'RFRFD'U'").unwrap());
+    assert!(ScrambleV1::generate(0, 0).is_empty());
+}
+
+#[test]
+fn test_scramble_v1_avoids_redundant_moves() {
+    let moves = ScrambleV1::generate(7, 200);
+    for w in moves.windows(2) {
+        assert_ne!(layer_turns(w[0]).0, layer_turns(w[1]).0);
+    }
+    for w in moves.windows(3) {
+        assert!(!(w[0].rotation().0 == w[1].rotation().0 && w[1].rotation().0 == w[2].rotation().0));
+    }
+}
+
+/// Get the stickers of a cube as per-face character arrays
+fn face_chars(cube: &Cube) -> HashMap<Face, [char; 9]> {
+    let mut faces = HashMap::new();
+    for &face in &FACES {
+        let mut chars = [' '; 9];
+        for (i, f) in cube.get_face(face).iter().enumerate() {
+            chars[i] = format!("{:?}", f).chars().next().unwrap();
+        }
+        faces.insert(face, chars);
+    }
+    faces
+}
+
+#[test]
+fn test_from_faces_round_trip() {
+    let mut cube = Cube::new();
+    cube.apply_moves("FRUB'LD'RR");
+    assert_eq!(Cube::from_faces(face_chars(&cube)), Ok(cube));
+}
+
+#[test]
+fn test_from_faces_errors() {
+    let mut faces = face_chars(&Cube::new());
+    faces.remove(&Face::B);
+    assert_eq!(Cube::from_faces(faces), Err(FaceletError::InvalidFormat));
+
+    let mut faces = face_chars(&Cube::new());
+    faces.get_mut(&Face::F).unwrap()[0] = 'x';
+    assert_eq!(Cube::from_faces(faces), Err(FaceletError::InvalidCharacter('x')));
+
+    let mut faces = face_chars(&Cube::new());
+    faces.get_mut(&Face::F).unwrap()[4] = 'U';
+    assert_eq!(Cube::from_faces(faces), Err(FaceletError::InvalidFormat));
+
+    // Swap two stickers on a corner to twist it
+    let mut faces = face_chars(&Cube::new());
+    faces.get_mut(&Face::F).unwrap()[0] = 'U';
+    faces.get_mut(&Face::U).unwrap()[6] = 'F';
+    assert!(Cube::from_faces(faces).is_err());
+}
+
+#[test]
+fn test_get_face_for_net() {
+    use self::Face::*;
+
+    let mut cube = Cube::new();
+    cube.apply_moves("RU");
+
+    let styles = [NetStyle::Horizontal, NetStyle::Vertical, NetStyle::Transparent];
+    for &style in &styles {
+        for &face in &[U, R, F, D, L] {
+            assert_eq!(cube.get_face_for_net(face, style), cube.get_face(face));
+        }
+    }
+
+    assert_eq!(cube.get_face_for_net(B, NetStyle::Horizontal), [L, L, L, U, B, B, U, B, B]);
+    assert_eq!(cube.get_face_for_net(B, NetStyle::Vertical), [B, B, U, B, B, U, L, L, L]);
+    assert_eq!(cube.get_face_for_net(B, NetStyle::Transparent), [L, L, L, B, B, U, B, B, U]);
+}
+
+#[test]
+fn test_pattern_cubes() {
+    let flip = superflip_cube();
+    let spot = four_spot_cube();
+
+    assert_eq!(flip, superflip(&Cube::new()));
+    assert!(spot.pow(2).is_solved());
+
+    // The superflip commutes with every cube
+    assert_eq!(flip.compose(&spot), spot.compose(&flip));
+    assert!(!flip.compose(&spot).is_solved());
+}
+
+#[test]
+fn test_apply_moves_limited() {
+    let mut cube = Cube::new();
+    assert_eq!(cube.apply_moves_limited("", 0), Ok(()));
+    assert_eq!(cube.apply_moves_limited("R", 0), Err(CubeError::TooLong));
+    assert!(cube.is_solved());
+
+    let long = "RU".repeat(100_000);
+    assert_eq!(cube.apply_moves_limited(&long, 1000), Err(CubeError::TooLong));
+    assert!(cube.is_solved());
+
+    // Repeated groups stop being expanded as soon as they pass the limit
+    let repeated = "(RU)1000".repeat(1000);
+    assert_eq!(cube.apply_moves_limited(&repeated, 1000), Err(CubeError::TooLong));
+    assert_eq!(cube.apply_moves_limited("(R)1000(U)1000", 2000), Ok(()));
+    cube.apply_moves("(U')1000(R')1000");
+    assert!(cube.is_solved());
+
+    assert_eq!(cube.apply_moves_limited("RUR'U'", 4), Ok(()));
+    let mut expected = Cube::new();
+    expected.apply_moves("RUR'U'");
+    assert_eq!(cube, expected);
+}
+
+#[test]
+fn test_solve_frames() {
+    use solver::IDSolver;
+
+    let mut cube = Cube::new();
+    cube.apply_moves("RU'");
+    let frames = cube.solve_frames(&mut IDSolver::new());
+
+    assert_eq!(frames.len(), 3);
+    assert_eq!(frames[0].rotation.2, 0);
+    for pair in frames.windows(2) {
+        let mut next = pair[0].state;
+        next.apply_move(pair[1].move_applied.unwrap());
+        assert_eq!(next, pair[1].state);
+        assert_eq!(pair[1].rotation, pair[1].move_applied.unwrap().rotation());
+    }
+    assert!(frames[2].state.is_solved());
+    assert!(!cube.is_solved());
+
+    assert_eq!(Cube::new().solve_frames(&mut IDSolver::new()).len(), 1);
+}
+
+#[test]
+fn test_apply_any() {
+    let mut expected = Cube::new();
+    expected.apply_moves("FR'U2");
+
+    let moves = vec![Move::F, Move::RPrime, Move::U, Move::U];
+    let mut from_vec = Cube::new();
+    assert_eq!(from_vec.apply_any(moves.clone()), Ok(()));
+    assert_eq!(from_vec, expected);
+
+    let mut from_slice = Cube::new();
+    assert_eq!(from_slice.apply_any(&moves[..]), Ok(()));
+    assert_eq!(from_slice, expected);
+
+    let mut from_str = Cube::new();
+    assert_eq!(from_str.apply_any("FR'U2"), Ok(()));
+    assert_eq!(from_str, expected);
+
+    let mut cube = Cube::new();
+    assert_eq!(cube.apply_any("FQ"), Err(MoveParseError { character: 'Q', offset: 1 }));
+    assert!(cube.is_solved());
+}
+
+#[test]
+fn test_half_turns() {
+    let pairs = [
+        (Move::F2, Move::F), (Move::R2, Move::R), (Move::U2, Move::U),
+        (Move::B2, Move::B), (Move::L2, Move::L), (Move::D2, Move::D),
+    ];
+    for &(half, quarter) in &pairs {
+        let mut a = Cube::new();
+        a.apply_move(half);
+        let mut b = Cube::new();
+        b.apply_move(quarter);
+        b.apply_move(quarter);
+        assert_eq!(a, b);
+
+        a.apply_move(half);
+        assert!(a.is_solved());
+    }
+
+    assert_eq!(Move::from("F2"), Move::F2);
+    assert_eq!(parse_moves("RU2").unwrap(), vec![Move::R, Move::U2]);
+}
+
+#[test]
+fn test_slice_moves_parse() {
+    assert_eq!(Move::from("M"), Move::M);
+    assert_eq!(Move::from("M'"), Move::MPrime);
+    assert_eq!(Move::from("E'"), Move::EPrime);
+    assert_eq!(Move::from('S'), Move::S);
+    assert_eq!(parse_moves("ME'S2").unwrap(), vec![Move::M, Move::EPrime, Move::S, Move::S]);
+}
+
+#[test]
+fn test_slice_moves_cycle() {
+    let slices = [(Move::M, Move::MPrime), (Move::E, Move::EPrime), (Move::S, Move::SPrime)];
+    for &(m, prime) in &slices {
+        let mut cube = Cube::new();
+        cube.apply_move(m);
+        assert!(!cube.is_solved());
+        cube.apply_move(prime);
+        assert!(cube.is_solved());
+
+        for _ in 0..4 {
+            cube.apply_move(m);
+        }
+        assert!(cube.is_solved());
+    }
+}
+
+#[test]
+fn test_slice_moves_fixed_stickers() {
+    use self::Face::*;
+
+    // M turns like L, so the U stickers in the middle column move onto F
+    let mut cube = Cube::new();
+    cube.apply_move(Move::M);
+    assert_eq!(cube.get_face(L), [L; 9]);
+    assert_eq!(cube.get_face(R), [R; 9]);
+    assert_eq!(cube.get_face(F), [F, U, F, F, F, F, F, U, F]);
+    assert_eq!(cube.sigma, CornerPermutation::default());
+    assert_eq!(cube.x, X::default());
+
+    // E turns like D, so the F stickers in the middle row move onto R
+    let mut cube = Cube::new();
+    cube.apply_move(Move::E);
+    assert_eq!(cube.get_face(U), [U; 9]);
+    assert_eq!(cube.get_face(D), [D; 9]);
+    assert_eq!(cube.get_face(R), [R, R, R, F, R, F, R, R, R]);
+
+    // S turns like F, so the U stickers in the middle row move onto R
+    let mut cube = Cube::new();
+    cube.apply_move(Move::S);
+    assert_eq!(cube.get_face(F), [F; 9]);
+    assert_eq!(cube.get_face(B), [B; 9]);
+    assert_eq!(cube.get_face(R), [R, U, R, R, R, R, R, U, R]);
+}
+
+/// Check that every sticker on a face other than the center is one color.
+/// Centers aren't tracked, so they always match the face.
+fn face_is(cube: &Cube, face: Face, color: Face) -> bool {
+    cube.get_face(face).iter().enumerate().all(|(i, &c)| i == 4 || c == color)
+}
+
+#[test]
+fn test_slice_moves_rotate_with_outer_layers() {
+    use self::Face::*;
+
+    // Turning both outer layers and the slice between them turns the whole
+    // cube, so every face ends up a single color. R M' L' turns it like R.
+    let mut cube = Cube::new();
+    cube.apply_moves("RM'L'");
+    assert!(face_is(&cube, U, F));
+    assert!(face_is(&cube, F, D));
+    assert!(face_is(&cube, D, B));
+    assert!(face_is(&cube, B, U));
+    assert!(face_is(&cube, R, R));
+    assert!(face_is(&cube, L, L));
+
+    // U E' D' turns it like U
+    let mut cube = Cube::new();
+    cube.apply_moves("UE'D'");
+    assert!(face_is(&cube, U, U));
+    assert!(face_is(&cube, F, R));
+    assert!(face_is(&cube, L, F));
+
+    // F S B' turns it like F
+    let mut cube = Cube::new();
+    cube.apply_moves("FSB'");
+    assert!(face_is(&cube, F, F));
+    assert!(face_is(&cube, R, U));
+    assert!(face_is(&cube, D, R));
+}
+
+#[test]
+fn test_move_try_from_str() {
+    assert_eq!(Move::try_from_str("F"), Ok(Move::F));
+    assert_eq!(Move::try_from_str("R`"), Ok(Move::RPrime));
+    assert_eq!(Move::try_from_str("D\u{2032}"), Ok(Move::DPrime));
+    assert_eq!(Move::try_from_str("B2"), Ok(Move::B2));
+    assert_eq!(Move::try_from_str("S'"), Ok(Move::SPrime));
+
+    assert_eq!(Move::try_from_str(""), Err(MoveParseError { character: '\0', offset: 0 }));
+    assert_eq!(Move::try_from_str("X"), Err(MoveParseError { character: 'X', offset: 0 }));
+    assert_eq!(Move::try_from_str("FR"), Err(MoveParseError { character: 'R', offset: 1 }));
+    assert_eq!(Move::try_from_str("F'2"), Err(MoveParseError { character: '2', offset: 2 }));
+    assert_eq!(Move::try_from_str("M2"), Err(MoveParseError { character: '2', offset: 1 }));
+}
+
+#[test]
+#[should_panic]
+fn test_move_from_invalid_str() {
+    let _ = Move::from("Q");
+}
+
+#[test]
+fn test_inverse() {
+    use rand::{SeedableRng, XorShiftRng};
+
+    assert!(Cube::new().inverse().is_solved());
+
+    let flip = superflip(&Cube::new());
+    assert_eq!(flip.inverse(), flip);
+
+    let mut cube = Cube::new();
+    cube.scramble_with_rng(&mut XorShiftRng::from_seed([253, 1, 2, 3]), 30);
+    let inverse = cube.inverse();
+    assert!(cube.compose(&inverse).is_solved());
+    assert!(inverse.compose(&cube).is_solved());
+    assert_eq!(inverse.inverse(), cube);
+}
+
+#[test]
+fn test_invert_moves() {
+    use rand::{SeedableRng, XorShiftRng};
+
+    assert_eq!(invert_moves(&[]), vec![]);
+    assert_eq!(invert_moves(&[Move::L]), vec![Move::LPrime]);
+    assert_eq!(invert_moves(&[Move::MPrime]), vec![Move::M]);
+
+    let moves = parse_moves("FR'UD2B'L").unwrap();
+    assert_eq!(invert_moves(&moves), parse_moves("L'BD2U'RF'").unwrap());
+    assert_eq!(invert_moves(&invert_moves(&moves)), moves);
+
+    let mut start = Cube::new();
+    start.scramble_with_rng(&mut XorShiftRng::from_seed([254, 1, 2, 3]), 10);
+    let mut cube = start;
+    for &m in moves.iter().chain(invert_moves(&moves).iter()) {
+        cube.apply_move(m);
+    }
+    assert_eq!(cube, start);
+}
+
+#[test]
+fn test_from_facelet_string_round_trip() {
+    let mut cube = Cube::new();
+    cube.apply_moves("RUF'D2LB'M2E2");
+    assert_eq!(Cube::from_facelet_string(&cube.to_facelet_string()), Ok(cube));
+
+    let flip = superflip(&Cube::new());
+    assert_eq!(Cube::from_facelet_string(&flip.to_facelet_string()), Ok(flip));
+}
+
+#[test]
+fn test_from_facelet_string_errors() {
+    let solved = Cube::new().to_facelet_string();
+
+    assert_eq!(Cube::from_facelet_string(&solved[1..]), Err(FaceletError::InvalidFormat));
+    assert_eq!(Cube::from_facelet_string(&solved.replacen("U", "x", 1)), Err(FaceletError::InvalidCharacter('x')));
+    assert_eq!(Cube::from_facelet_string(&solved.replacen("U", "R", 1)), Err(FaceletError::ColorCount(Face::U)));
+
+    // Swapping two stickers on a corner makes a mirror image of it
+    let mut stickers: Vec<char> = solved.chars().collect();
+    stickers.swap(8, 9);
+    let mirrored: String = stickers.iter().collect();
+    assert_eq!(Cube::from_facelet_string(&mirrored), Err(FaceletError::InvalidCubie));
+
+    // Cycling all three twists it
+    stickers.swap(9, 20);
+    let twisted: String = stickers.iter().collect();
+    assert_eq!(Cube::from_facelet_string(&twisted), Err(FaceletError::InvalidState(CubeError::Illegal)));
+}
+
+#[test]
+fn test_scramble_random_state_is_legal() {
+    let mut cube = Cube::new();
+    let mut solved = 0;
+    let mut ranks = HashSet::new();
+    for _ in 0..1000 {
+        cube.scramble_random_state();
+        assert!(cube.is_valid());
+        if cube.is_solved() {
+            solved += 1;
+        }
+        ranks.insert(cube.rank());
+    }
+
+    assert_eq!(solved, 0);
+    assert!(ranks.len() > 990);
+}
+
+#[test]
+fn test_scramble_returns_moves() {
+    let mut cube = Cube::new();
+    let moves = cube.scramble(25);
+    assert_eq!(moves.len(), 25);
+
+    let mut replay = Cube::new();
+    for &m in &moves {
+        replay.apply_move(m);
+    }
+    assert_eq!(replay, cube);
+}
+
+#[test]
+fn test_scramble_with_seed() {
+    let mut a = Cube::new();
+    let mut b = Cube::new();
+    let moves = a.scramble_with_seed(30, 99);
+    assert_eq!(b.scramble_with_seed(30, 99), moves);
+    assert_eq!(a, b);
+
+    let mut replay = Cube::new();
+    for &m in &moves {
+        replay.apply_move(m);
+    }
+    assert_eq!(replay, a);
+
+    let mut c = Cube::new();
+    assert_ne!(c.scramble_with_seed(30, 100), moves);
+}
+
+#[test]
+fn test_move_metrics() {
+    let moves = parse_moves("RU'F2D'L2B").unwrap();
+    assert_eq!(count_htm(&moves), 6);
+    assert_eq!(count_qtm(&moves), 8);
+    assert_eq!(count_stm(&moves), 6);
+
+    let slices = parse_moves("M'E2S").unwrap();
+    assert_eq!(slices.len(), 4);
+    assert_eq!(count_htm(&slices), 8);
+    assert_eq!(count_qtm(&slices), 8);
+    assert_eq!(count_stm(&slices), 4);
+
+    // Moves are counted as written, even if they cancel out
+    let cancelled = parse_moves("RUU'R'").unwrap();
+    assert_eq!(count_htm(&cancelled), 4);
+    assert_eq!(count_qtm(&cancelled), 4);
+    assert_eq!(count_stm(&cancelled), 4);
+
+    assert_eq!(count_htm(&[]), 0);
+    assert_eq!(count_qtm(&[]), 0);
+    assert_eq!(count_stm(&[]), 0);
+}
+
+#[test]
+fn test_display_solved() {
+    let expected = "   UUU
+   UUU
+   UUU
+LLLFFFRRRBBB
+LLLFFFRRRBBB
+LLLFFFRRRBBB
+   DDD
+   DDD
+   DDD";
+    assert_eq!(format!("{}", Cube::new()), expected);
+}
+
+#[test]
+fn test_display_rows_line_up() {
+    let mut c = Cube::new();
+    c.apply_moves("FRUBLD");
+    let net = c.to_string();
+    let lines: Vec<&str> = net.lines().collect();
+
+    assert_eq!(lines.len(), 9);
+    for line in &lines[..3] {
+        assert_eq!(line.len(), 6);
+        assert!(line.starts_with("   "));
+    }
+    for line in &lines[3..6] {
+        assert_eq!(line.len(), 12);
+    }
+    for line in &lines[6..] {
+        assert_eq!(line.len(), 6);
+        assert!(line.starts_with("   "));
+    }
+}
+
+#[test]
+fn test_colored_display() {
+    let mut c = Cube::new();
+    c.apply_moves("RU");
+    let net = c.colored(false).to_string();
+
+    // Removing the escape codes leaves the plain net
+    let mut plain = net.clone();
+    for &face in &FACES {
+        plain = plain.replace(face_ansi_code(face), "");
+    }
+    assert_eq!(plain.replace(ANSI_RESET, ""), c.to_string());
+    assert_eq!(net.matches(ANSI_RESET).count(), 54);
+
+    assert_eq!(c.colored(true).to_string(), c.to_string());
+}
+
+#[test]
+fn test_face_ansi_codes_distinct() {
+    for &a in &FACES {
+        for &b in &FACES {
+            assert_eq!(a == b, face_ansi_code(a) == face_ansi_code(b));
+        }
+    }
+}
+
+#[test]
+fn test_svg_solved() {
+    let svg = Cube::new().to_svg();
+
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    assert_eq!(svg.matches("<rect").count(), 54);
+    assert_eq!(svg.matches("/>").count(), 54);
+    for &face in &FACES {
+        assert_eq!(svg.matches(&format!("fill=\"{}\"", face_svg_color(face))).count(), 9);
+    }
+}
+
+#[test]
+fn test_svg_options() {
+    let options = SvgOptions {
+        sticker_size: 20,
+        stroke_width: 1,
+        stroke_color: String::from("gray"),
+    };
+    let mut c = Cube::new();
+    c.apply_moves("FRU");
+    let svg = c.to_svg_with(&options);
+
+    assert!(svg.contains("width=\"240\" height=\"180\""));
+    assert_eq!(svg.matches("stroke=\"gray\" stroke-width=\"1\"").count(), 54);
+    // The last sticker of D is in the bottom right corner of its face
+    assert!(svg.contains("<rect x=\"100\" y=\"160\""));
+}
+
+#[test]
+fn test_apply_move_str() {
+    let mut cube = Cube::new();
+    cube.apply_move_str("R'").unwrap();
+    let mut expected = Cube::new();
+    expected.apply_move(Move::RPrime);
+    assert_eq!(cube, expected);
+
+    cube.apply_move_str("F2").unwrap();
+    expected.apply_move(Move::F2);
+    assert_eq!(cube, expected);
+
+    cube.apply_move_str("M2").unwrap();
+    expected.apply_moves("MM");
+    assert_eq!(cube, expected);
+}
+
+#[test]
+fn test_apply_move_str_errors() {
+    let mut cube = Cube::new();
+    assert_eq!(cube.apply_move_str("RU"), Err(MoveParseError { character: 'U', offset: 1 }));
+    assert_eq!(cube.apply_move_str("R'2"), Err(MoveParseError { character: '2', offset: 2 }));
+    assert_eq!(cube.apply_move_str(""), Err(MoveParseError { character: '\0', offset: 0 }));
+    assert_eq!(cube.apply_move_str("X"), Err(MoveParseError { character: 'X', offset: 0 }));
+    assert!(cube.is_solved());
+}
+
+#[test]
+fn test_wide_moves() {
+    let wide_equivalents = [
+        ("Rw", "RM'"), ("Lw", "LM"), ("Uw", "UE'"), ("Dw", "DE"), ("Fw", "FS"), ("Bw", "BS'"),
+        ("Rw'", "R'M"), ("Rw2", "R2M'M'"), ("r", "RM'"), ("u'", "U'E"), ("f2", "F2SS"),
+    ];
+    for &(wide, expected) in &wide_equivalents {
+        assert_eq!(parse_moves(wide).unwrap(), parse_moves(expected).unwrap(), "{}", wide);
+    }
+}
+
+#[test]
+fn test_wide_moves_undo() {
+    for &(wide, inverse) in &[("Rw", "Rw'"), ("l", "l'"), ("Uw2", "Uw2"), ("Fwb'", "bFw'")] {
+        let mut c = Cube::new();
+        c.apply_moves(wide);
+        assert!(!c.is_solved());
+        c.apply_moves(inverse);
+        assert!(c.is_solved(), "{} {}", wide, inverse);
+    }
+
+    let mut c = Cube::new();
+    c.apply_moves("RwRwRwRw");
+    assert!(c.is_solved());
+}
+
+#[test]
+fn test_wide_move_str() {
+    let mut a = Cube::new();
+    a.apply_move_str("Rw'").unwrap();
+    let mut b = Cube::new();
+    b.apply_moves("R'M");
+    assert_eq!(a, b);
+
+    assert_eq!(a.apply_move_str("Rwr"), Err(MoveParseError { character: 'r', offset: 2 }));
+    assert_eq!(a.apply_move_str("Rx"), Err(MoveParseError { character: 'x', offset: 1 }));
+    assert_eq!(a, b);
+}
+
+#[test]
+fn test_get_sticker_matches_get_face() {
+    let mut c = Cube::new();
+    c.apply_moves("FRUBLDF'R'U2B'L2D'");
+
+    for &face in &FACES {
+        let stickers = c.get_face(face);
+        for (i, &sticker) in stickers.iter().enumerate() {
+            assert_eq!(c.get_sticker(face, i), sticker, "{:?} {}", face, i);
+        }
+    }
+}
+
+#[test]
+fn test_get_sticker_positions() {
+    let mut c = Cube::new();
+    c.apply_moves("R");
+
+    // R brings the F stickers of the right column up to U
+    assert_eq!(c.get_sticker(Face::U, 2), Face::F);
+    assert_eq!(c.get_sticker(Face::U, 5), Face::F);
+    assert_eq!(c.get_sticker(Face::U, 8), Face::F);
+    assert_eq!(c.get_sticker(Face::U, 0), Face::U);
+    assert_eq!(c.get_sticker(Face::B, 0), Face::U);
+    assert_eq!(c.get_sticker(Face::R, 4), Face::R);
+}
+
+#[test]
+#[should_panic]
+fn test_get_sticker_out_of_range() {
+    Cube::new().get_sticker(Face::U, 9);
+}
+
+#[test]
+fn test_matches_ignoring_faces() {
+    let side_faces = [Face::D, Face::F, Face::R, Face::B, Face::L];
+
+    // Turning the bottom two layers leaves U alone
+    let mut c = Cube::new();
+    c.apply_moves("DE2D'E2D");
+    assert!(c.matches(&Cube::new(), &side_faces));
+    assert!(!c.matches(&Cube::new(), &[Face::D]));
+    assert!(!c.matches(&Cube::new(), &[]));
+
+    // D turns don't change the top two rows of the side faces, so a cube whose
+    // last step is a D turn still matches when ignoring D
+    let mut target = Cube::new();
+    target.apply_moves("RUR'");
+    let mut c = target;
+    c.apply_moves("D");
+    assert!(!c.matches(&target, &[Face::D]));
+    assert!(c.matches(&target, &side_faces));
+
+    // Everything matches when every face is ignored
+    assert!(c.matches(&Cube::new(), &FACES));
+    assert!(c.matches(&c, &[]));
+}
+
+#[test]
+fn test_commutator_of_commuting_moves() {
+    let mut c = Cube::new();
+    for m in commutator(&[Move::R, Move::R], &[Move::L]) {
+        c.apply_move(m);
+    }
+    assert!(c.is_solved());
+
+    assert_eq!(commutator(&[], &[Move::F]), vec![Move::F, Move::FPrime]);
+}
+
+#[test]
+fn test_commutator_three_cycle() {
+    // [R U R', D] cycles three corners and leaves everything else alone
+    let insert = parse_moves("RUR'").unwrap();
+    let moves = commutator(&insert, &[Move::D]);
+    assert_eq!(moves, parse_moves("RUR'DRU'R'D'").unwrap());
+
+    let mut c = Cube::new();
+    for &m in &moves {
+        c.apply_move(m);
+    }
+    assert_eq!(c.unsolved_pieces(), (3, 0));
+
+    // Three applications of a 3-cycle put the pieces back
+    for _ in 0..2 {
+        for &m in &moves {
+            c.apply_move(m);
+        }
+    }
+    assert!(c.is_solved());
+}
+
+#[test]
+fn test_conjugate() {
+    let setup = parse_moves("FU").unwrap();
+    let alg = parse_moves("RUR'U'").unwrap();
+    let moves = conjugate(&setup, &alg);
+    assert_eq!(moves, parse_moves("FURUR'U'U'F'").unwrap());
+
+    // Conjugating the identity does nothing
+    let mut c = Cube::new();
+    for m in conjugate(&setup, &[]) {
+        c.apply_move(m);
+    }
+    assert!(c.is_solved());
+}
+
+#[test]
+fn test_to_quarter_turns_half_turns() {
+    let moves = parse_moves("F2R2U2B2L2D2").unwrap();
+    let quarters = to_quarter_turns(&moves);
+    assert_eq!(quarters, parse_moves("FFRRUUBBLLDD").unwrap());
+    assert_eq!(simplify_moves(&quarters), moves);
+}
+
+#[test]
+fn test_to_quarter_turns_mixed() {
+    let moves = parse_moves("RU2F'MD2S'L").unwrap();
+    let quarters = to_quarter_turns(&moves);
+    assert_eq!(quarters, parse_moves("RUUF'MDDS'L").unwrap());
+
+    let (mut a, mut b) = (Cube::new(), Cube::new());
+    moves.iter().for_each(|&m| a.apply_move(m));
+    quarters.iter().for_each(|&m| b.apply_move(m));
+    assert_eq!(a, b);
+
+    assert_eq!(to_quarter_turns(&[]), vec![]);
+}
+
+#[test]
+fn test_cube_rotations() {
+    let rotations = cube_rotations();
+    assert_eq!(rotations.len(), 24);
+
+    // Opposite faces stay opposite, and a move that's turned all the way
+    // around an axis comes back to itself
+    for rotation in &rotations {
+        for i in 0..3 {
+            assert_eq!(face_turn_index(rotation[i + 3]), (face_turn_index(rotation[i]) + 3) % 6);
+        }
+    }
+    let mut m = Move::M;
+    for _ in 0..4 {
+        m = rotate_move(m, &rotations[1]).next().unwrap();
+    }
+    assert_eq!(m, Move::M);
+}
+
+#[test]
+fn test_canonicalize_rotations() {
+    let moves = parse_moves("RUR'U'MFD2S'").unwrap();
+    let canonical = canonicalize(&moves);
+    for rotation in &cube_rotations() {
+        let rotated: Vec<Move> = moves.iter().flat_map(|&m| rotate_move(m, rotation)).collect();
+        assert_eq!(canonicalize(&rotated), canonical);
+    }
+    assert_eq!(canonicalize(&canonical), canonical);
+
+    // Simplification happens first
+    assert_eq!(canonicalize(&parse_moves("RR'UUU").unwrap()), canonicalize(&parse_moves("F'").unwrap()));
+}
+
+#[test]
+fn test_canonicalize_different() {
+    let sexy = canonicalize(&parse_moves("RUR'U'").unwrap());
+    assert_ne!(sexy, canonicalize(&parse_moves("RU'R'U").unwrap()));
+    assert_ne!(sexy, canonicalize(&parse_moves("RUR'").unwrap()));
+    assert_ne!(canonicalize(&parse_moves("M").unwrap()), canonicalize(&parse_moves("R").unwrap()));
+
+    // A mirror image isn't a rotation
+    assert_ne!(canonicalize(&parse_moves("RU").unwrap()), canonicalize(&parse_moves("L'U'").unwrap()));
+
+    // Turns that only cancel out after commuting aren't merged
+    assert_ne!(canonicalize(&parse_moves("RLR'").unwrap()), canonicalize(&parse_moves("L").unwrap()));
+}
+
+#[test]
+fn test_simplify_moves_cancels_completely() {
+    assert_eq!(simplify_moves(&[]), vec![]);
+    assert_eq!(simplify_moves(&parse_moves("RR'").unwrap()), vec![]);
+    assert_eq!(simplify_moves(&parse_moves("F2F2").unwrap()), vec![]);
+    assert_eq!(simplify_moves(&parse_moves("FRUU'R'F'").unwrap()), vec![]);
+    assert_eq!(simplify_moves(&parse_moves("MMMM").unwrap()), vec![]);
+}
+
+#[test]
+fn test_simplify_moves_merges() {
+    assert_eq!(simplify_moves(&parse_moves("FF").unwrap()), vec![Move::F2]);
+    assert_eq!(simplify_moves(&parse_moves("FFF").unwrap()), vec![Move::FPrime]);
+    assert_eq!(simplify_moves(&parse_moves("F2F").unwrap()), vec![Move::FPrime]);
+    assert_eq!(simplify_moves(&parse_moves("RUU'R").unwrap()), vec![Move::R2]);
+    assert_eq!(simplify_moves(&parse_moves("UR'RRD").unwrap()), vec![Move::U, Move::R, Move::D]);
+    assert_eq!(simplify_moves(&parse_moves("MMM").unwrap()), vec![Move::MPrime]);
+}
+
+#[test]
+fn test_simplify_moves_keeps_separate_turns() {
+    let moves = parse_moves("FUFRLR").unwrap();
+    assert_eq!(simplify_moves(&moves), moves);
+}
+
+#[test]
+fn test_simplify_moves_same_effect() {
+    use rand::{SeedableRng, XorShiftRng};
+
+    let moves = parse_moves("RRUU'FF'FL2L2D'DDBB").unwrap();
+    let simplified = simplify_moves(&moves);
+    assert!(simplified.len() < moves.len());
+
+    let mut a = Cube::new();
+    a.scramble_with_rng(&mut XorShiftRng::from_seed([272, 1, 2, 3]), 20);
+    let mut b = a;
+    for &m in &moves {
+        a.apply_move(m);
+    }
+    for &m in &simplified {
+        b.apply_move(m);
+    }
+    assert_eq!(a, b);
+}
+
+#[test]
+fn test_move_iter_suffixes() {
+    let moves: Vec<Move> = MoveIter::new("RU'F2D`L\u{2032}B").map(|m| m.unwrap()).collect();
+    assert_eq!(moves, vec![Move::R, Move::UPrime, Move::F2, Move::DPrime, Move::LPrime, Move::B]);
+
+    // A slice half turn is two moves
+    let moves: Vec<Move> = MoveIter::new("M2E").map(|m| m.unwrap()).collect();
+    assert_eq!(moves, vec![Move::M, Move::M, Move::E]);
+}
+
+#[test]
+fn test_move_iter_trailing_prime() {
+    let moves: Vec<Move> = MoveIter::new("RU'").map(|m| m.unwrap()).collect();
+    assert_eq!(moves, vec![Move::R, Move::UPrime]);
+
+    // A prime can't follow another suffix
+    let mut iter = MoveIter::new("R2'");
+    assert_eq!(iter.next(), Some(Ok(Move::R2)));
+    assert_eq!(iter.next(), Some(Err(MoveParseError { character: '\'', offset: 2 })));
+    assert_eq!(iter.next(), None);
+
+    // Neither can it start a sequence
+    let mut iter = MoveIter::new("'R");
+    assert_eq!(iter.next(), Some(Err(MoveParseError { character: '\'', offset: 0 })));
+    assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn test_move_iter_empty() {
+    assert_eq!(MoveIter::new("").next(), None);
+    assert_eq!(MoveIter::new("  ").next(), None);
+}
+
+#[test]
+fn test_move_iter_lazy() {
+    // Moves before an error are still yielded
+    let mut iter = MoveIter::new("FRX");
+    assert_eq!(iter.next(), Some(Ok(Move::F)));
+    assert_eq!(iter.next(), Some(Ok(Move::R)));
+    assert_eq!(iter.next(), Some(Err(MoveParseError { character: 'X', offset: 2 })));
+    assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn test_repeated_group() {
+    let mut cube = Cube::new();
+    cube.apply_moves("(RUR'U')6");
+    assert!(cube.is_solved());
+
+    cube.apply_moves("(RUR'U')x6");
+    assert!(cube.is_solved());
+
+    cube.apply_moves("(RUR'U')3");
+    assert!(!cube.is_solved());
+    cube.apply_moves("(RUR'U')3");
+    assert!(cube.is_solved());
+}
+
+#[test]
+fn test_repeated_group_counts() {
+    assert_eq!(parse_moves("(RU)2").unwrap(), parse_moves("RURU").unwrap());
+    assert_eq!(parse_moves("F(RU)x2B").unwrap(), parse_moves("FRURUB").unwrap());
+    assert_eq!(parse_moves("(R2U')").unwrap(), parse_moves("R2U'").unwrap());
+    assert_eq!(parse_moves("(RU)10").unwrap().len(), 20);
+    assert_eq!(parse_moves("(RU)0F").unwrap(), vec![Move::F]);
+    assert_eq!(parse_moves("()3F").unwrap(), vec![Move::F]);
+    assert_eq!(parse_moves("(R)1000").unwrap().len(), MAX_GROUP_REPEATS);
+}
+
+#[test]
+fn test_repeated_group_errors() {
+    assert_eq!(parse_moves("((R)2)3"), Err(MoveParseError { character: '(', offset: 1 }));
+    assert_eq!(parse_moves("F(RU"), Err(MoveParseError { character: '(', offset: 1 }));
+    assert_eq!(parse_moves("RU)2"), Err(MoveParseError { character: ')', offset: 2 }));
+    assert_eq!(parse_moves("(R)x"), Err(MoveParseError { character: '\0', offset: 4 }));
+    assert_eq!(parse_moves("(R)xU"), Err(MoveParseError { character: 'U', offset: 4 }));
+    assert_eq!(parse_moves("(R)99999999999999999999"), Err(MoveParseError { character: '9', offset: 6 }));
+    assert_eq!(parse_moves("(R)1001"), Err(MoveParseError { character: '1', offset: 6 }));
+
+    // A group is never a single move
+    let mut cube = Cube::new();
+    assert_eq!(cube.apply_move_str("(RU)2"), Err(MoveParseError { character: '(', offset: 0 }));
+    assert_eq!(cube.apply_move_str(" (R)"), Err(MoveParseError { character: '(', offset: 1 }));
+    assert!(cube.is_solved());
+}
+
+#[test]
+fn test_check_legality() {
+    let mut cube = Cube::new();
+    cube.apply_moves("R2U'FLB2");
+    assert_eq!(cube.check_legality(), Ok(()));
+
+    let mut flipped = Cube::new();
+    flipped.y = Y::from_array(&[0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(flipped.check_legality(), Err(Illegality::EdgeFlip));
+
+    let mut twisted = Cube::new();
+    twisted.x = X::from_array(&[0, 0, 0, 0, 0, 2, 0, 0]);
+    assert_eq!(twisted.check_legality(), Err(Illegality::CornerTwist));
+
+    let mut swapped = Cube::new();
+    swapped.tau = EdgePermutation::from_array(&[0, 1, 3, 2, 4, 5, 6, 7, 8, 9, 10, 11]);
+    assert_eq!(swapped.check_legality(), Err(Illegality::PermutationParity));
+
+    let mut duplicate = Cube::new();
+    duplicate.sigma = CornerPermutation::from_array(&[0, 0, 2, 3, 4, 5, 6, 7]);
+    assert_eq!(duplicate.check_legality(), Err(Illegality::DuplicateCorner));
+}
+
+#[test]
+fn test_permutation_views() {
+    let mut cube = Cube::new();
+    assert_eq!(cube.corner_permutation(), CORNERS);
+    assert_eq!(cube.edge_permutation(), EDGES);
+
+    cube.apply_moves("R");
+    let corners = cube.corner_permutation();
+    let edges = cube.edge_permutation();
+    assert_ne!(corners, CORNERS);
+    assert_ne!(edges, EDGES);
+    assert_eq!(corners[Corner::UBR as usize], Corner::URF);
+    assert_eq!(edges[Edge::UR as usize], Edge::RF);
+    // The L side isn't touched
+    assert_eq!(corners[Corner::UFL as usize], Corner::UFL);
+    assert_eq!(edges[Edge::UL as usize], Edge::UL);
+
+    // Orientation doesn't show up in the permutation
+    assert_eq!(superflip(&Cube::new()).edge_permutation(), EDGES);
+}
+
+#[test]
+fn test_cycles_known_scramble() {
+    let mut cube = Cube::new();
+    cube.apply_moves("U2");
+    assert_eq!(cube.corner_cycles(), vec![vec![Corner::URF, Corner::ULB], vec![Corner::UFL, Corner::UBR]]);
+    assert_eq!(cube.edge_cycles(), vec![vec![Edge::UR, Edge::UL], vec![Edge::UB, Edge::UF]]);
+
+    // [R U R', D] only cycles three corners
+    let mut cube = Cube::new();
+    cube.apply_moves("RUR'DRU'R'D'");
+    let corners = cube.corner_cycles();
+    assert_eq!(corners.len(), 1);
+    assert_eq!(corners[0].len(), 3);
+    assert!(cube.edge_cycles().is_empty());
+}
+
+#[test]
+fn test_cycles_follow_cubies() {
+    let mut cube = Cube::new();
+    cube.apply_moves("FRUBLDF'R'U2");
+    let corners = cube.corner_permutation();
+    let edges = cube.edge_permutation();
+
+    for cycle in cube.corner_cycles() {
+        for (i, &cubicle) in cycle.iter().enumerate() {
+            assert_eq!(corners[cubicle as usize], cycle[(i + 1) % cycle.len()]);
+        }
+    }
+    for cycle in cube.edge_cycles() {
+        for (i, &cubicle) in cycle.iter().enumerate() {
+            assert_eq!(edges[cubicle as usize], cycle[(i + 1) % cycle.len()]);
+        }
+    }
+
+    let moved_corners: usize = cube.corner_cycles().iter().map(|c| c.len()).sum();
+    let moved_edges: usize = cube.edge_cycles().iter().map(|c| c.len()).sum();
+    assert_eq!(moved_corners, corners.iter().enumerate().filter(|&(i, &c)| c as usize != i).count());
+    assert_eq!(moved_edges, edges.iter().enumerate().filter(|&(i, &e)| e as usize != i).count());
+}
+
+#[test]
+fn test_million_moves() {
+    // Applying a sequence and then its inverse over and over always gets back
+    // to the same states. The cube is Copy, so it can't own any heap memory
+    // that could grow along the way.
+    let moves = ScrambleV1::generate(2018, 25);
+    let inverse = invert_moves(&moves);
+    let mut scrambled = Cube::new();
+    for &m in &moves {
+        scrambled.apply_move(m);
+    }
+
+    let mut cube = Cube::new();
+    for _ in 0..20_000 {
+        for &m in &moves {
+            cube.apply_move(m);
+        }
+        assert_eq!(cube, scrambled);
+        for &m in &inverse {
+            cube.apply_move(m);
+        }
+        assert!(cube.is_solved());
+    }
+}
+
+#[test]
+fn test_hash_cube() {
+    let mut cube = Cube::new();
+    cube.apply_moves("FRUBLD");
+    let copy = cube;
+
+    let mut set = HashSet::new();
+    set.insert(cube);
+    set.insert(copy);
+    assert_eq!(set.len(), 1);
+
+    // The same state reached by different moves is the same key
+    let mut other = Cube::new();
+    other.apply_moves("FRUBLDD2D2");
+    assert!(set.contains(&other));
+
+    set.insert(Cube::new());
+    assert_eq!(set.len(), 2);
+}
+
+#[test]
+fn test_checkerboard() {
+    let c = checkerboard(&Cube::new());
+    assert!(!c.is_solved());
+    assert_eq!(c.get_face(Face::U), [Face::U, Face::D, Face::U, Face::D, Face::U, Face::D, Face::U, Face::D, Face::U]);
+    assert_eq!(c.get_face(Face::R), [Face::R, Face::L, Face::R, Face::L, Face::R, Face::L, Face::R, Face::L, Face::R]);
+    assert_eq!(c.get_face(Face::F), [Face::F, Face::B, Face::F, Face::B, Face::F, Face::B, Face::F, Face::B, Face::F]);
+
+    // The checkerboard is its own inverse
+    assert!(checkerboard(&c).is_solved());
+}
+
+#[test]
+fn test_cube_in_cube() {
+    let c = cube_in_cube(&Cube::new());
+    assert!(!c.is_solved());
+    assert_eq!(c.get_face(Face::U), [Face::F, Face::F, Face::F, Face::F, Face::U, Face::U, Face::F, Face::U, Face::U]);
+    assert_eq!(c.get_face(Face::R), [Face::R, Face::R, Face::U, Face::R, Face::R, Face::U, Face::U, Face::U, Face::U]);
+    assert_eq!(c.get_face(Face::F), [Face::R, Face::F, Face::F, Face::R, Face::F, Face::F, Face::R, Face::R, Face::R]);
+    assert!(c.is_valid());
+}
+
+#[test]
+fn test_six_spot() {
+    let c = six_spot(&Cube::new());
+    assert!(!c.is_solved());
+    for &(face, border) in &[(Face::U, Face::F), (Face::R, Face::U), (Face::F, Face::R),
+                             (Face::D, Face::B), (Face::L, Face::D), (Face::B, Face::L)] {
+        let mut expected = [border; 9];
+        expected[4] = face;
+        assert_eq!(c.get_face(face), expected, "{:?}", face);
+    }
+
+    // Patterns apply on top of whatever state the cube is in
+    let mut scrambled = Cube::new();
+    scrambled.apply_moves("RU");
+    assert_eq!(six_spot(&scrambled), scrambled.compose(&six_spot(&Cube::new())));
+}
+
+#[test]
+fn test_solution_length() {
+    use solver::IDSolver;
+
+    let mut solver = IDSolver::new();
+    assert_eq!(Cube::new().solution_length(&mut solver), 0);
+
+    for m in &["F", "R'", "U2", "B"] {
+        let mut cube = Cube::new();
+        cube.apply_moves(m);
+        let before = cube;
+        assert_eq!(cube.solution_length(&mut solver), 1, "{}", m);
+        assert_eq!(cube, before);
+    }
+}
+
+#[test]
+fn test_generate_scramble() {
+    for seed in 0..50 {
+        let (scramble, cube) = generate_scramble(25, Some(seed));
+        let moves: Vec<Move> = scramble.split(' ').map(|m| Move::try_from_str(m).unwrap()).collect();
+        assert_eq!(moves.len(), 25);
+
+        for pair in moves.windows(2) {
+            assert_ne!(layer_turns(pair[0]).0, layer_turns(pair[1]).0, "{}", scramble);
+        }
+        for triple in moves.windows(3) {
+            let axes: Vec<Axis> = triple.iter().map(|m| m.rotation().0).collect();
+            assert!(axes[0] != axes[1] || axes[1] != axes[2], "{}", scramble);
+        }
+
+        let mut expected = Cube::new();
+        expected.apply_moves(&scramble);
+        assert_eq!(cube, expected);
+
+        assert_eq!(generate_scramble(25, Some(seed)), (scramble, cube));
+    }
+}
+
+#[test]
+fn test_generate_scramble_unseeded() {
+    let (scramble, cube) = generate_scramble(20, None);
+    assert_eq!(scramble.split(' ').count(), 20);
+    assert!(!cube.is_solved());
+
+    assert_eq!(generate_scramble(0, None), (String::new(), Cube::new()));
+}
+
+#[test]
+fn test_face_opposite() {
+    let pairs = [(Face::U, Face::D), (Face::D, Face::U), (Face::L, Face::R),
+                 (Face::R, Face::L), (Face::F, Face::B), (Face::B, Face::F)];
+    for &(face, opposite) in &pairs {
+        assert_eq!(face.opposite(), opposite);
+        assert_eq!(face.opposite().opposite(), face);
+    }
+}
+
+#[test]
+fn test_face_is_adjacent() {
+    assert!(Face::U.is_adjacent(Face::R));
+    assert!(Face::F.is_adjacent(Face::L));
+    assert!(Face::B.is_adjacent(Face::D));
+    assert!(!Face::F.is_adjacent(Face::B));
+    assert!(!Face::R.is_adjacent(Face::R));
+
+    // Every face has four neighbours, and adjacency goes both ways
+    for &a in &FACES {
+        assert_eq!(FACES.iter().filter(|&&b| a.is_adjacent(b)).count(), 4);
+        for &b in &FACES {
+            assert_eq!(a.is_adjacent(b), b.is_adjacent(a));
+        }
+    }
+}
+
+#[test]
+fn test_from_faces_arrays() {
+    let faces_of = |c: &Cube| {
+        let mut faces = [(Face::U, [Face::U; 9]); 6];
+        for (i, &f) in FACES.iter().enumerate() {
+            faces[i] = (f, c.get_face(f));
+        }
+        faces
+    };
+
+    assert_eq!(Cube::from_faces(faces_of(&Cube::new())), Ok(Cube::new()));
+
+    let mut scrambled = Cube::new();
+    scrambled.apply_moves("FRUB'L2D");
+    let faces = faces_of(&scrambled);
+    let cube = Cube::from_faces(faces).unwrap();
+    assert_eq!(cube, scrambled);
+    for &(face, stickers) in &faces {
+        assert_eq!(cube.get_face(face), stickers);
+    }
+
+    // The order of the faces doesn't matter
+    let mut reversed = faces;
+    reversed.reverse();
+    assert_eq!(Cube::from_faces(reversed), Ok(scrambled));
+}
+
+#[test]
+fn test_from_faces_arrays_errors() {
+    let solved = [
+        (Face::U, [Face::U; 9]), (Face::R, [Face::R; 9]), (Face::F, [Face::F; 9]),
+        (Face::D, [Face::D; 9]), (Face::L, [Face::L; 9]), (Face::B, [Face::B; 9]),
+    ];
+
+    let mut repeated = solved;
+    repeated[1] = (Face::U, [Face::U; 9]);
+    assert_eq!(Cube::from_faces(repeated), Err(FaceletError::InvalidFormat));
+
+    let mut wrong_center = solved;
+    wrong_center[0].1[4] = Face::R;
+    assert_eq!(Cube::from_faces(wrong_center), Err(FaceletError::InvalidFormat));
+
+    let mut miscounted = solved;
+    miscounted[0].1[0] = Face::R;
+    assert_eq!(Cube::from_faces(miscounted), Err(FaceletError::ColorCount(Face::U)));
+
+    // Swapping two stickers keeps the counts right but makes an impossible corner
+    let mut impossible = solved;
+    impossible[0].1[8] = Face::R;
+    impossible[1].1[0] = Face::U;
+    assert_eq!(Cube::from_faces(impossible), Err(FaceletError::InvalidCubie));
+}
+
+#[test]
+fn test_move_inverse() {
+    for &m in &MOVES {
+        assert_eq!(m.inverse().inverse(), m, "{:?}", m);
+
+        let mut cube = Cube::new();
+        cube.apply_move(m);
+        cube.apply_move(m.inverse());
+        assert!(cube.is_solved(), "{:?}", m);
+    }
+}
+
+#[test]
+fn test_move_is_prime() {
+    let primes: Vec<Move> = MOVES.iter().cloned().filter(|m| m.is_prime()).collect();
+    assert_eq!(primes, vec![
+        Move::FPrime, Move::RPrime, Move::UPrime, Move::BPrime, Move::LPrime, Move::DPrime,
+        Move::MPrime, Move::EPrime, Move::SPrime,
+    ]);
+}
+
+#[test]
+fn test_move_face() {
+    for &m in &MOVES {
+        assert_eq!(m.inverse().face(), m.face(), "{:?}", m);
+    }
+    assert_eq!(Move::F.face(), Face::F);
+    assert_eq!(Move::BPrime.face(), Face::B);
+    assert_eq!(Move::L2.face(), Face::L);
+    assert_eq!(Move::UPrime.face(), Face::U);
+    assert_eq!(Move::E.face(), Face::D);
+    assert_eq!(Move::SPrime.face(), Face::F);
+}
+
+#[test]
+fn test_move_display() {
+    let notation: Vec<String> = MOVES.iter().map(|m| m.to_string()).collect();
+    assert_eq!(notation, vec![
+        "F", "R", "U", "B", "L", "D",
+        "F'", "R'", "U'", "B'", "L'", "D'",
+        "F2", "R2", "U2", "B2", "L2", "D2",
+        "M", "E", "S",
+        "M'", "E'", "S'",
+    ]);
+
+    for &m in &MOVES {
+        assert_eq!(Move::try_from_str(&m.to_string()), Ok(m));
+    }
+}
+
+#[test]
+fn test_moves_to_string() {
+    assert_eq!(moves_to_string(&[]), "");
+    assert_eq!(moves_to_string(&[Move::M]), "M");
+
+    let s = moves_to_string(&MOVES);
+    assert_eq!(s.split(' ').count(), 24);
+    assert_eq!(parse_moves(&s).unwrap(), MOVES.to_vec());
+}
+
+#[test]
+fn test_pocket_cube_inverse() {
+    use self::pocket::PocketCube;
+
+    let mut cube = PocketCube::new();
+    cube.apply_moves("FF'");
+    assert!(cube.is_solved());
+
+    let moves = parse_moves("RUF'L2DB'U2R'").unwrap();
+    for &m in &moves {
+        cube.apply_move(m);
+    }
+    assert!(!cube.is_solved());
+    for m in invert_moves(&moves) {
+        cube.apply_move(m);
+    }
+    assert!(cube.is_solved());
+}
+
+#[test]
+fn test_pocket_cube_matches_corners() {
+    use self::pocket::PocketCube;
+
+    // The pocket cube looks like the corners of a Rubik's cube
+    let scramble = "RUF'L2DB'U2R'D2";
+    let mut pocket = PocketCube::new();
+    pocket.apply_moves(scramble);
+    let mut cube = Cube::new();
+    cube.apply_moves(scramble);
+
+    for &face in &FACES {
+        let stickers = cube.get_face(face);
+        assert_eq!(pocket.get_face(face), [stickers[0], stickers[2], stickers[6], stickers[8]], "{:?}", face);
+    }
+}
+
+#[test]
+fn test_pocket_cube_slice_moves() {
+    use self::pocket::PocketCube;
+
+    // There are no middle layers to turn
+    let mut cube = PocketCube::new();
+    cube.apply_moves("MES");
+    assert!(cube.is_solved());
+}
+
+#[test]
+fn test_pocket_cube_rotations_are_solved() {
+    use self::pocket::PocketCube;
+
+    // Turning opposite faces the opposite ways rotates the whole cube
+    for moves in &["RL'", "UD'", "FB'", "R2L2U'D"] {
+        let mut cube = PocketCube::new();
+        cube.apply_moves(moves);
+        assert!(cube.is_solved(), "{}", moves);
+    }
+
+    let mut cube = PocketCube::new();
+    cube.apply_moves("RL");
+    assert!(!cube.is_solved());
+}
+
+#[test]
+fn test_turn() {
+    let equivalents = [(1, "R"), (-1, "R'"), (2, "R2"), (-2, "R2"), (3, "R'"), (-3, "R"), (5, "R"), (-6, "R2")];
+    for &(quarters, moves) in &equivalents {
+        let mut a = Cube::new();
+        a.turn(Face::R, quarters);
+        let mut b = Cube::new();
+        b.apply_moves(moves);
+        assert_eq!(a, b, "{}", quarters);
+    }
+
+    for &quarters in &[0, 4, -4, 8] {
+        let mut cube = Cube::new();
+        cube.turn(Face::R, quarters);
+        assert!(cube.is_solved(), "{}", quarters);
+    }
+
+    for &face in &FACES {
+        let mut a = Cube::new();
+        a.turn(face, 1);
+        let mut b = Cube::new();
+        b.apply_move(Move::from(format!("{:?}", face).as_str()));
+        assert_eq!(a, b, "{:?}", face);
+    }
+}
+
+#[test]
+fn test_cross_solved_not_layer() {
+    // Taking the front right pair out of its slot leaves the D cross alone
+    let mut cube = Cube::new();
+    cube.apply_moves("RUR'");
+    assert!(cube.is_cross_solved(Face::D));
+    assert!(!cube.is_layer_solved(Face::D));
+}
+
+#[test]
+fn test_layer_solved() {
+    for &face in &FACES {
+        assert!(Cube::new().is_layer_solved(face));
+        assert!(Cube::new().is_cross_solved(face));
+    }
+
+    let mut cube = Cube::new();
+    cube.apply_moves("U");
+    assert!(cube.is_layer_solved(Face::D));
+    assert!(!cube.is_layer_solved(Face::U));
+    assert!(!cube.is_cross_solved(Face::U));
+
+    cube.apply_moves("RUR'");
+    assert!(!cube.is_layer_solved(Face::D));
+    cube.apply_moves("RU'R'");
+    assert!(cube.is_layer_solved(Face::D));
+}
+
+#[test]
+fn test_f2l_pairs_solved() {
+    let mut cube = Cube::new();
+    assert_eq!(cube.f2l_pairs_solved(), 4);
+
+    // U doesn't touch the first two layers
+    cube.apply_moves("U");
+    assert_eq!(cube.f2l_pairs_solved(), 4);
+
+    // Take pairs out of their slots one at a time
+    cube.apply_moves("RUR'");
+    assert_eq!(cube.f2l_pairs_solved(), 3);
+    assert!(cube.satisfies(&Goal::Keyhole));
+    cube.apply_moves("L'U'L");
+    assert_eq!(cube.f2l_pairs_solved(), 2);
+}
+
+#[test]
+fn test_apply_pattern() {
+    let mut c = Cube::new();
+    c.apply_moves("FRU");
+
+    assert_eq!(apply_pattern(&c, "superflip"), Ok(superflip(&c)));
+    assert_eq!(apply_pattern(&c, "checkerboard"), Ok(checkerboard(&c)));
+    assert_eq!(apply_pattern(&c, "cube_in_cube"), Ok(cube_in_cube(&c)));
+    assert_eq!(apply_pattern(&c, "six_spot"), Ok(six_spot(&c)));
+    assert_eq!(apply_pattern(&Cube::new(), "four_spot"), Ok(four_spot_cube()));
+
+    for name in list_patterns() {
+        assert!(!apply_pattern(&Cube::new(), name).unwrap().is_solved(), "{}", name);
+    }
+}
+
+#[test]
+fn test_apply_pattern_unknown() {
+    let err = apply_pattern(&Cube::new(), "Superflip").unwrap_err();
+    assert_eq!(err, PatternError { name: "Superflip".to_string() });
+
+    let message = err.to_string();
+    for name in list_patterns() {
+        assert!(message.contains(name), "{}", message);
+    }
+}
+
+#[test]
+fn test_permutation_and_orientation_solved() {
+    let solved = Cube::new();
+    assert!(solved.permutation_solved());
+    assert!(solved.orientation_solved());
+
+    let flipped = superflip_cube();
+    assert!(flipped.permutation_solved());
+    assert!(!flipped.orientation_solved());
+
+    // Half turns and U/D turns move pieces without changing orientations
+    let mut permuted = Cube::new();
+    permuted.apply_moves("R2UF2D'");
+    assert!(!permuted.permutation_solved());
+    assert!(permuted.orientation_solved());
+
+    let mut scrambled = Cube::new();
+    scrambled.apply_moves("FR");
+    assert!(!scrambled.permutation_solved());
+    assert!(!scrambled.orientation_solved());
+}
+
+#[test]
+fn test_all_stickers() {
+    let solved = Cube::new().all_stickers();
+    for (i, &face) in FACES.iter().enumerate() {
+        assert_eq!(solved[i * 9..(i + 1) * 9], [face; 9]);
+    }
+
+    let mut cube = Cube::new();
+    cube.apply_moves("FRUB'L2D");
+    let stickers = cube.all_stickers();
+    for (i, &face) in FACES.iter().enumerate() {
+        assert_eq!(stickers[i * 9..(i + 1) * 9], cube.get_face(face), "{:?}", face);
+    }
+    for &face in &FACES {
+        assert_eq!(stickers.iter().filter(|&&f| f == face).count(), 9);
+    }
+}
+
+#[test]
+fn test_apply_moves_whitespace() {
+    let mut expected = Cube::new();
+    expected.apply_moves("RUR'U'");
+
+    for &moves in &["R U R' U'", "R  U\tR'   U'", "  RUR'U'\n", "\tR U\r\nR' U' ", "(R U R' U')", "( R U R' U' )1"] {
+        let mut cube = Cube::new();
+        cube.apply_moves(moves);
+        assert_eq!(cube, expected, "{:?}", moves);
+    }
+
+    let mut cube = Cube::new();
+    cube.apply_moves("(R U R' U')x6");
+    assert!(cube.is_solved());
+}
+
+#[test]
+fn test_apply_moves_whitespace_errors() {
+    // Suffixes have to be attached to their moves
+    assert_eq!(parse_moves("R '"), Err(MoveParseError { character: '\'', offset: 2 }));
+    assert_eq!(parse_moves("R 2"), Err(MoveParseError { character: '2', offset: 2 }));
+    assert_eq!(parse_moves("(R U) 2"), Err(MoveParseError { character: '2', offset: 6 }));
+
+    // Offsets still point into the original string
+    assert_eq!(parse_moves("  R X"), Err(MoveParseError { character: 'X', offset: 4 }));
+
+    let mut cube = Cube::new();
+    assert_eq!(cube.apply_move_str(" R' "), Ok(()));
+    assert_eq!(cube.apply_move_str("R U"), Err(MoveParseError { character: 'U', offset: 2 }));
+}
+
+#[test]
+fn test_apply_moves_lowercase() {
+    // Lowercase faces are wide moves, and nothing else is
+    assert_eq!(parse_moves("r u'").unwrap(), parse_moves("Rw Uw'").unwrap());
+    assert_eq!(parse_moves("R x"), Err(MoveParseError { character: 'x', offset: 2 }));
+    assert_eq!(parse_moves("m"), Err(MoveParseError { character: 'm', offset: 0 }));
+}
+
+#[test]
+fn test_scramble_with_rng() {
+    use rand::{SeedableRng, XorShiftRng};
+
+    let seed = [2018, 12, 25, 7];
+    let mut a = Cube::new();
+    let a_moves = a.scramble_with_rng(&mut XorShiftRng::from_seed(seed), 25);
+    let mut b = Cube::new();
+    let b_moves = b.scramble_with_rng(&mut XorShiftRng::from_seed(seed), 25);
+
+    assert_eq!(a_moves.len(), 25);
+    assert_eq!(a_moves, b_moves);
+    assert_eq!(a, b);
+
+    // The moves returned are the ones that were applied
+    let mut c = Cube::new();
+    for &m in &a_moves {
+        c.apply_move(m);
+    }
+    assert_eq!(a, c);
+
+    // A different seed gives a different scramble
+    let mut d = Cube::new();
+    assert_ne!(d.scramble_with_rng(&mut XorShiftRng::from_seed([1, 2, 3, 4]), 25), a_moves);
+}
+
+#[test]
+fn test_with_moves() {
+    let cube = Cube::new().with_moves("FR").with_moves("U'");
+    let mut expected = Cube::new();
+    expected.apply_moves("FRU'");
+    assert_eq!(cube, expected);
+
+    // The original cube is copied, not changed
+    let base = Cube::new();
+    let _ = base.with_moves("F");
+    assert!(base.is_solved());
+}
+
+#[test]
+fn test_to_colors_solved() {
+    let colors = Cube::new().to_colors(&ColorScheme::default());
+    let expected = [Color::White, Color::Red, Color::Green, Color::Yellow, Color::Orange, Color::Blue];
+    for (i, &color) in expected.iter().enumerate() {
+        assert_eq!(colors[i * 9..(i + 1) * 9], [color; 9]);
+    }
+}
+
+#[test]
+fn test_to_colors_custom_scheme() {
+    let scheme = ColorScheme { u: Color::Yellow, d: Color::White, ..ColorScheme::western() };
+    let mut cube = Cube::new();
+    cube.apply_moves("FRU");
+
+    let colors = cube.to_colors(&scheme);
+    for (&color, &face) in colors.iter().zip(cube.all_stickers().iter()) {
+        assert_eq!(color, scheme.color(face));
+    }
+    assert_eq!(colors[4], Color::Yellow);
+    assert_eq!(colors[31], Color::White);
+}
+
+#[test]
+fn test_oll_case() {
+    for (i, alg) in OLL_ALGORITHMS.iter().enumerate() {
+        let mut cube = Cube::new().with_moves_inverted(alg);
+        assert!(cube.satisfies(&Goal::F2L), "OLL {} breaks F2L", i + 1);
+        for _ in 0..4 {
+            assert_eq!(cube.oll_case(), Some(i as u8 + 1));
+            cube.apply_move(Move::U);
+        }
+    }
+
+    // Every way to orient the last layer except the solved one has a case
+    assert_eq!(last_layer_tables().oll.len(), 215);
+
+    // The dot cases have no edges oriented and the cross cases all of them
+    let edges_oriented = |case: u8| {
+        let pattern = oll_pattern(&Cube::new().with_moves_inverted(OLL_ALGORITHMS[case as usize - 1]));
+        [1, 3, 5, 7].iter().filter(|&&b| pattern & (1 << b) != 0).count()
+    };
+    for &case in [1, 2, 3, 4, 17, 18, 19, 20].iter() {
+        assert_eq!(edges_oriented(case), 0);
+    }
+    for case in 21..28 {
+        assert_eq!(edges_oriented(case), 4);
+    }
+}
+
+#[test]
+fn test_oll_case_none() {
+    assert_eq!(Cube::new().oll_case(), None);
+
+    let mut cube = Cube::new();
+    cube.apply_moves("R");
+    assert_eq!(cube.oll_case(), None);
+
+    // The last layer is oriented but not permuted
+    let mut cube = Cube::new();
+    cube.apply_moves("RUR'U'R'FR2U'R'U'RUR'F'");
+    assert_eq!(cube.oll_case(), None);
+}
+
+#[test]
+fn test_pll_case() {
+    for &(name, alg) in PLL_ALGORITHMS.iter() {
+        let case = Cube::new().with_moves_inverted(alg);
+        assert!(case.satisfies(&Goal::OrientedLastLayer), "{} perm breaks OLL", name);
+
+        let mut before = Cube::new();
+        for _ in 0..4 {
+            let mut cube = before.compose(&case);
+            for _ in 0..4 {
+                assert_eq!(cube.pll_case(), Some(name));
+                cube.apply_move(Move::U);
+            }
+            before.apply_move(Move::U);
+        }
+    }
+
+    // Every way to permute the last layer except the four U turns has a case
+    assert_eq!(last_layer_tables().pll.len(), 284);
+}
+
+#[test]
+fn test_pll_case_none() {
+    let mut cube = Cube::new();
+    for _ in 0..4 {
+        assert_eq!(cube.pll_case(), None);
+        cube.apply_move(Move::U);
+    }
+
+    let mut cube = Cube::new();
+    cube.apply_moves("RUR'U'");
+    assert_eq!(cube.pll_case(), None);
+
+    let mut cube = Cube::new();
+    cube.apply_moves("RUR'URU2R'");
+    assert_eq!(cube.pll_case(), None);
+}
+
+#[test]
+fn test_move_u8_round_trip() {
+    for (i, &m) in MOVES.iter().enumerate() {
+        assert_eq!(u8::from(m), i as u8);
+        assert_eq!(Move::from_u8(u8::from(m)), Some(m));
+    }
+    assert_eq!(Move::from_u8(24), None);
+    assert_eq!(Move::from_u8(255), None);
+}
+
+#[test]
+fn test_superflip_composed() {
+    let mut c = Cube::new();
+    c.apply_moves("FRUL'D2");
+    assert_eq!(c.superflip_composed(), superflip_cube().compose(&c));
+    assert_eq!(c.superflip_composed().superflip_composed(), c);
+}
+
+#[test]
+fn test_generate_hard_scramble() {
+    use solver::{IDAStarSolver, Solver};
+
+    for seed in 0..3 {
+        let cube = generate_hard_scramble(seed);
+        assert_eq!(cube, generate_hard_scramble(seed));
+        assert_eq!(IDAStarSolver::with_max_depth(8).find_solution(&cube), vec![]);
+    }
+    assert_ne!(generate_hard_scramble(1), generate_hard_scramble(2));
+}
+
+#[test]
+fn test_sticker_editor() {
+    let mut scrambled = Cube::new();
+    scrambled.apply_moves("FRUL'D2");
+
+    let mut editor = scrambled.edit_stickers();
+    assert_eq!(editor.get_face(Face::F), scrambled.get_face(Face::F));
+    assert_eq!(editor.rebuild_from_stickers(), Ok(()));
+    assert_eq!(editor.cube(), &scrambled);
+
+    // Swap the two stickers on an edge, which flips it
+    let (a, b) = (editor.get_face(Face::U)[7], editor.get_face(Face::F)[1]);
+    editor.set_sticker(Face::U, 7, b);
+    editor.set_sticker(Face::F, 1, a);
+    assert_eq!(editor.rebuild_from_stickers(), Err(FaceletError::InvalidState(CubeError::Illegal)));
+    assert_eq!(editor.cube(), &scrambled);
+    assert_eq!(editor.get_face(Face::U)[7], b);
+
+    // Moving a center is caught too
+    editor.set_sticker(Face::U, 7, a);
+    editor.set_sticker(Face::F, 1, b);
+    editor.set_sticker(Face::U, 4, Face::D);
+    assert_eq!(editor.rebuild_from_stickers(), Err(FaceletError::InvalidFormat));
+}
+
+#[test]
+#[should_panic]
+fn test_sticker_editor_bad_index() {
+    Cube::new().edit_stickers().set_sticker(Face::U, 9, Face::U);
+}
+
+
+#[test]
+fn test_misplaced_stickers() {
+    assert_eq!(Cube::new().misplaced_stickers(), 0);
+
+    // Two columns of U, F, D, and B
+    let mut c = Cube::new();
+    c.apply_moves("RL");
+    assert_eq!(c.misplaced_stickers(), 24);
+
+    // The superflip leaves only the centers and corners in place
+    assert_eq!(superflip_cube().misplaced_stickers(), 24);
+
+    // Centers don't move, so a slice turn only moves edge stickers
+    let mut c = Cube::new();
+    c.apply_moves("M2");
+    assert_eq!(c.misplaced_stickers(), 8);
+}
+
+#[test]
+fn test_wca_round_trip() {
+    let scramble = "D2 F2 U' B2 R2 U' L2 D R2 U2 B2 R' B L' U' F' L2 R U2 F";
+    let mut cube = Cube::new();
+    assert_eq!(cube.import_wca(scramble), Ok(()));
+    assert_eq!(cube, Cube::new().with_moves(scramble));
+
+    let moves = parse_moves(scramble).unwrap();
+    assert_eq!(moves.len(), 20);
+    assert_eq!(export_wca(&moves), Ok(scramble.to_string()));
+
+    // Extra whitespace is normalized away
+    let mut spaced = Cube::new();
+    assert_eq!(spaced.import_wca("  D2 F2  U' B2 R2 U' L2 D R2 U2\nB2 R' B L' U' F' L2 R U2 F "), Ok(()));
+    assert_eq!(spaced, cube);
+}
+
+#[test]
+fn test_import_wca_errors() {
+    let errors = [
+        ("R Rw", 'w', 3),
+        ("R M2", 'M', 2),
+        ("R U F'2", '2', 6),
+        ("RU", 'U', 1),
+        ("r", 'r', 0),
+        ("R U`", '`', 3),
+        ("(R U)2", '(', 0),
+    ];
+    for &(scramble, character, offset) in errors.iter() {
+        let mut cube = Cube::new();
+        assert_eq!(cube.import_wca(scramble), Err(MoveParseError { character, offset }), "{}", scramble);
+        assert!(cube.is_solved());
+    }
+    assert_eq!(Cube::new().import_wca(""), Ok(()));
+}
+
+#[test]
+fn test_export_wca_slice_moves() {
+    assert_eq!(export_wca(&[Move::R, Move::MPrime]), Err(CubeError::SliceMove(Move::MPrime)));
+    assert_eq!(export_wca(&[Move::E, Move::S]), Err(CubeError::SliceMove(Move::E)));
+    assert_eq!(export_wca(&[]), Ok(String::new()));
+}
+
+#[test]
+fn test_apply_algorithm_pll() {
+    // The T perm swaps the UL and UR edges and the URF and UBR corners
+    let mut cube = Cube::new();
+    let effect = cube.apply_algorithm("RUR'U'R'FR2U'R'U'RUR'F'");
+    assert_eq!(effect.corners, vec![vec![Corner::URF, Corner::UBR]]);
+    assert_eq!(effect.edges, vec![vec![Edge::UR, Edge::UL]]);
+    assert_eq!(cube.pll_case(), Some("T"));
+
+    // The U perm cycles three edges and nothing else
+    let effect = Cube::new().apply_algorithm("M2UMU2M'UM2");
+    assert!(effect.corners.is_empty());
+    assert_eq!(effect.edges.len(), 1);
+    assert_eq!(effect.edges[0].len(), 3);
+}
+
+#[test]
+fn test_apply_algorithm_ignores_state() {
+    let mut scrambled = Cube::new();
+    scrambled.apply_moves("FRUL'D2");
+
+    let mut cube = scrambled;
+    let effect = cube.apply_algorithm("RUR'U'");
+    assert_eq!(effect, Cube::new().apply_algorithm("RUR'U'"));
+    assert_eq!(cube, scrambled.with_moves("RUR'U'"));
+
+    // Twisting corners in place doesn't move them
+    let effect = Cube::new().apply_algorithm("(R'D'RD)2U(R'D'RD)4U'");
+    assert!(effect.corners.is_empty());
+    assert!(effect.edges.is_empty());
+}
+
+#[test]
+fn test_rotated_matches_rotated_moves() {
+    let moves = parse_moves("RUR'U'MFD2S'B'L").unwrap();
+    let mut cube = Cube::new();
+    moves.iter().for_each(|&m| cube.apply_move(m));
+
+    for rotation in &cube_rotations() {
+        let mut expected = Cube::new();
+        moves.iter().flat_map(|&m| rotate_move(m, rotation)).for_each(|m| expected.apply_move(m));
+        assert_eq!(cube.rotated(rotation), expected);
+    }
+}
+
+#[test]
+fn test_equivalent_up_to_rotation() {
+    let mut scrambled = Cube::new();
+    scrambled.apply_moves("FRUL'D2B");
+    assert!(scrambled.equivalent_up_to_rotation(&scrambled));
+
+    // Holding the cube turned a quarter turn like U relabels F R B L as L F R B
+    let mut turned = Cube::new();
+    turned.apply_moves("LFUB'D2R");
+    assert!(scrambled.equivalent_up_to_rotation(&turned));
+    assert!(turned.equivalent_up_to_rotation(&scrambled));
+
+    let mut different = Cube::new();
+    different.apply_moves("FRUL'D2B'");
+    assert!(!scrambled.equivalent_up_to_rotation(&different));
+
+    // A mirror image isn't a rotation
+    let mut mirrored = Cube::new();
+    mirrored.apply_moves("F'L'U'RD2B'");
+    assert!(!scrambled.equivalent_up_to_rotation(&mirrored));
+}
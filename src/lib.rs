@@ -2,6 +2,12 @@
 // Copyright Peter Beard, licensed under the GPLv3. See LICENSE for details.
 //! A library for working with Rubik's cubes.
 extern crate rand;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(all(test, feature = "serde"))]
+extern crate serde_json;
 
 pub mod cube;
 pub mod solver;
+#[cfg(feature = "bench_fixtures")]
+pub mod bench_fixtures;
@@ -2,12 +2,71 @@
 // Copyright Peter Beard, licensed under the GPLv3. See LICENSE for details.
 //
 //! Algorithms for solving Rubik's cubes
-use super::cube::{Cube, Move};
+use super::cube::{Cube, Goal, Layer, Metric, Move};
+
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "parallel")]
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Every quarter turn, in the order the search tries them
+const ALL_MOVES: [Move; 12] = [
+    Move::F,
+    Move::R,
+    Move::U,
+    Move::B,
+    Move::L,
+    Move::D,
+    Move::FPrime,
+    Move::RPrime,
+    Move::UPrime,
+    Move::BPrime,
+    Move::LPrime,
+    Move::DPrime,
+];
 
 /// Trait for things that can solve Rubik's cubes
 pub trait Solver {
     /// Calculate a sequence of moves that puts the cube in the solved state
     fn find_solution(&mut self, cube: &Cube) -> Vec<Move>;
+
+    /// Calculate a sequence of moves that puts the cube in the solved state,
+    /// calling `on_solution` with each solution as soon as it is found. The
+    /// best solution is returned at the end.
+    ///
+    /// By default `on_solution` is called once with the final solution (if
+    /// there is one). Solvers that keep improving on their first solution
+    /// should override this to report each improvement.
+    fn find_solution_streaming(&mut self, cube: &Cube, on_solution: &mut dyn FnMut(&[Move])) -> Vec<Move> {
+        let solution = self.find_solution(cube);
+        if solves(cube, &solution) {
+            on_solution(&solution);
+        }
+
+        solution
+    }
+
+    /// Calculate a sequence of moves that puts the cube in the solved state,
+    /// calling `on_progress` with the search depth and the number of cube
+    /// states visited so far whenever the search goes a level deeper.
+    ///
+    /// By default there's no progress to report, so `on_progress` is never
+    /// called. Solvers that search depth by depth should override this.
+    fn find_solution_with_progress(&mut self, cube: &Cube, _on_progress: &mut dyn FnMut(u8, u64)) -> Vec<Move> {
+        self.find_solution(cube)
+    }
+
+    /// Estimate the number of moves needed to solve the cube. This must never
+    /// be more than the real number of moves for searches to stay optimal.
+    ///
+    /// The default estimate is zero, which is always safe but doesn't help.
+    fn lower_bound(&self, _cube: &Cube) -> u8 {
+        0
+    }
 }
 
 /// Solver that doesn't do anything
@@ -24,6 +83,12 @@ pub trait Solver {
 /// ```
 pub struct NullSolver;
 
+impl Default for NullSolver {
+    fn default() -> NullSolver {
+        NullSolver
+    }
+}
+
 impl NullSolver {
     pub fn new() -> NullSolver {
         NullSolver
@@ -56,14 +121,57 @@ impl Solver for NullSolver {
 /// ```
 pub struct IDSolver {
     max_depth: u8,
+    goal: Goal,
+    moves: Vec<Move>,
+    timeout: Option<Duration>,
+    timed_out: bool,
+    node_budget: Option<u64>,
+    budget_exceeded: bool,
+}
+
+impl Default for IDSolver {
+    /// The default solver uses the half turn metric, with a maximum depth of 20
+    /// (all cubes are solveable in at most 20 moves)
+    fn default() -> IDSolver {
+        IDSolver::with_metric(Metric::HalfTurn)
+    }
 }
 
 impl IDSolver {
-    /// Create a new solver with the default maximum depth of 26
-    /// (all cubes are solveable in at most 26 moves)
+    /// Create a new solver using the half turn metric, with the default
+    /// maximum depth of 20 (all cubes are solveable in at most 20 moves)
     pub fn new() -> IDSolver {
+        IDSolver::default()
+    }
+
+    /// Create a solver whose solutions are optimal in the given metric. It
+    /// only uses the moves that count as one move in that metric, and gives
+    /// up once solutions would be longer than any cube needs.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::{Cube, Metric};
+    /// use rubik::solver::IDSolver;
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("R2");
+    ///
+    /// assert_eq!(c.solve(&mut IDSolver::with_metric(Metric::QuarterTurn)).len(), 2);
+    /// ```
+    pub fn with_metric(metric: Metric) -> IDSolver {
+        let moves = match metric {
+            Metric::HalfTurn => FACE_TURNS.to_vec(),
+            Metric::QuarterTurn => ALL_MOVES.to_vec(),
+        };
+
         IDSolver {
-            max_depth: 26u8,
+            max_depth: metric.gods_number(),
+            goal: Goal::Solved,
+            moves,
+            timeout: None,
+            timed_out: false,
+            node_budget: None,
+            budget_exceeded: false,
         }
     }
 
@@ -71,75 +179,1493 @@ impl IDSolver {
     pub fn with_max_depth(d: u8) -> IDSolver {
         IDSolver {
             max_depth: d,
+            ..IDSolver::default()
+        }
+    }
+
+    /// Create a solver that stops as soon as the cube reaches the given goal
+    /// instead of solving it completely
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::{Cube, Goal};
+    /// use rubik::solver::IDSolver;
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("UR'");
+    /// c.solve(&mut IDSolver::with_goal(Goal::F2L));
+    ///
+    /// assert!(c.satisfies(&Goal::F2L));
+    /// assert!(!c.is_solved());
+    /// ```
+    pub fn with_goal(goal: Goal) -> IDSolver {
+        IDSolver {
+            goal,
+            ..IDSolver::default()
+        }
+    }
+
+    /// Create a solver that gives up after visiting `n` cube states, so it
+    /// can't run for too long on hard scrambles. If the budget runs out the
+    /// solver returns no moves and `budget_exceeded` is true.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::Cube;
+    /// use rubik::solver::{IDSolver, Solver};
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("FRUBLD");
+    ///
+    /// let mut solver = IDSolver::with_node_budget(100);
+    /// assert_eq!(solver.find_solution(&c), vec![]);
+    /// assert!(solver.budget_exceeded());
+    /// ```
+    pub fn with_node_budget(n: u64) -> IDSolver {
+        IDSolver {
+            node_budget: Some(n),
+            ..IDSolver::default()
         }
     }
+
+    /// Determine whether the last search gave up because it visited as many
+    /// cube states as its node budget allows
+    pub fn budget_exceeded(&self) -> bool {
+        self.budget_exceeded
+    }
+
+    /// Find every solution with at most `max_len` moves, shortest first, using
+    /// the solver's goal and moves. Solutions that turn opposite faces in both
+    /// orders are left out, since there's always an equivalent solution that
+    /// doesn't, so each solution is listed once. Which turns of the same face
+    /// in a row are left out depends on the moves: with half turns available
+    /// (`Metric::HalfTurn`), a face is never turned twice in a row, but with
+    /// only quarter turns (`Metric::QuarterTurn`) `F F` is kept since it's the
+    /// only way to turn F twice. Solutions stop as soon as the goal is reached.
+    /// A solved cube has one solution with no moves.
+    ///
+    /// The timeout and node budget apply to the whole search; if either runs
+    /// out, the solutions found so far are returned.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::{Cube, Move};
+    /// use rubik::solver::IDSolver;
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("FR");
+    ///
+    /// let solutions = IDSolver::new().find_all_solutions(&c, 2);
+    /// assert_eq!(solutions, vec![vec![Move::RPrime, Move::FPrime]]);
+    /// ```
+    pub fn find_all_solutions(&mut self, cube: &Cube, max_len: u8) -> Vec<Vec<Move>> {
+        let mut limits = Limits {
+            deadline: self.timeout.map(|t| Instant::now() + t),
+            nodes_left: self.node_budget,
+            nodes: 0,
+        };
+        self.timed_out = false;
+        self.budget_exceeded = false;
+
+        let mut solutions = Vec::new();
+        if cube.satisfies(&self.goal) {
+            solutions.push(Vec::new());
+        } else {
+            collect_solutions(*cube, max_len, self.goal, &self.moves, &mut Vec::new(), &mut limits, &mut solutions);
+            self.timed_out = past(limits.deadline);
+            self.budget_exceeded = limits.nodes_left == Some(0);
+        }
+
+        // The search finds solutions in move order; keep that within each length
+        solutions.sort_by_key(|s| s.len());
+        solutions
+    }
 }
 
 impl Solver for IDSolver {
+    /// Every face turn moves four corners and four edges, so at least a
+    /// quarter of the unsolved pieces of each kind need a move. Partial goals
+    /// don't need every piece solved, so they get no estimate.
+    fn lower_bound(&self, cube: &Cube) -> u8 {
+        if self.goal != Goal::Solved {
+            return 0;
+        }
+
+        let (corners, edges) = cube.unsolved_pieces();
+        corners.div_ceil(4).max(edges.div_ceil(4)) as u8
+    }
+
     fn find_solution(&mut self, cube: &Cube) -> Vec<Move> {
+        self.search(cube, &mut |_, _| {}, &mut |_| {})
+    }
+
+    /// Reports the solution as soon as the deepening search finds it, which is
+    /// the shortest one, so there's never more than one
+    fn find_solution_streaming(&mut self, cube: &Cube, on_solution: &mut dyn FnMut(&[Move])) -> Vec<Move> {
+        self.search(cube, &mut |_, _| {}, on_solution)
+    }
+
+    /// Reports each depth before searching it
+    fn find_solution_with_progress(&mut self, cube: &Cube, on_progress: &mut dyn FnMut(u8, u64)) -> Vec<Move> {
+        self.search(cube, on_progress, &mut |_| {})
+    }
+}
+
+impl IDSolver {
+    /// Search deeper and deeper for a solution, calling `on_progress` before
+    /// each depth and `on_solution` with the solution once it's found
+    fn search(&mut self, cube: &Cube, on_progress: &mut dyn FnMut(u8, u64), on_solution: &mut dyn FnMut(&[Move])) -> Vec<Move> {
         let mut current_solution: Option<Vec<Move>> = None;
         let mut current_depth = 1;
+        let mut limits = Limits {
+            deadline: self.timeout.map(|t| Instant::now() + t),
+            nodes_left: self.node_budget,
+            nodes: 0,
+        };
+        self.timed_out = false;
+        self.budget_exceeded = false;
 
         // A solved cube requires zero moves to solve
-        if !cube.is_solved() {
+        if !cube.satisfies(&self.goal) {
             // Look until we find a solution or run out of moves
             while current_depth <= self.max_depth && current_solution.is_none() {
-                current_solution = dbsearch(cube, current_depth);
+                on_progress(current_depth, limits.nodes);
+                current_solution = dbsearch(*cube, current_depth, self.goal, &self.moves, &mut Vec::new(), &mut limits);
                 current_depth += 1;
+
+                if let Some(ref solution) = current_solution {
+                    on_solution(solution);
+                } else if limits.exhausted() {
+                    self.timed_out = past(limits.deadline);
+                    self.budget_exceeded = limits.nodes_left == Some(0);
+                    break;
+                }
             }
+        } else {
+            on_solution(&[]);
         }
         // Return no moves if there's no solution within the max depth
-        if let Some(s) = current_solution {
-            s
-        } else {
-            vec![]
+        current_solution.unwrap_or_default()
+    }
+}
+
+/// Determine whether a deadline has passed
+fn past(deadline: Option<Instant>) -> bool {
+    match deadline {
+        Some(d) => Instant::now() >= d,
+        None => false,
+    }
+}
+
+/// The limits on how much work a search can do before giving up
+struct Limits {
+    deadline: Option<Instant>,
+    /// How many more cube states the search can visit, if it's limited
+    nodes_left: Option<u64>,
+    /// How many cube states the search has visited
+    nodes: u64,
+}
+
+impl Limits {
+    /// Determine whether the search has to stop
+    fn exhausted(&self) -> bool {
+        past(self.deadline) || self.nodes_left == Some(0)
+    }
+
+    /// Count a visit to a cube state, returning false if there was no budget
+    /// left for it
+    fn visit(&mut self) -> bool {
+        match self.nodes_left {
+            Some(0) => return false,
+            Some(ref mut n) => *n -= 1,
+            None => {},
         }
+        self.nodes += 1;
+        true
     }
+}
 
+/// Depth-bounded search for every solution, like `dbsearch`. Each solution
+/// found is added to `solutions`.
+fn collect_solutions(start: Cube, maxdepth: u8, goal: Goal, possible_moves: &[Move], path: &mut Vec<Move>, limits: &mut Limits, solutions: &mut Vec<Vec<Move>>) {
+    if maxdepth == 0 || limits.exhausted() {
+        return;
+    }
+
+    for &m in possible_moves {
+        if redundant(path, m, possible_moves) {
+            continue;
+        }
+        if !limits.visit() {
+            return;
+        }
+
+        let mut s = start;
+        s.apply_move(m);
+        path.push(m);
+        if s.satisfies(&goal) {
+            solutions.push(path.clone());
+        } else {
+            collect_solutions(s, maxdepth - 1, goal, possible_moves, path, limits, solutions);
+        }
+        path.pop();
+    }
 }
 
-/// Depth-bounded search for a solution
-fn dbsearch(start: &Cube, maxdepth: u8) -> Option<Vec<Move>> {
+/// Depth-bounded search for a solution. `path` holds the moves made so far,
+/// which are used to skip moves that can't lead anywhere new.
+fn dbsearch(start: Cube, maxdepth: u8, goal: Goal, possible_moves: &[Move], path: &mut Vec<Move>, limits: &mut Limits) -> Option<Vec<Move>> {
     // Zero means we're at the max depth
-    if maxdepth == 0 {
+    if maxdepth == 0 || limits.exhausted() {
         return None;
     }
 
-    let possible_moves = [
-        Move::F,
-        Move::R,
-        Move::U,
-        Move::B,
-        Move::L,
-        Move::D,
-        Move::FPrime,
-        Move::RPrime,
-        Move::UPrime,
-        Move::BPrime,
-        Move::LPrime,
-        Move::DPrime,
-    ];
     let mut moves = Vec::new();
     // Try every possible move and see where we get
-    for &m in &possible_moves {
-        let mut s = start.clone();
+    for &m in possible_moves {
+        if redundant(path, m, possible_moves) {
+            continue;
+        }
+        if !limits.visit() {
+            break;
+        }
+
+        let mut s = start;
         s.apply_move(m);
         moves.push(m);
 
-        if s.is_solved() {
+        if s.satisfies(&goal) {
             break;
         }
 
-        if let Some(ms) = dbsearch(&s, maxdepth - 1) {
-            moves.append(&mut ms.clone());
+        path.push(m);
+        let found = dbsearch(s, maxdepth - 1, goal, possible_moves, path, limits);
+        path.pop();
+
+        if let Some(ms) = found {
+            moves.extend(ms);
             break;
         } else {
             moves.pop();
         }
     }
-    if moves.len() > 0 {
+    if !moves.is_empty() {
         Some(moves)
     } else {
         None
     }
 }
+
+/// Solver that uses iterative deepening A* (IDA*) with pattern databases
+///
+/// The search is guided by two tables of exact distances: one for the
+/// orientations of all of the corners and edges together, and one for the
+/// positions of the corners. Neither ever overestimates the number of moves
+/// left, so the solutions found are optimal. The tables take a few seconds to
+/// compute the first time any `IDAStarSolver` is used and are shared after that.
+///
+/// # Example
+/// ```
+/// use rubik::cube::Cube;
+/// use rubik::solver::IDAStarSolver;
+///
+/// let mut c = Cube::new();
+/// let mut solver = IDAStarSolver::new();
+///
+/// c.apply_moves("FRUBLDF'R'");
+/// assert!(c.solve(&mut solver).len() <= 8);
+/// assert!(c.is_solved());
+/// ```
+pub struct IDAStarSolver {
+    max_depth: u8,
+}
+
+impl Default for IDAStarSolver {
+    /// The default solver has a maximum depth of 26
+    /// (all cubes are solveable in at most 26 moves)
+    fn default() -> IDAStarSolver {
+        IDAStarSolver {
+            max_depth: 26u8,
+        }
+    }
+}
+
+impl IDAStarSolver {
+    /// Create a new solver with the default maximum depth of 26
+    /// (all cubes are solveable in at most 26 moves)
+    pub fn new() -> IDAStarSolver {
+        IDAStarSolver::default()
+    }
+
+    /// Create a solver with the given maximum depth (max number of moves)
+    pub fn with_max_depth(d: u8) -> IDAStarSolver {
+        IDAStarSolver {
+            max_depth: d,
+        }
+    }
+}
+
+impl Solver for IDAStarSolver {
+    /// The larger of the exact distances to solve the orientations and to
+    /// solve the corner positions
+    fn lower_bound(&self, cube: &Cube) -> u8 {
+        pattern_tables().estimate(coordinates(cube))
+    }
+
+    fn find_solution(&mut self, cube: &Cube) -> Vec<Move> {
+        let tables = pattern_tables();
+        let start = coordinates(cube);
+        let mut bound = tables.estimate(start);
+        let mut path = Vec::new();
+
+        while bound <= self.max_depth {
+            let mut next_bound = u8::MAX;
+            if idasearch(tables, *cube, start, bound, &mut path, &mut next_bound, &AtomicBool::new(false)) {
+                return path;
+            }
+            bound = next_bound;
+        }
+
+        vec![]
+    }
+}
+
+/// Solver that does the same search as `IDAStarSolver`, but splits it across
+/// several threads. Each thread searches the cubes reached by some of the
+/// possible first moves, and once one finds a solution the rest stop. The
+/// solutions are optimal, so they're the same length as the ones
+/// `IDAStarSolver` finds, but they aren't necessarily the same moves.
+///
+/// Only available with the `parallel` feature.
+///
+/// # Example
+/// ```
+/// use rubik::cube::Cube;
+/// use rubik::solver::{IDAStarSolver, ParallelIDAStarSolver, Solver};
+///
+/// let mut c = Cube::new();
+/// c.apply_moves("FRUBLDF'R'");
+///
+/// let solution = ParallelIDAStarSolver::new().find_solution(&c);
+/// assert_eq!(solution.len(), IDAStarSolver::new().find_solution(&c).len());
+/// assert!(c.solve(&mut ParallelIDAStarSolver::new()).len() <= 8);
+/// assert!(c.is_solved());
+/// ```
+#[cfg(feature = "parallel")]
+pub struct ParallelIDAStarSolver {
+    max_depth: u8,
+    threads: usize,
+}
+
+#[cfg(feature = "parallel")]
+impl Default for ParallelIDAStarSolver {
+    /// The default solver has a maximum depth of 26 (all cubes are solveable
+    /// in at most 26 moves) and uses one thread for each available core
+    fn default() -> ParallelIDAStarSolver {
+        ParallelIDAStarSolver {
+            max_depth: 26u8,
+            threads: thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        }
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl ParallelIDAStarSolver {
+    /// Create a new solver with the default maximum depth of 26, using one
+    /// thread for each available core
+    pub fn new() -> ParallelIDAStarSolver {
+        ParallelIDAStarSolver::default()
+    }
+
+    /// Create a solver with the given maximum depth (max number of moves)
+    pub fn with_max_depth(d: u8) -> ParallelIDAStarSolver {
+        ParallelIDAStarSolver {
+            max_depth: d,
+            ..ParallelIDAStarSolver::default()
+        }
+    }
+
+    /// Use the given number of threads instead of one for each core. There's
+    /// always at least one thread, and no more than one for each first move.
+    ///
+    /// # Example
+    /// ```
+    /// use rubik::cube::Cube;
+    /// use rubik::solver::{ParallelIDAStarSolver, Solver};
+    ///
+    /// let mut c = Cube::new();
+    /// c.apply_moves("FRU");
+    ///
+    /// let mut solver = ParallelIDAStarSolver::new().with_threads(2);
+    /// assert_eq!(solver.find_solution(&c).len(), 3);
+    /// ```
+    pub fn with_threads(mut self, threads: usize) -> ParallelIDAStarSolver {
+        self.threads = threads.clamp(1, ALL_MOVES.len());
+        self
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl Solver for ParallelIDAStarSolver {
+    /// The same estimate as `IDAStarSolver`
+    fn lower_bound(&self, cube: &Cube) -> u8 {
+        pattern_tables().estimate(coordinates(cube))
+    }
+
+    fn find_solution(&mut self, cube: &Cube) -> Vec<Move> {
+        if cube.is_solved() {
+            return vec![];
+        }
+
+        let tables = pattern_tables();
+        let start = coordinates(cube);
+        let threads = self.threads.clamp(1, ALL_MOVES.len());
+        let mut bound = tables.estimate(start).max(1);
+
+        while bound <= self.max_depth {
+            let found = AtomicBool::new(false);
+            let results: Vec<(Option<Vec<Move>>, u8)> = thread::scope(|scope| {
+                let workers: Vec<_> = (0..threads).map(|first| {
+                    let found = &found;
+                    scope.spawn(move || {
+                        let mut next_bound = u8::MAX;
+                        for (i, &m) in ALL_MOVES.iter().enumerate().skip(first).step_by(threads) {
+                            let mut next = *cube;
+                            next.apply_move(m);
+                            let mut path = vec![m];
+                            if idasearch(tables, next, tables.apply(start, i), bound, &mut path, &mut next_bound, found) {
+                                found.store(true, Ordering::Relaxed);
+                                return (Some(path), next_bound);
+                            }
+                        }
+                        (None, next_bound)
+                    })
+                }).collect();
+                workers.into_iter().map(|w| w.join().unwrap()).collect()
+            });
+
+            let mut next_bound = u8::MAX;
+            for (solution, worker_bound) in results {
+                if let Some(solution) = solution {
+                    return solution;
+                }
+                next_bound = next_bound.min(worker_bound);
+            }
+            bound = next_bound;
+        }
+
+        vec![]
+    }
+}
+
+/// The number of values of `Cube::corner_orientation_coordinate`
+const CORNER_ORIENTATIONS: usize = 2187;
+/// The number of values of `Cube::edge_orientation_coordinate`
+const EDGE_ORIENTATIONS: usize = 2048;
+/// The number of values of `Cube::corner_permutation_coordinate`
+const CORNER_PERMUTATIONS: usize = 40320;
+
+/// The coordinates used to look up distances: corner orientation, edge
+/// orientation, and corner permutation
+type Coordinates = (u16, u16, u16);
+
+/// Get the coordinates of a cube
+fn coordinates(cube: &Cube) -> Coordinates {
+    (
+        cube.corner_orientation_coordinate(),
+        cube.edge_orientation_coordinate(),
+        cube.corner_permutation_coordinate(),
+    )
+}
+
+/// Move tables and distance tables for the IDA* heuristic
+struct PatternTables {
+    /// The corner orientation after each move in `ALL_MOVES`
+    corner_orientation_moves: Vec<[u16; 12]>,
+    /// The edge orientation after each move in `ALL_MOVES`
+    edge_orientation_moves: Vec<[u16; 12]>,
+    /// The corner permutation after each move in `ALL_MOVES`
+    corner_permutation_moves: Vec<[u16; 12]>,
+    /// The number of moves needed to orient every piece, indexed by
+    /// `corner orientation * EDGE_ORIENTATIONS + edge orientation`
+    orientation_distance: Vec<u8>,
+    /// The number of moves needed to put every corner in its home cubicle
+    corner_permutation_distance: Vec<u8>,
+}
+
+impl PatternTables {
+    /// Compute all of the tables. This is deterministic: the tables only
+    /// depend on how the moves act on the cube.
+    fn new() -> PatternTables {
+        let corner_orientation_moves = move_table(CORNER_ORIENTATIONS, Cube::corner_orientation_coordinate, &ALL_MOVES);
+        let edge_orientation_moves = move_table(EDGE_ORIENTATIONS, Cube::edge_orientation_coordinate, &ALL_MOVES);
+        let corner_permutation_moves = move_table(CORNER_PERMUTATIONS, Cube::corner_permutation_coordinate, &ALL_MOVES);
+
+        let orientation_distance = distance_table(&corner_orientation_moves, &edge_orientation_moves);
+        let corner_permutation_distance = distance_table(&corner_permutation_moves, &[[0; 12]]);
+
+        PatternTables {
+            corner_orientation_moves,
+            edge_orientation_moves,
+            corner_permutation_moves,
+            orientation_distance,
+            corner_permutation_distance,
+        }
+    }
+
+    /// Get the coordinates after applying the `m`th move in `ALL_MOVES`
+    fn apply(&self, (co, eo, cp): Coordinates, m: usize) -> Coordinates {
+        (
+            self.corner_orientation_moves[co as usize][m],
+            self.edge_orientation_moves[eo as usize][m],
+            self.corner_permutation_moves[cp as usize][m],
+        )
+    }
+
+    /// Get a lower bound on the number of moves needed to solve a cube with
+    /// the given coordinates
+    fn estimate(&self, (co, eo, cp): Coordinates) -> u8 {
+        let orientation = self.orientation_distance[co as usize * EDGE_ORIENTATIONS + eo as usize];
+        orientation.max(self.corner_permutation_distance[cp as usize])
+    }
+}
+
+/// Get the pattern tables, computing them the first time they're needed
+fn pattern_tables() -> &'static PatternTables {
+    static TABLES: OnceLock<PatternTables> = OnceLock::new();
+    TABLES.get_or_init(PatternTables::new)
+}
+
+/// Build a table of how each of the given moves changes a coordinate. Moves
+/// change each coordinate in a way that only depends on its current value, so
+/// any cube with a given value can stand in for all of them.
+fn move_table<C: Into<u16>, const N: usize>(size: usize, coordinate: fn(&Cube) -> C, moves: &[Move; N]) -> Vec<[u16; N]> {
+    let mut table = vec![[0u16; N]; size];
+    let mut seen = vec![false; size];
+    let mut queue = VecDeque::new();
+    seen[0] = true;
+    queue.push_back(Cube::new());
+
+    while let Some(cube) = queue.pop_front() {
+        let from = coordinate(&cube).into() as usize;
+        for (i, &m) in moves.iter().enumerate() {
+            let mut next = cube;
+            next.apply_move(m);
+            let to = coordinate(&next).into();
+            table[from][i] = to;
+            if !seen[to as usize] {
+                seen[to as usize] = true;
+                queue.push_back(next);
+            }
+        }
+    }
+    table
+}
+
+/// Find the number of moves from zero to every pair of values of two
+/// coordinates with a breadth-first search, given their move tables. The
+/// distances are indexed by `a * b_moves.len() + b`.
+fn distance_table<const N: usize>(a_moves: &[[u16; N]], b_moves: &[[u16; N]]) -> Vec<u8> {
+    let size = a_moves.len() * b_moves.len();
+    let next = |i: usize, m: usize| {
+        let a = a_moves[i / b_moves.len()][m] as usize;
+        let b = b_moves[i % b_moves.len()][m] as usize;
+        a * b_moves.len() + b
+    };
+    let mut distance = vec![u8::MAX; size];
+    let mut frontier = vec![0];
+    let mut depth = 0;
+    distance[0] = 0;
+
+    while !frontier.is_empty() {
+        depth += 1;
+        let mut next_frontier = Vec::new();
+        for i in frontier {
+            for m in 0..N {
+                let j = next(i, m);
+                if distance[j] == u8::MAX {
+                    distance[j] = depth;
+                    next_frontier.push(j);
+                }
+            }
+        }
+        frontier = next_frontier;
+    }
+    distance
+}
+
+/// Determine whether a move can be skipped after the moves already in a path
+/// because a shorter or equivalent path will be tried instead. Which paths
+/// are shorter depends on the moves available, e.g. `F F` is the only way to
+/// do a half turn of F if `F2` isn't one of `possible_moves`.
+fn redundant(path: &[Move], m: Move, possible_moves: &[Move]) -> bool {
+    let (axis, layer, turns) = m.rotation();
+    let (prev_axis, prev_layer, prev_turns) = match path.last() {
+        Some(prev) => prev.rotation(),
+        None => return false,
+    };
+
+    if prev_axis != axis {
+        false
+    } else if prev_layer == layer {
+        // Turning the same face twice in a row can always be done in one move
+        // if its half turn is available. Otherwise, skip undoing the last
+        // move, or turning the face three times when once the other way would do.
+        let half_turn = possible_moves.iter().any(|h| {
+            let (h_axis, h_layer, h_turns) = h.rotation();
+            h_axis == axis && h_layer == layer && h_turns.abs() == 2
+        });
+        half_turn || prev_turns != turns || (path.len() >= 2 && path[path.len() - 2] == m)
+    } else {
+        // Opposite faces commute, so only try them in one order
+        prev_layer == Layer::Negative
+    }
+}
+
+/// Depth-first search for a solution whose estimated length is within the
+/// bound. Returns true if a solution was found (it's left in `path`),
+/// otherwise `next_bound` is lowered to the smallest estimate over the bound.
+fn idasearch(tables: &PatternTables, cube: Cube, coords: Coordinates, bound: u8, path: &mut Vec<Move>, next_bound: &mut u8, stop: &AtomicBool) -> bool {
+    // Another search found a solution first
+    if stop.load(Ordering::Relaxed) {
+        return false;
+    }
+
+    let estimate = path.len() as u8 + tables.estimate(coords);
+    if estimate > bound {
+        *next_bound = (*next_bound).min(estimate);
+        return false;
+    }
+    // The tables don't cover the edge positions, so an estimate of zero
+    // doesn't mean the cube is solved
+    if cube.is_solved() {
+        return true;
+    }
+
+    for (i, &m) in ALL_MOVES.iter().enumerate() {
+        if redundant(path, m, &ALL_MOVES) {
+            continue;
+        }
+
+        let mut next = cube;
+        next.apply_move(m);
+        path.push(m);
+        if idasearch(tables, next, tables.apply(coords, i), bound, path, next_bound, stop) {
+            return true;
+        }
+        path.pop();
+    }
+    false
+}
+
+/// Solver that uses Kociemba's two-phase algorithm
+///
+/// The first phase moves the cube into the group generated by U, D, L2, R2,
+/// F2, and B2, where every piece is oriented and the E slice edges are in the
+/// E slice. The second phase solves the cube using only those moves. Each
+/// phase is searched with IDA* using pattern databases, and the solver keeps
+/// trying longer first phases until the total is short enough.
+///
+/// Solutions are counted in the half turn metric and aren't necessarily
+/// optimal, but they're found quickly and are usually around 20 moves. The
+/// tables take a few seconds to compute the first time any `TwoPhaseSolver`
+/// is used and are shared after that.
+///
+/// # Example
+/// ```
+/// use rubik::cube::Cube;
+/// use rubik::solver::TwoPhaseSolver;
+///
+/// let mut c = Cube::new();
+/// let mut solver = TwoPhaseSolver::new();
+///
+/// c.scramble_from_seed(2018, 25);
+/// assert!(c.solve(&mut solver).len() <= 24);
+/// assert!(c.is_solved());
+/// ```
+pub struct TwoPhaseSolver {
+    max_length: u8,
+}
+
+impl Default for TwoPhaseSolver {
+    /// The default solver stops at the first solution of 24 moves or fewer
+    fn default() -> TwoPhaseSolver {
+        TwoPhaseSolver {
+            max_length: 24u8,
+        }
+    }
+}
+
+impl TwoPhaseSolver {
+    /// Create a new solver that stops at the first solution of 24 moves or
+    /// fewer
+    pub fn new() -> TwoPhaseSolver {
+        TwoPhaseSolver::default()
+    }
+
+    /// Create a solver that stops at the first solution of at most the given
+    /// number of moves. Lower limits give shorter solutions but take longer
+    /// to find, and limits under 20 might not finish in a reasonable time.
+    pub fn with_max_length(n: u8) -> TwoPhaseSolver {
+        TwoPhaseSolver {
+            max_length: n,
+        }
+    }
+}
+
+impl Solver for TwoPhaseSolver {
+    fn find_solution(&mut self, cube: &Cube) -> Vec<Move> {
+        self.find_solution_streaming(cube, &mut |_| {})
+    }
+
+    /// Reports each solution that's shorter than the ones before it, until
+    /// one is short enough
+    fn find_solution_streaming(&mut self, cube: &Cube, on_solution: &mut dyn FnMut(&[Move])) -> Vec<Move> {
+        // Unreachable states would never get out of the first phase
+        if !cube.is_valid() {
+            return vec![];
+        }
+
+        let tables = two_phase_tables();
+        let start = (
+            cube.corner_orientation_coordinate(),
+            cube.edge_orientation_coordinate(),
+            cube.ud_slice_coordinate(),
+        );
+        let mut search = TwoPhaseSearch {
+            tables,
+            cube: *cube,
+            max_length: self.max_length,
+            path: Vec::new(),
+            best: None,
+            on_solution,
+        };
+
+        // Every cube can be moved into the subgroup in at most 12 moves
+        for depth in tables.phase_one_estimate(start)..=12 {
+            if search.done(depth) || search.phase_one(start, depth) {
+                break;
+            }
+        }
+
+        search.best.unwrap_or_default()
+    }
+}
+
+/// Every face turn in the half turn metric
+const FACE_TURNS: [Move; 18] = [
+    Move::F,
+    Move::R,
+    Move::U,
+    Move::B,
+    Move::L,
+    Move::D,
+    Move::FPrime,
+    Move::RPrime,
+    Move::UPrime,
+    Move::BPrime,
+    Move::LPrime,
+    Move::DPrime,
+    Move::F2,
+    Move::R2,
+    Move::U2,
+    Move::B2,
+    Move::L2,
+    Move::D2,
+];
+
+/// The moves that keep a cube in the second phase subgroup
+const PHASE_TWO_MOVES: [Move; 10] = [
+    Move::U,
+    Move::D,
+    Move::UPrime,
+    Move::DPrime,
+    Move::U2,
+    Move::D2,
+    Move::F2,
+    Move::R2,
+    Move::B2,
+    Move::L2,
+];
+
+/// The number of values of `Cube::ud_slice_coordinate`
+const UD_SLICES: usize = 495;
+/// The number of values of `Cube::ud_edge_permutation_coordinate`
+const UD_EDGE_PERMUTATIONS: usize = 40320;
+/// The number of values of `Cube::slice_edge_permutation_coordinate`
+const SLICE_EDGE_PERMUTATIONS: usize = 24;
+
+/// Move tables and distance tables for `TwoPhaseSolver`
+struct TwoPhaseTables {
+    /// The corner orientation after each move in `FACE_TURNS`
+    corner_orientation_moves: Vec<[u16; 18]>,
+    /// The edge orientation after each move in `FACE_TURNS`
+    edge_orientation_moves: Vec<[u16; 18]>,
+    /// The E slice edge cubicles after each move in `FACE_TURNS`
+    ud_slice_moves: Vec<[u16; 18]>,
+    /// The corner permutation after each move in `PHASE_TWO_MOVES`
+    corner_permutation_moves: Vec<[u16; 10]>,
+    /// The U and D edge permutation after each move in `PHASE_TWO_MOVES`
+    ud_edge_permutation_moves: Vec<[u16; 10]>,
+    /// The E slice edge permutation after each move in `PHASE_TWO_MOVES`
+    slice_edge_permutation_moves: Vec<[u16; 10]>,
+    /// The number of moves needed to orient the corners and put the E slice
+    /// edges in the E slice
+    corner_slice_distance: Vec<u8>,
+    /// The number of moves needed to orient the edges and put the E slice
+    /// edges in the E slice
+    edge_slice_distance: Vec<u8>,
+    /// The number of second phase moves needed to solve the corners and the
+    /// E slice edges
+    corner_permutation_distance: Vec<u8>,
+    /// The number of second phase moves needed to solve all of the edges
+    edge_permutation_distance: Vec<u8>,
+}
+
+impl TwoPhaseTables {
+    /// Compute all of the tables
+    fn new() -> TwoPhaseTables {
+        let corner_orientation_moves = move_table(CORNER_ORIENTATIONS, Cube::corner_orientation_coordinate, &FACE_TURNS);
+        let edge_orientation_moves = move_table(EDGE_ORIENTATIONS, Cube::edge_orientation_coordinate, &FACE_TURNS);
+        let ud_slice_moves = move_table(UD_SLICES, Cube::ud_slice_coordinate, &FACE_TURNS);
+        let corner_permutation_moves = move_table(CORNER_PERMUTATIONS, Cube::corner_permutation_coordinate, &PHASE_TWO_MOVES);
+        let ud_edge_permutation_moves = move_table(UD_EDGE_PERMUTATIONS, Cube::ud_edge_permutation_coordinate, &PHASE_TWO_MOVES);
+        let slice_edge_permutation_moves = move_table(SLICE_EDGE_PERMUTATIONS, Cube::slice_edge_permutation_coordinate, &PHASE_TWO_MOVES);
+
+        TwoPhaseTables {
+            corner_slice_distance: distance_table(&corner_orientation_moves, &ud_slice_moves),
+            edge_slice_distance: distance_table(&edge_orientation_moves, &ud_slice_moves),
+            corner_permutation_distance: distance_table(&corner_permutation_moves, &slice_edge_permutation_moves),
+            edge_permutation_distance: distance_table(&ud_edge_permutation_moves, &slice_edge_permutation_moves),
+            corner_orientation_moves,
+            edge_orientation_moves,
+            ud_slice_moves,
+            corner_permutation_moves,
+            ud_edge_permutation_moves,
+            slice_edge_permutation_moves,
+        }
+    }
+
+    /// Get a lower bound on the number of moves needed to reach the second
+    /// phase from the given corner orientation, edge orientation, and E slice
+    /// edge cubicles
+    fn phase_one_estimate(&self, (co, eo, slice): Coordinates) -> u8 {
+        let corners = self.corner_slice_distance[co as usize * UD_SLICES + slice as usize];
+        let edges = self.edge_slice_distance[eo as usize * UD_SLICES + slice as usize];
+        corners.max(edges)
+    }
+
+    /// Get a lower bound on the number of second phase moves needed to solve
+    /// a cube from the given corner, U and D edge, and E slice edge
+    /// permutations
+    fn phase_two_estimate(&self, (cp, ep, slice): Coordinates) -> u8 {
+        let corners = self.corner_permutation_distance[cp as usize * SLICE_EDGE_PERMUTATIONS + slice as usize];
+        let edges = self.edge_permutation_distance[ep as usize * SLICE_EDGE_PERMUTATIONS + slice as usize];
+        corners.max(edges)
+    }
+}
+
+/// Get the two-phase tables, computing them the first time they're needed
+fn two_phase_tables() -> &'static TwoPhaseTables {
+    static TABLES: OnceLock<TwoPhaseTables> = OnceLock::new();
+    TABLES.get_or_init(TwoPhaseTables::new)
+}
+
+/// The state of a two-phase search for a single cube
+struct TwoPhaseSearch<'a> {
+    tables: &'a TwoPhaseTables,
+    /// The cube being solved
+    cube: Cube,
+    /// The longest solution that's good enough to stop searching
+    max_length: u8,
+    /// The moves made so far
+    path: Vec<Move>,
+    /// The shortest solution found so far
+    best: Option<Vec<Move>>,
+    /// Called with each new shortest solution
+    on_solution: &'a mut dyn FnMut(&[Move]),
+}
+
+impl TwoPhaseSearch<'_> {
+    /// Determine whether there's no point searching first phases of the given
+    /// length, either because the current solution is good enough or because
+    /// any new solution would be at least as long
+    fn done(&self, phase_one_length: u8) -> bool {
+        match self.best {
+            Some(ref best) => best.len() <= self.max_length as usize || best.len() <= phase_one_length as usize,
+            None => false,
+        }
+    }
+
+    /// Search first phases of exactly `depth` more moves, finishing each one
+    /// with the second phase. Returns true once there's a good enough solution.
+    fn phase_one(&mut self, coords: Coordinates, depth: u8) -> bool {
+        if self.tables.phase_one_estimate(coords) > depth {
+            return false;
+        }
+        if depth == 0 {
+            // If the last move was a second phase move, the first phase
+            // already ended one move earlier and that's been searched
+            return match self.path.last() {
+                Some(m) if PHASE_TWO_MOVES.contains(m) => false,
+                _ => self.phase_two(),
+            };
+        }
+
+        let (co, eo, slice) = coords;
+        for (i, &m) in FACE_TURNS.iter().enumerate() {
+            if redundant(&self.path, m, &FACE_TURNS) {
+                continue;
+            }
+
+            let next = (
+                self.tables.corner_orientation_moves[co as usize][i],
+                self.tables.edge_orientation_moves[eo as usize][i],
+                self.tables.ud_slice_moves[slice as usize][i],
+            );
+            self.path.push(m);
+            let finished = self.phase_one(next, depth - 1);
+            self.path.pop();
+            if finished {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Find the shortest second phase that improves on the best solution so
+    /// far. Returns true if the best solution is now good enough.
+    fn phase_two(&mut self) -> bool {
+        let mut cube = self.cube;
+        for &m in &self.path {
+            cube.apply_move(m);
+        }
+        let coords = (
+            cube.corner_permutation_coordinate(),
+            cube.ud_edge_permutation_coordinate(),
+            cube.slice_edge_permutation_coordinate() as u16,
+        );
+
+        // The second phase never needs more than 18 moves
+        let phase_one_length = self.path.len();
+        let limit = match self.best {
+            Some(ref best) => (best.len() - 1).saturating_sub(phase_one_length).min(18),
+            None => 18,
+        };
+
+        for depth in self.tables.phase_two_estimate(coords)..=limit as u8 {
+            if self.phase_two_search(coords, depth) {
+                (self.on_solution)(&self.path);
+                self.best = Some(self.path.clone());
+                self.path.truncate(phase_one_length);
+                break;
+            }
+        }
+        self.done(phase_one_length as u8)
+    }
+
+    /// Search for a second phase of exactly `depth` more moves, leaving it in
+    /// `path` if there is one
+    fn phase_two_search(&mut self, coords: Coordinates, depth: u8) -> bool {
+        let estimate = self.tables.phase_two_estimate(coords);
+        if estimate > depth {
+            return false;
+        }
+        // The estimate is exact when it's zero: everything is solved
+        if estimate == 0 {
+            return depth == 0;
+        }
+
+        let (cp, ep, slice) = coords;
+        for (i, &m) in PHASE_TWO_MOVES.iter().enumerate() {
+            if redundant(&self.path, m, &FACE_TURNS) {
+                continue;
+            }
+
+            let next = (
+                self.tables.corner_permutation_moves[cp as usize][i],
+                self.tables.ud_edge_permutation_moves[ep as usize][i],
+                self.tables.slice_edge_permutation_moves[slice as usize][i],
+            );
+            self.path.push(m);
+            if self.phase_two_search(next, depth - 1) {
+                return true;
+            }
+            self.path.pop();
+        }
+        false
+    }
+}
+
+/// The solving algorithm used by a `SolverConfig`
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Method {
+    /// Don't solve the cube at all (see `NullSolver`)
+    Null,
+    /// Uninformed iterative deepening (see `IDSolver`)
+    IterativeDeepening,
+    /// Iterative deepening A* with pattern databases (see `IDAStarSolver`)
+    IDAStar,
+    /// Kociemba's two-phase algorithm (see `TwoPhaseSolver`)
+    TwoPhase,
+}
+
+/// Reasons a configured solver can fail to solve a cube
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SolveError {
+    /// No solution exists within the maximum depth using the allowed moves
+    NotFound,
+    /// The solver ran out of time before finding a solution
+    TimedOut,
+    /// The solver visited as many cube states as it was allowed to without
+    /// finding a solution
+    BudgetExceeded,
+    /// The configuration has a setting that the chosen method can't use
+    Unsupported,
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SolveError::NotFound => write!(f, "No solution found within the maximum depth"),
+            SolveError::TimedOut => write!(f, "Timed out before finding a solution"),
+            SolveError::BudgetExceeded => write!(f, "Node budget exceeded before finding a solution"),
+            SolveError::Unsupported => write!(f, "The solving method does not support every configured setting"),
+        }
+    }
+}
+
+impl Error for SolveError {}
+
+/// All of the settings needed to build and run a solver
+///
+/// # Example
+/// ```
+/// use rubik::cube::Cube;
+/// use rubik::solver::{Method, SolverConfig};
+///
+/// let mut c = Cube::new();
+/// c.apply_moves("UR");
+///
+/// let config = SolverConfig {
+///     method: Method::IterativeDeepening,
+///     max_depth: 4,
+///     ..SolverConfig::default()
+/// };
+/// assert_eq!(c.solve_with(&config).unwrap().len(), 2);
+/// assert!(c.is_solved());
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SolverConfig {
+    /// The algorithm to use
+    pub method: Method,
+    /// The maximum number of moves in a solution. The two-phase algorithm
+    /// treats this as the length that's short enough to stop searching.
+    pub max_depth: u8,
+    /// How long to search before giving up, or `None` to search until done.
+    /// Only iterative deepening supports a timeout.
+    pub timeout: Option<Duration>,
+    /// The moves a solution may contain, or `None` for the moves that count
+    /// as one move in `metric`. Only iterative deepening supports a move set.
+    pub move_set: Option<Vec<Move>>,
+    /// The metric solutions are optimal in (see `IDSolver::with_metric`). Only
+    /// used by iterative deepening; IDA* always counts quarter turns and the
+    /// two-phase algorithm always counts half turns.
+    pub metric: Metric,
+    /// How many cube states to visit before giving up, or `None` for no limit.
+    /// Only iterative deepening supports a node budget.
+    pub node_budget: Option<u64>,
+}
+
+impl Default for SolverConfig {
+    /// By default, use iterative deepening in the half turn metric with no
+    /// timeout, the same as `IDSolver::new`
+    fn default() -> SolverConfig {
+        SolverConfig {
+            method: Method::IterativeDeepening,
+            max_depth: Metric::HalfTurn.gods_number(),
+            timeout: None,
+            move_set: None,
+            metric: Metric::HalfTurn,
+            node_budget: None,
+        }
+    }
+}
+
+impl SolverConfig {
+    /// Build the configured solver and use it to find a solution for a cube.
+    /// It's an error to set a timeout, move set, or node budget for IDA* or
+    /// the two-phase algorithm, since they can't use them.
+    pub fn find_solution(&self, cube: &Cube) -> Result<Vec<Move>, SolveError> {
+        let limited = self.timeout.is_some() || self.move_set.is_some() || self.node_budget.is_some();
+        if limited && (self.method == Method::IDAStar || self.method == Method::TwoPhase) {
+            return Err(SolveError::Unsupported);
+        }
+        if cube.is_solved() {
+            return Ok(vec![]);
+        }
+
+        let (solution, timed_out, budget_exceeded) = match self.method {
+            Method::Null => (NullSolver::new().find_solution(cube), false, false),
+            Method::IterativeDeepening => {
+                let mut solver = IDSolver {
+                    max_depth: self.max_depth,
+                    timeout: self.timeout,
+                    node_budget: self.node_budget,
+                    ..IDSolver::with_metric(self.metric)
+                };
+                if let Some(ref moves) = self.move_set {
+                    solver.moves = moves.clone();
+                }
+                (solver.find_solution(cube), solver.timed_out, solver.budget_exceeded)
+            },
+            Method::IDAStar => (IDAStarSolver::with_max_depth(self.max_depth).find_solution(cube), false, false),
+            Method::TwoPhase => (TwoPhaseSolver::with_max_length(self.max_depth).find_solution(cube), false, false),
+        };
+
+        if !solution.is_empty() {
+            Ok(solution)
+        } else if timed_out {
+            Err(SolveError::TimedOut)
+        } else if budget_exceeded {
+            Err(SolveError::BudgetExceeded)
+        } else {
+            Err(SolveError::NotFound)
+        }
+    }
+}
+
+/// Determine whether a solution solves a scramble, i.e. applying the scramble
+/// and then the solution to a solved cube leaves it solved. The solution
+/// doesn't have to be short, just correct.
+///
+/// # Example
+/// ```
+/// use rubik::cube::Move;
+/// use rubik::solver::verifies_solution;
+///
+/// let scramble = [Move::F, Move::R];
+/// assert!(verifies_solution(&scramble, &[Move::RPrime, Move::FPrime]));
+/// assert!(!verifies_solution(&scramble, &[Move::FPrime, Move::RPrime]));
+/// ```
+pub fn verifies_solution(scramble: &[Move], solution: &[Move]) -> bool {
+    let mut cube = Cube::new();
+    for &m in scramble {
+        cube.apply_move(m);
+    }
+    solves(&cube, solution)
+}
+
+/// Determine whether a sequence of moves solves a cube
+fn solves(cube: &Cube, moves: &[Move]) -> bool {
+    let mut c = *cube;
+    for &m in moves {
+        c.apply_move(m);
+    }
+    c.is_solved()
+}
+
+/// Solver that tries one solver and falls back to another if the first one
+/// fails, i.e. it returns moves that don't solve the cube (solvers return no
+/// moves when they give up)
+///
+/// # Example
+/// ```
+/// use rubik::cube::Cube;
+/// use rubik::solver::{FallbackSolver, IDSolver, NullSolver};
+///
+/// let mut c = Cube::new();
+/// let mut solver = FallbackSolver::new(NullSolver::new(), IDSolver::new());
+///
+/// c.apply_moves("FU");
+/// c.solve(&mut solver);
+///
+/// assert!(c.is_solved());
+/// ```
+pub struct FallbackSolver<A: Solver, B: Solver> {
+    first: A,
+    fallback: B,
+}
+
+impl<A: Solver, B: Solver> FallbackSolver<A, B> {
+    /// Create a solver that tries `first` and then `fallback`
+    pub fn new(first: A, fallback: B) -> FallbackSolver<A, B> {
+        FallbackSolver {
+            first,
+            fallback,
+        }
+    }
+}
+
+impl<A: Solver, B: Solver> Solver for FallbackSolver<A, B> {
+    fn find_solution(&mut self, cube: &Cube) -> Vec<Move> {
+        let solution = self.first.find_solution(cube);
+        if solves(cube, &solution) {
+            solution
+        } else {
+            self.fallback.find_solution(cube)
+        }
+    }
+
+    /// Reports the solutions of whichever solver is running
+    fn find_solution_streaming(&mut self, cube: &Cube, on_solution: &mut dyn FnMut(&[Move])) -> Vec<Move> {
+        let solution = self.first.find_solution_streaming(cube, on_solution);
+        if solves(cube, &solution) {
+            solution
+        } else {
+            self.fallback.find_solution_streaming(cube, on_solution)
+        }
+    }
+
+    /// Reports the progress of whichever solver is running
+    fn find_solution_with_progress(&mut self, cube: &Cube, on_progress: &mut dyn FnMut(u8, u64)) -> Vec<Move> {
+        let solution = self.first.find_solution_with_progress(cube, on_progress);
+        if solves(cube, &solution) {
+            solution
+        } else {
+            self.fallback.find_solution_with_progress(cube, on_progress)
+        }
+    }
+}
+
+/// Solver that tries a list of solvers in order and returns the first solution
+/// that actually solves the cube, or no moves if none of them do
+///
+/// # Example
+/// ```
+/// use rubik::cube::Cube;
+/// use rubik::solver::{FirstOf, IDSolver, NullSolver};
+///
+/// let mut c = Cube::new();
+/// let mut solver = FirstOf::new(vec![
+///     Box::new(NullSolver::new()),
+///     Box::new(IDSolver::with_max_depth(1)),
+///     Box::new(IDSolver::with_max_depth(3)),
+/// ]);
+///
+/// c.apply_moves("FU");
+/// assert_eq!(c.solve(&mut solver).len(), 2);
+/// assert!(c.is_solved());
+/// ```
+pub struct FirstOf {
+    solvers: Vec<Box<dyn Solver>>,
+}
+
+impl FirstOf {
+    /// Create a solver that tries each of the given solvers in order
+    pub fn new(solvers: Vec<Box<dyn Solver>>) -> FirstOf {
+        FirstOf {
+            solvers,
+        }
+    }
+}
+
+impl Solver for FirstOf {
+    fn find_solution(&mut self, cube: &Cube) -> Vec<Move> {
+        for solver in &mut self.solvers {
+            let solution = solver.find_solution(cube);
+            if solves(cube, &solution) {
+                return solution;
+            }
+        }
+
+        vec![]
+    }
+
+    /// Reports the solutions of whichever solver is running
+    fn find_solution_streaming(&mut self, cube: &Cube, on_solution: &mut dyn FnMut(&[Move])) -> Vec<Move> {
+        for solver in &mut self.solvers {
+            let solution = solver.find_solution_streaming(cube, on_solution);
+            if solves(cube, &solution) {
+                return solution;
+            }
+        }
+
+        vec![]
+    }
+
+    /// Reports the progress of whichever solver is running
+    fn find_solution_with_progress(&mut self, cube: &Cube, on_progress: &mut dyn FnMut(u8, u64)) -> Vec<Move> {
+        for solver in &mut self.solvers {
+            let solution = solver.find_solution_with_progress(cube, on_progress);
+            if solves(cube, &solution) {
+                return solution;
+            }
+        }
+
+        vec![]
+    }
+}
+
+/// Solver that remembers the solutions another solver finds, so solving the
+/// same cube again returns the stored solution without searching. Only
+/// solutions that actually solve the cube are stored, so a solver that gave up
+/// (e.g. because of a timeout) gets to try again.
+///
+/// # Example
+/// ```
+/// use rubik::cube::Cube;
+/// use rubik::solver::{CachingSolver, IDSolver, Solver};
+///
+/// let mut c = Cube::new();
+/// c.apply_moves("FRU");
+///
+/// let mut solver = CachingSolver::new(IDSolver::new());
+/// let solution = solver.find_solution(&c);
+/// assert_eq!(solver.find_solution(&c), solution);
+/// assert_eq!(solver.len(), 1);
+/// ```
+pub struct CachingSolver<T: Solver> {
+    inner: T,
+    solutions: HashMap<Cube, Vec<Move>>,
+}
+
+impl<T: Solver> CachingSolver<T> {
+    /// Create a solver that caches the solutions found by `inner`
+    pub fn new(inner: T) -> CachingSolver<T> {
+        CachingSolver {
+            inner,
+            solutions: HashMap::new(),
+        }
+    }
+
+    /// Get the solver that finds solutions that aren't in the cache
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Get the number of cached solutions
+    pub fn len(&self) -> usize {
+        self.solutions.len()
+    }
+
+    /// Determine whether there are no cached solutions
+    pub fn is_empty(&self) -> bool {
+        self.solutions.is_empty()
+    }
+
+    /// Forget every cached solution
+    pub fn clear(&mut self) {
+        self.solutions.clear();
+    }
+}
+
+impl<T: Solver> Solver for CachingSolver<T> {
+    fn find_solution(&mut self, cube: &Cube) -> Vec<Move> {
+        if let Some(solution) = self.solutions.get(cube) {
+            return solution.clone();
+        }
+
+        let solution = self.inner.find_solution(cube);
+        if solves(cube, &solution) {
+            self.solutions.insert(*cube, solution.clone());
+        }
+
+        solution
+    }
+
+    /// A cached solution is reported once; otherwise the inner solver reports
+    /// its solutions as it finds them
+    fn find_solution_streaming(&mut self, cube: &Cube, on_solution: &mut dyn FnMut(&[Move])) -> Vec<Move> {
+        if let Some(solution) = self.solutions.get(cube) {
+            on_solution(solution);
+            return solution.clone();
+        }
+
+        let solution = self.inner.find_solution_streaming(cube, on_solution);
+        if solves(cube, &solution) {
+            self.solutions.insert(*cube, solution.clone());
+        }
+
+        solution
+    }
+
+    /// A cached solution needs no search, so there's no progress to report
+    fn find_solution_with_progress(&mut self, cube: &Cube, on_progress: &mut dyn FnMut(u8, u64)) -> Vec<Move> {
+        if let Some(solution) = self.solutions.get(cube) {
+            return solution.clone();
+        }
+
+        let solution = self.inner.find_solution_with_progress(cube, on_progress);
+        if solves(cube, &solution) {
+            self.solutions.insert(*cube, solution.clone());
+        }
+
+        solution
+    }
+
+    fn lower_bound(&self, cube: &Cube) -> u8 {
+        self.inner.lower_bound(cube)
+    }
+}
+
+/// How a solver's `lower_bound` compares to the real distance to solved
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ErrorStats {
+    /// The number of cubes measured
+    pub samples: usize,
+    /// The average of the optimal solution length minus the lower bound, over
+    /// the cubes whose lower bound wasn't an overestimate
+    pub mean_underestimate: f64,
+    /// The largest difference between the optimal solution length and the
+    /// lower bound
+    pub max_underestimate: u8,
+    /// The number of cubes whose lower bound was longer than their optimal
+    /// solution, i.e. where the estimate wasn't admissible
+    pub overestimates: usize,
+}
+
+/// Measure how close a solver's `lower_bound` is to the optimal solution length
+/// for a sample of cubes. The solver is also used to find the solutions, so it
+/// should be one that finds optimal solutions, like `IDSolver`. Cubes the
+/// solver can't solve are skipped.
+///
+/// # Example
+/// ```
+/// use rubik::cube::Cube;
+/// use rubik::solver::{heuristic_error_stats, IDSolver};
+///
+/// let mut a = Cube::new();
+/// a.apply_moves("R");
+/// let mut b = Cube::new();
+/// b.apply_moves("RU");
+///
+/// let stats = heuristic_error_stats(&[a, b], &mut IDSolver::new());
+/// assert_eq!(stats.samples, 2);
+/// assert_eq!(stats.overestimates, 0);
+/// ```
+pub fn heuristic_error_stats<T: Solver>(cubes: &[Cube], solver: &mut T) -> ErrorStats {
+    let mut stats = ErrorStats {
+        samples: 0,
+        mean_underestimate: 0.0,
+        max_underestimate: 0,
+        overestimates: 0,
+    };
+    let mut total = 0u64;
+
+    for cube in cubes {
+        let solution = solver.find_solution(cube);
+        if !solves(cube, &solution) {
+            continue;
+        }
+
+        let optimal = solution.len() as u8;
+        let bound = solver.lower_bound(cube);
+        stats.samples += 1;
+        if bound > optimal {
+            stats.overestimates += 1;
+        } else {
+            total += u64::from(optimal - bound);
+            stats.max_underestimate = stats.max_underestimate.max(optimal - bound);
+        }
+    }
+
+    let admissible = stats.samples - stats.overestimates;
+    if admissible > 0 {
+        stats.mean_underestimate = total as f64 / admissible as f64;
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests;
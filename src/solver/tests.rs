@@ -0,0 +1,471 @@
+use super::*;
+
+/// Get the pattern database estimate for a cube after some moves
+fn estimate_after(moves: &str) -> (u8, u8) {
+    let tables = pattern_tables();
+    let mut cube = Cube::new();
+    cube.apply_moves(moves);
+    let (co, eo, cp) = coordinates(&cube);
+
+    (
+        tables.orientation_distance[co as usize * EDGE_ORIENTATIONS + eo as usize],
+        tables.corner_permutation_distance[cp as usize],
+    )
+}
+
+#[test]
+fn test_pattern_tables_known_distances() {
+    assert_eq!(estimate_after(""), (0, 0));
+    // U and D don't change any orientations
+    assert_eq!(estimate_after("U"), (0, 1));
+    assert_eq!(estimate_after("UD'"), (0, 2));
+    assert_eq!(estimate_after("R"), (1, 1));
+    assert_eq!(estimate_after("F"), (1, 1));
+    // A half turn restores the orientations but not the positions
+    assert_eq!(estimate_after("RR"), (0, 2));
+    assert_eq!(estimate_after("FF"), (0, 2));
+}
+
+#[test]
+fn test_pattern_tables_deterministic() {
+    let a = pattern_tables();
+    let b = PatternTables::new();
+
+    assert!(a.orientation_distance == b.orientation_distance);
+    assert!(a.corner_permutation_distance == b.corner_permutation_distance);
+    assert!(a.corner_permutation_moves == b.corner_permutation_moves);
+}
+
+#[test]
+fn test_pattern_tables_cover_every_state() {
+    let tables = pattern_tables();
+    let count = |table: &[u8], d: u8| table.iter().filter(|&&x| x == d).count();
+
+    assert!(!tables.orientation_distance.contains(&u8::MAX));
+    assert!(!tables.corner_permutation_distance.contains(&u8::MAX));
+    assert_eq!(count(&tables.orientation_distance, 0), 1);
+    assert_eq!(count(&tables.corner_permutation_distance, 0), 1);
+    assert_eq!(count(&tables.corner_permutation_distance, 1), 12);
+    assert_eq!(tables.corner_permutation_distance.iter().max(), Some(&8));
+    assert_eq!(tables.orientation_distance.iter().max(), Some(&10));
+}
+
+#[test]
+fn test_idastar_lower_bound_admissible() {
+    let cubes: Vec<Cube> = ["R", "FU", "RUR'", "FRL'", "UFRB"].iter().map(|moves| {
+        let mut c = Cube::new();
+        c.apply_moves(moves);
+        c
+    }).collect();
+
+    let stats = heuristic_error_stats(&cubes, &mut IDAStarSolver::new());
+    assert_eq!(stats.samples, cubes.len());
+    assert_eq!(stats.overestimates, 0);
+}
+
+#[test]
+fn test_error_stats_skip_overestimates() {
+    // A solver whose lower bound is always two moves
+    struct Overestimating(IDSolver);
+    impl Solver for Overestimating {
+        fn find_solution(&mut self, cube: &Cube) -> Vec<Move> {
+            self.0.find_solution(cube)
+        }
+
+        fn lower_bound(&self, _: &Cube) -> u8 {
+            2
+        }
+    }
+
+    let cubes: Vec<Cube> = ["R", "RU", "RUF"].iter().map(|moves| {
+        let mut c = Cube::new();
+        c.apply_moves(moves);
+        c
+    }).collect();
+
+    let stats = heuristic_error_stats(&cubes, &mut Overestimating(IDSolver::new()));
+    assert_eq!(stats.samples, 3);
+    assert_eq!(stats.overestimates, 1);
+    assert_eq!(stats.max_underestimate, 1);
+    assert_eq!(stats.mean_underestimate, 0.5);
+}
+
+#[test]
+fn test_idastar_matches_idsolver() {
+    for moves in &["FRU", "RUR'U'", "FB'LR'"] {
+        let mut c = Cube::new();
+        c.apply_moves(moves);
+
+        let optimal = IDSolver::with_metric(Metric::QuarterTurn).find_solution(&c).len();
+        let solution = IDAStarSolver::new().find_solution(&c);
+        assert!(solves(&c, &solution));
+        assert_eq!(solution.len(), optimal);
+    }
+}
+
+#[test]
+fn test_idastar_solves_deep_scramble() {
+    let mut c = Cube::new();
+    c.apply_moves("FRUBLDF'R'U'B'");
+
+    let solution = IDAStarSolver::new().find_solution(&c);
+    assert!(solves(&c, &solution));
+    assert!(solution.len() <= 10);
+}
+
+#[test]
+fn test_idastar_max_depth() {
+    let mut c = Cube::new();
+    c.apply_moves("FRU");
+
+    assert_eq!(IDAStarSolver::with_max_depth(2).find_solution(&c), vec![]);
+    assert_eq!(IDAStarSolver::with_max_depth(3).find_solution(&c).len(), 3);
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_parallel_idastar_matches_idastar() {
+    let mut c = Cube::new();
+    c.apply_moves("FRUBLDF'R'U'");
+
+    let expected = IDAStarSolver::new().find_solution(&c);
+    for &threads in &[1, 3, 12] {
+        let solution = ParallelIDAStarSolver::new().with_threads(threads).find_solution(&c);
+        assert!(solves(&c, &solution));
+        assert_eq!(solution.len(), expected.len());
+    }
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_parallel_idastar_edge_cases() {
+    assert_eq!(ParallelIDAStarSolver::new().find_solution(&Cube::new()), vec![]);
+
+    let mut c = Cube::new();
+    c.apply_moves("FRU");
+    assert_eq!(ParallelIDAStarSolver::with_max_depth(2).find_solution(&c), vec![]);
+    assert_eq!(ParallelIDAStarSolver::with_max_depth(3).find_solution(&c).len(), 3);
+    assert_eq!(ParallelIDAStarSolver::new().with_threads(0).find_solution(&c).len(), 3);
+}
+
+#[test]
+fn test_idsolver_pruned_depth_six() {
+    // Without pruning this takes well over the timeout in a debug build
+    let mut c = Cube::new();
+    c.apply_moves("FR2UB2LD'");
+
+    let mut move_set = ALL_MOVES.to_vec();
+    move_set.extend(&[Move::F2, Move::R2, Move::U2, Move::B2, Move::L2, Move::D2]);
+    let config = SolverConfig {
+        move_set: Some(move_set),
+        timeout: Some(Duration::from_secs(10)),
+        ..SolverConfig::default()
+    };
+
+    let solution = config.find_solution(&c).unwrap();
+    assert_eq!(solution.len(), 6);
+    assert!(solves(&c, &solution));
+}
+
+#[test]
+fn test_redundant_moves() {
+    let with_half_turns = [Move::F, Move::F2, Move::R];
+
+    assert!(!redundant(&[], Move::F, &ALL_MOVES));
+    assert!(redundant(&[Move::F], Move::FPrime, &ALL_MOVES));
+    assert!(!redundant(&[Move::F], Move::F, &ALL_MOVES));
+    assert!(redundant(&[Move::F, Move::F], Move::F, &ALL_MOVES));
+    assert!(redundant(&[Move::F], Move::F, &with_half_turns));
+    assert!(!redundant(&[Move::R], Move::R, &with_half_turns));
+    // Opposite faces are only tried in one order
+    assert!(redundant(&[Move::B], Move::F, &ALL_MOVES) != redundant(&[Move::F], Move::B, &ALL_MOVES));
+    assert!(!redundant(&[Move::F], Move::U, &ALL_MOVES));
+}
+
+#[test]
+fn test_two_phase_solves_random_scrambles() {
+    let mut total = 0;
+    for seed in 0..50 {
+        let mut c = Cube::new();
+        c.scramble_from_seed(seed, 30);
+
+        let solution = TwoPhaseSolver::new().find_solution(&c);
+        assert!(solves(&c, &solution), "seed {} wasn't solved", seed);
+        assert!(solution.len() <= 24);
+        total += solution.len();
+    }
+
+    assert!(total < 25 * 50);
+}
+
+#[test]
+fn test_two_phase_short_scrambles() {
+    assert_eq!(TwoPhaseSolver::new().find_solution(&Cube::new()), vec![]);
+
+    let mut c = Cube::new();
+    c.apply_moves("R2U'");
+    assert_eq!(TwoPhaseSolver::new().find_solution(&c), vec![Move::U, Move::R2]);
+}
+
+#[test]
+fn test_two_phase_invalid_cube() {
+    let mut c = Cube::new();
+    c.apply_moves("M");
+
+    assert_eq!(TwoPhaseSolver::new().find_solution(&c), vec![]);
+}
+
+#[test]
+fn test_idsolver_default_bound() {
+    let solver = IDSolver::new();
+    assert_eq!(solver.max_depth, 20);
+    assert_eq!(solver.moves, FACE_TURNS.to_vec());
+
+    let solver = IDSolver::with_metric(Metric::QuarterTurn);
+    assert_eq!(solver.max_depth, 26);
+    assert_eq!(solver.moves, ALL_MOVES.to_vec());
+}
+
+#[test]
+fn test_idsolver_metrics() {
+    let mut c = Cube::new();
+    c.apply_moves("R2U'");
+
+    let htm = IDSolver::new().find_solution(&c);
+    assert!(solves(&c, &htm));
+    assert_eq!(htm, vec![Move::U, Move::R2]);
+    assert!(htm.len() <= Metric::HalfTurn.gods_number() as usize);
+
+    let qtm = IDSolver::with_metric(Metric::QuarterTurn).find_solution(&c);
+    assert!(solves(&c, &qtm));
+    assert_eq!(qtm.len(), 3);
+}
+
+/// Solver that counts how many times it's asked for a solution
+struct CountingSolver {
+    inner: IDSolver,
+    calls: usize,
+}
+
+impl Solver for CountingSolver {
+    fn find_solution(&mut self, cube: &Cube) -> Vec<Move> {
+        self.calls += 1;
+        self.inner.find_solution(cube)
+    }
+}
+
+#[test]
+fn test_caching_solver() {
+    let mut solver = CachingSolver::new(CountingSolver { inner: IDSolver::new(), calls: 0 });
+    assert!(solver.is_empty());
+
+    let mut cube = Cube::new();
+    cube.apply_moves("FR'U");
+    let first = solver.find_solution(&cube);
+    let second = solver.find_solution(&cube);
+    assert_eq!(first, second);
+    assert_eq!(first.len(), 3);
+    assert_eq!(solver.inner().calls, 1);
+    assert_eq!(solver.len(), 1);
+
+    // The same state reached another way is a cache hit too
+    let mut same = Cube::new();
+    same.apply_moves("FR'UD2D2");
+    assert_eq!(solver.find_solution(&same), first);
+    assert_eq!(solver.inner().calls, 1);
+
+    solver.clear();
+    assert_eq!(solver.find_solution(&cube), first);
+    assert_eq!(solver.inner().calls, 2);
+}
+
+#[test]
+fn test_caching_solver_skips_failures() {
+    let mut solver = CachingSolver::new(IDSolver::with_max_depth(1));
+
+    let mut cube = Cube::new();
+    cube.apply_moves("FR");
+    assert_eq!(solver.find_solution(&cube), vec![]);
+    assert!(solver.is_empty());
+}
+
+#[test]
+fn test_idsolver_node_budget() {
+    use std::time::Instant;
+
+    let mut cube = Cube::new();
+    cube.apply_moves("D2U'R2UF2DR2U'B2L'D'BR'D2FLU'B'R'F");
+
+    let start = Instant::now();
+    let mut solver = IDSolver::with_node_budget(10_000);
+    assert_eq!(solver.find_solution(&cube), vec![]);
+    assert!(solver.budget_exceeded());
+    assert!(start.elapsed().as_secs() < 5);
+
+    // A big enough budget finds the solution, and the flag is reset
+    let mut easy = Cube::new();
+    easy.apply_moves("FRU");
+    assert_eq!(solver.find_solution(&easy).len(), 3);
+    assert!(!solver.budget_exceeded());
+
+    // A search that fails within its budget didn't exceed it
+    let mut shallow = IDSolver { max_depth: 1, ..IDSolver::with_node_budget(10_000) };
+    assert_eq!(shallow.find_solution(&cube), vec![]);
+    assert!(!shallow.budget_exceeded());
+}
+
+#[test]
+fn test_idsolver_progress() {
+    let mut cube = Cube::new();
+    cube.apply_moves("FRU");
+
+    let mut progress = Vec::new();
+    let solution = IDSolver::new().find_solution_with_progress(&cube, &mut |depth, nodes| progress.push((depth, nodes)));
+    assert_eq!(solution.len(), 3);
+
+    // Each depth is reported once, before it's searched
+    let depths: Vec<u8> = progress.iter().map(|&(depth, _)| depth).collect();
+    assert_eq!(depths, vec![1, 2, 3]);
+    assert_eq!(progress[0].1, 0);
+    assert_eq!(progress[1].1, 18);
+    assert!(progress[2].1 > progress[1].1);
+}
+
+#[test]
+fn test_default_progress() {
+    let mut called = false;
+    let solution = NullSolver::new().find_solution_with_progress(&Cube::new(), &mut |_, _| called = true);
+    assert_eq!(solution, vec![]);
+    assert!(!called);
+}
+
+#[test]
+fn test_combined_solvers_progress() {
+    let mut cube = Cube::new();
+    cube.apply_moves("FRU");
+    let depths = |solver: &mut dyn Solver| {
+        let mut depths = Vec::new();
+        solver.find_solution_with_progress(&cube, &mut |depth, _| depths.push(depth));
+        depths
+    };
+
+    assert_eq!(depths(&mut FallbackSolver::new(NullSolver::new(), IDSolver::new())), vec![1, 2, 3]);
+    assert_eq!(depths(&mut FallbackSolver::new(IDSolver::with_max_depth(1), IDSolver::new())), vec![1, 1, 2, 3]);
+    assert_eq!(depths(&mut FirstOf::new(vec![Box::new(NullSolver::new()), Box::new(IDSolver::new())])), vec![1, 2, 3]);
+
+    let mut caching = CachingSolver::new(IDSolver::new());
+    assert_eq!(depths(&mut caching), vec![1, 2, 3]);
+    assert!(depths(&mut caching).is_empty());
+}
+
+#[test]
+fn test_verifies_solution() {
+    let scramble = [Move::F, Move::R, Move::U2, Move::LPrime];
+
+    // The exact inverse
+    assert!(verifies_solution(&scramble, &[Move::L, Move::U2, Move::RPrime, Move::FPrime]));
+    // Missing the last move
+    assert!(!verifies_solution(&scramble, &[Move::L, Move::U2, Move::RPrime]));
+    // Longer than it needs to be, but still solves it
+    assert!(verifies_solution(&scramble, &[
+        Move::L, Move::U, Move::U, Move::D, Move::DPrime, Move::R2, Move::R, Move::FPrime, Move::B2, Move::B2,
+    ]));
+
+    assert!(verifies_solution(&[], &[]));
+    assert!(!verifies_solution(&scramble, &[]));
+}
+
+#[test]
+fn test_find_all_solutions() {
+    let mut c = Cube::new();
+    c.apply_moves("FR");
+
+    let mut solver = IDSolver::new();
+    assert_eq!(solver.find_all_solutions(&c, 1), Vec::<Vec<Move>>::new());
+    assert_eq!(solver.find_all_solutions(&c, 3), vec![vec![Move::RPrime, Move::FPrime]]);
+
+    assert_eq!(solver.find_all_solutions(&Cube::new(), 3), vec![vec![]]);
+}
+
+#[test]
+fn test_find_all_solutions_goal() {
+    use cube::Face;
+
+    let mut c = Cube::new();
+    c.apply_moves("R");
+
+    // Lots of ways to fix the cross, each listed once and shortest first
+    let solutions = IDSolver::with_goal(Goal::Cross(Face::D)).find_all_solutions(&c, 3);
+    assert_eq!(solutions[0], vec![Move::RPrime]);
+    assert!(solutions.len() > 1);
+    for (i, solution) in solutions.iter().enumerate() {
+        let mut solved = c;
+        solution.iter().for_each(|&m| solved.apply_move(m));
+        assert!(solved.satisfies(&Goal::Cross(Face::D)));
+        assert!(!solutions[i + 1..].contains(solution));
+        assert!(i == 0 || solutions[i - 1].len() <= solution.len());
+    }
+}
+
+#[test]
+fn test_find_all_solutions_quarter_turns() {
+    let mut c = Cube::new();
+    c.apply_moves("F2");
+
+    // Without half turns there are two ways to undo a half turn
+    let solutions = IDSolver::with_metric(Metric::QuarterTurn).find_all_solutions(&c, 2);
+    assert_eq!(solutions, vec![vec![Move::F, Move::F], vec![Move::FPrime, Move::FPrime]]);
+}
+
+#[test]
+fn test_find_all_solutions_budget() {
+    let mut c = Cube::new();
+    c.apply_moves("FRU");
+
+    let mut solver = IDSolver::with_node_budget(10);
+    assert!(solver.find_all_solutions(&c, 3).is_empty());
+    assert!(solver.budget_exceeded());
+}
+
+#[test]
+fn test_two_phase_streams_improvements() {
+    let mut c = Cube::new();
+    c.scramble_from_seed(2018, 25);
+
+    let mut found = Vec::new();
+    let solution = TwoPhaseSolver::with_max_length(20).find_solution_streaming(&c, &mut |s| found.push(s.to_vec()));
+
+    // Longer solutions are reported before the search settles on the best one
+    assert!(found.len() >= 2);
+    assert!(found.iter().all(|s| solves(&c, s)));
+    assert!(found.windows(2).all(|w| w[1].len() < w[0].len()));
+    assert_eq!(found.last(), Some(&solution));
+}
+
+#[test]
+fn test_idsolver_streams_solution() {
+    let mut c = Cube::new();
+    c.apply_moves("FRU");
+
+    let mut found = Vec::new();
+    let solution = IDSolver::new().find_solution_streaming(&c, &mut |s| found.push(s.to_vec()));
+    assert_eq!(found, vec![solution]);
+}
+
+#[test]
+fn test_combined_solvers_stream() {
+    let mut c = Cube::new();
+    c.scramble_from_seed(2018, 25);
+    let count = |solver: &mut dyn Solver| {
+        let mut found = 0;
+        solver.find_solution_streaming(&c, &mut |_| found += 1);
+        found
+    };
+
+    assert!(count(&mut FallbackSolver::new(NullSolver::new(), TwoPhaseSolver::with_max_length(20))) >= 2);
+    assert!(count(&mut FirstOf::new(vec![Box::new(NullSolver::new()), Box::new(TwoPhaseSolver::with_max_length(20))])) >= 2);
+
+    let mut caching = CachingSolver::new(TwoPhaseSolver::with_max_length(20));
+    assert!(count(&mut caching) >= 2);
+    assert_eq!(count(&mut caching), 1);
+}